@@ -0,0 +1,155 @@
+//! Operators vs the in-place ones (`*=`, `/=`, `mul_into`, `div_into`) in a hot loop, for values
+//! fitting into `u128` (stored inline, nothing allocates) and for wider ones.
+//!
+//! cargo bench --bench assign_ops
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ic_e8s::c::{E18s, E8s};
+use num_bigint::BigUint;
+
+const ITERATIONS: usize = 1_000;
+
+fn inputs() -> [(&'static str, E18s, E18s); 2] {
+    [
+        // 1.5 and 1.000001, products and quotients fit into u128
+        (
+            "u128",
+            E18s::from(1_500_000_000_000_000_000u128),
+            E18s::from(1_000_001_000_000_000_000u128),
+        ),
+        // ~10^40 raw, the generic BigUint path
+        (
+            "wide",
+            E18s::new(BigUint::from(u128::MAX) * 30u32),
+            E18s::from(1_000_001_000_000_000_000u128),
+        ),
+    ]
+}
+
+fn mul(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mul");
+
+    for (name, a, b) in inputs() {
+        group.bench_with_input(
+            BenchmarkId::new("operator", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        black_box(a * b);
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("mul_into", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                let mut out = E18s::zero();
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        a.mul_into(b, &mut out);
+                        black_box(&out);
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("mul_assign", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                let mut acc = a.clone();
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        acc.clone_from(a);
+                        acc *= b;
+                        black_box(&acc);
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn div(c: &mut Criterion) {
+    let mut group = c.benchmark_group("div");
+
+    for (name, a, b) in inputs() {
+        group.bench_with_input(
+            BenchmarkId::new("operator", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        black_box(a / b);
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("div_into", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                let mut out = E18s::zero();
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        a.div_into(b, &mut out);
+                        black_box(&out);
+                    }
+                })
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("div_assign", name),
+            &(&a, &b),
+            |bench, &(a, b)| {
+                let mut acc = a.clone();
+                bench.iter(|| {
+                    for _ in 0..ITERATIONS {
+                        acc.clone_from(a);
+                        acc /= b;
+                        black_box(&acc);
+                    }
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// A typical balance loop: compounding a per-period rate in place vs into new values
+fn compound(c: &mut Criterion) {
+    let rate = E8s::from(100_010_000u64);
+
+    c.bench_function("compound/operator", |bench| {
+        bench.iter(|| {
+            let mut balance = E8s::from_whole(1_000);
+            for _ in 0..ITERATIONS {
+                balance = &balance * &rate;
+            }
+            black_box(balance)
+        })
+    });
+
+    c.bench_function("compound/mul_assign", |bench| {
+        bench.iter(|| {
+            let mut balance = E8s::from_whole(1_000);
+            for _ in 0..ITERATIONS {
+                balance *= &rate;
+            }
+            black_box(balance)
+        })
+    });
+}
+
+criterion_group!(benches, mul, div, compound);
+criterion_main!(benches);
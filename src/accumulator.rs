@@ -0,0 +1,318 @@
+//! Summation of many amounts into a single growing buffer, without per-addition temporaries
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::{abs_diff_raw, c::ECs, d::EDs, uint::Uint};
+
+pub type E8sAccumulator = ECsAccumulator<8>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct ECsAccumulator<const D: usize> {
+    val: Uint,
+}
+
+impl<const D: usize> ECsAccumulator<D> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, amount: &ECs<D>) {
+        self.val += &amount.val;
+    }
+
+    /// Adds a raw value, e.g. a ledger balance in e8s
+    pub fn add_u64(&mut self, raw: u64) {
+        self.val += raw;
+    }
+
+    pub fn finish(self) -> ECs<D> {
+        ECs::new(self.val)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EDsAccumulator {
+    val: Uint,
+    decimals: u8,
+}
+
+impl EDsAccumulator {
+    pub fn new(decimals: u8) -> Self {
+        Self {
+            val: Uint::ZERO,
+            decimals,
+        }
+    }
+
+    /// Traps on incompatible decimal points
+    pub fn add(&mut self, amount: &EDs) {
+        if self.decimals != amount.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        self.val += &amount.val;
+    }
+
+    /// Adds a raw value with the accumulator's decimal points
+    pub fn add_u64(&mut self, raw: u64) {
+        self.val += raw;
+    }
+
+    pub fn finish(self) -> EDs {
+        EDs::new(self.val, self.decimals)
+    }
+}
+
+/// Error message of an unbalanced batch
+fn imbalance_error(debits: &str, credits: &str, dust: &str) -> String {
+    format!(
+        "Books don't balance: debits {} vs credits {} (dust tolerance {})",
+        debits, credits, dust
+    )
+}
+
+/// Balanced-books check of a batch: every operation records its debits and credits, and at
+/// the end they should sum up to the same value, give or take `dust` (e.g. rounding leftovers).
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct Conservation<const D: usize> {
+    debits: Uint,
+    credits: Uint,
+    dust: ECs<D>,
+}
+
+impl<const D: usize> Conservation<D> {
+    /// Pass zero dust to require an exact balance
+    pub fn new(dust: ECs<D>) -> Self {
+        Self {
+            debits: Uint::ZERO,
+            credits: Uint::ZERO,
+            dust,
+        }
+    }
+
+    pub fn debit(&mut self, amount: &ECs<D>) {
+        self.debits += &amount.val;
+    }
+
+    pub fn credit(&mut self, amount: &ECs<D>) {
+        self.credits += &amount.val;
+    }
+
+    pub fn debits(&self) -> ECs<D> {
+        ECs::new(self.debits.clone())
+    }
+
+    pub fn credits(&self) -> ECs<D> {
+        ECs::new(self.credits.clone())
+    }
+
+    /// `|debits - credits|`
+    pub fn imbalance(&self) -> ECs<D> {
+        ECs::new(abs_diff_raw(&self.debits, &self.credits))
+    }
+
+    /// Whether the imbalance is within the dust tolerance
+    pub fn is_balanced(&self) -> bool {
+        abs_diff_raw(&self.debits, &self.credits) <= self.dust.val
+    }
+
+    /// Fails with both totals, if the books don't balance
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_balanced() {
+            return Ok(());
+        }
+
+        Err(imbalance_error(
+            &self.debits().to_string(),
+            &self.credits().to_string(),
+            &self.dust.to_string(),
+        ))
+    }
+
+    /// Traps, if the books don't balance
+    pub fn assert_balanced(&self) {
+        if let Err(e) = self.check() {
+            unreachable!("{}", e);
+        }
+    }
+}
+
+/// `Conservation` with a dynamic number of decimal points
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EDsConservation {
+    debits: Uint,
+    credits: Uint,
+    dust: EDs,
+}
+
+impl EDsConservation {
+    /// Amounts should have the same decimal points as `dust`. Pass zero dust to require
+    /// an exact balance.
+    pub fn new(dust: EDs) -> Self {
+        Self {
+            debits: Uint::ZERO,
+            credits: Uint::ZERO,
+            dust,
+        }
+    }
+
+    fn check_decimals(&self, amount: &EDs) {
+        if self.dust.decimals != amount.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+    }
+
+    /// Traps on incompatible decimal points
+    pub fn debit(&mut self, amount: &EDs) {
+        self.check_decimals(amount);
+        self.debits += &amount.val;
+    }
+
+    /// Traps on incompatible decimal points
+    pub fn credit(&mut self, amount: &EDs) {
+        self.check_decimals(amount);
+        self.credits += &amount.val;
+    }
+
+    pub fn debits(&self) -> EDs {
+        EDs::new(self.debits.clone(), self.dust.decimals)
+    }
+
+    pub fn credits(&self) -> EDs {
+        EDs::new(self.credits.clone(), self.dust.decimals)
+    }
+
+    /// `|debits - credits|`
+    pub fn imbalance(&self) -> EDs {
+        EDs::new(
+            abs_diff_raw(&self.debits, &self.credits),
+            self.dust.decimals,
+        )
+    }
+
+    /// Whether the imbalance is within the dust tolerance
+    pub fn is_balanced(&self) -> bool {
+        abs_diff_raw(&self.debits, &self.credits) <= self.dust.val
+    }
+
+    /// Fails with both totals, if the books don't balance
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_balanced() {
+            return Ok(());
+        }
+
+        Err(imbalance_error(
+            &self.debits().to_string(),
+            &self.credits().to_string(),
+            &self.dust.to_string(),
+        ))
+    }
+
+    /// Traps, if the books don't balance
+    pub fn assert_balanced(&self) {
+        if let Err(e) = self.check() {
+            unreachable!("{}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::c::E8s;
+
+    #[test]
+    fn accumulates_past_u128() {
+        let mut acc = E8sAccumulator::new();
+        assert_eq!(acc.clone().finish(), E8s::zero());
+
+        let big = E8s::new(u128::MAX);
+        acc.add(&big);
+        acc.add(&big);
+        acc.add_u64(2);
+
+        assert_eq!(
+            acc.finish(),
+            E8s::new(BigUint::from(u128::MAX) * 2u32 + 2u32)
+        );
+    }
+
+    #[test]
+    fn eds_accumulator_keeps_decimals() {
+        let mut acc = EDsAccumulator::new(6);
+        assert_eq!(acc.clone().finish(), EDs::zero(6));
+
+        acc.add(&EDs::new(1_500_000u64, 6));
+        acc.add_u64(250_000);
+
+        let sum = acc.finish();
+        assert_eq!(sum, EDs::new(1_750_000u64, 6));
+        assert_eq!(sum.decimals, 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible decimal points")]
+    fn eds_accumulator_traps_on_other_decimals() {
+        EDsAccumulator::new(6).add(&EDs::new(1u64, 8));
+    }
+
+    #[test]
+    fn conservation_within_dust() {
+        let mut books = Conservation::new(E8s::new(2u64));
+        books.debit(&E8s::new(100u64));
+        books.credit(&E8s::new(33u64));
+        books.credit(&E8s::new(33u64));
+        books.credit(&E8s::new(33u64));
+
+        assert_eq!(books.imbalance(), E8s::new(1u64));
+        assert!(books.is_balanced());
+        books.assert_balanced();
+
+        books.credit(&E8s::new(5u64));
+        assert_eq!(books.debits(), E8s::new(100u64));
+        assert_eq!(books.credits(), E8s::new(104u64));
+        assert_eq!(books.imbalance(), E8s::new(4u64));
+        assert_eq!(
+            books.check(),
+            Err(
+                "Books don't balance: debits 0.00000100 vs credits 0.00000104 (dust tolerance 0.00000002)"
+                    .to_string()
+            )
+        );
+
+        let exact = Conservation::<8>::default();
+        assert!(exact.is_balanced());
+    }
+
+    #[test]
+    #[should_panic(expected = "Books don't balance")]
+    fn conservation_assert_traps() {
+        let mut books = Conservation::new(E8s::zero());
+        books.debit(&E8s::new(1u64));
+        books.assert_balanced();
+    }
+
+    #[test]
+    fn eds_conservation() {
+        let mut books = EDsConservation::new(EDs::zero(6));
+        books.debit(&EDs::new(3_000_000u64, 6));
+        books.credit(&EDs::new(1_000_000u64, 6));
+        assert!(books.check().is_err());
+
+        books.credit(&EDs::new(2_000_000u64, 6));
+        assert_eq!(books.imbalance(), EDs::zero(6));
+        assert_eq!(books.credits(), EDs::new(3_000_000u64, 6));
+        books.assert_balanced();
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible decimal points")]
+    fn eds_conservation_traps_on_other_decimals() {
+        EDsConservation::new(EDs::zero(6)).credit(&EDs::new(1u64, 8));
+    }
+}
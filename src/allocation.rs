@@ -0,0 +1,361 @@
+//! Splitting an amount into parts which sum up exactly to the original amount
+
+use alloc::vec::Vec;
+
+use crate::{c::ECs, uint::Uint};
+
+/// Splits the total into `n` equal parts. The remainder is distributed one unit each,
+/// starting from the first part. `None`, if `n` is zero.
+pub fn allocate<const D: usize>(total: &ECs<D>, n: usize) -> Option<Vec<ECs<D>>> {
+    if n == 0 {
+        return None;
+    }
+
+    let (part, rem) = total.val.div_rem(&Uint::from(n as u64));
+    let parts = (0..n)
+        .map(|i| {
+            if Uint::from(i as u64) < rem {
+                ECs::new(&part + 1u32)
+            } else {
+                ECs::new(part.clone())
+            }
+        })
+        .collect();
+
+    Some(parts)
+}
+
+/// Splits the total proportionally to the weights, using the largest remainder method:
+/// each part is rounded down, and the leftover units go one by one to parts with the biggest
+/// fractional remainders (earlier parts win ties). `None`, if the total weight is zero.
+pub fn allocate_weighted<const D: usize, const W: usize>(
+    total: &ECs<D>,
+    weights: &[ECs<W>],
+) -> Option<Vec<ECs<D>>> {
+    let total_weight = weights.iter().fold(Uint::ZERO, |acc, it| acc + &it.val);
+    if total_weight.is_zero() {
+        return None;
+    }
+
+    let mut parts = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    let mut allocated = Uint::ZERO;
+
+    for (i, weight) in weights.iter().enumerate() {
+        let (part, rem) = (&total.val * &weight.val).div_rem(&total_weight);
+
+        allocated += &part;
+        parts.push(part);
+        remainders.push((rem, i));
+    }
+
+    let leftover = &total.val - allocated;
+    // leftover < weights.len(), since each part lost less than one unit
+    let leftover = usize::try_from(leftover).unwrap();
+
+    remainders.sort_by(|(a, i), (b, j)| b.cmp(a).then(i.cmp(j)));
+    for (_, i) in remainders.into_iter().take(leftover) {
+        parts[i] += 1u32;
+    }
+
+    Some(parts.into_iter().map(ECs::new).collect())
+}
+
+/// What to do with the units left after rounding the payouts down
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RemainderPolicy {
+    /// Keep the remainder for the next distribution
+    #[default]
+    Carry,
+    /// Take the remainder out of circulation
+    Burn,
+    /// Add the remainder to the biggest payout (the earliest one on ties)
+    LargestShare,
+}
+
+/// Result of a [`Distribution`]. `payouts` are aligned with the weights (filtered out
+/// recipients get zero) and `payouts + carried + burned` is exactly the pot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payouts<const D: usize> {
+    pub payouts: Vec<ECs<D>>,
+    pub carried: ECs<D>,
+    pub burned: ECs<D>,
+}
+
+/// Builds a pro-rata distribution of a pot by weights
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distribution<const D: usize, const W: usize> {
+    pot: ECs<D>,
+    weights: Vec<ECs<W>>,
+    min_payout: ECs<D>,
+    policy: RemainderPolicy,
+}
+
+impl<const D: usize, const W: usize> Distribution<D, W> {
+    /// Defaults to no minimum payout and carrying the remainder
+    pub fn new(pot: ECs<D>, weights: Vec<ECs<W>>) -> Self {
+        Self {
+            pot,
+            weights,
+            min_payout: ECs::zero(),
+            policy: RemainderPolicy::default(),
+        }
+    }
+
+    /// Recipients, whose payout would be less than this, are excluded and their
+    /// share is distributed between the others
+    pub fn min_payout(mut self, min_payout: ECs<D>) -> Self {
+        self.min_payout = min_payout;
+        self
+    }
+
+    pub fn remainder(mut self, policy: RemainderPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn build(&self) -> Payouts<D> {
+        let mut eligible: Vec<bool> = self.weights.iter().map(|w| !w.val.is_zero()).collect();
+
+        // excluding recipients only increases the payouts of others, so this ends
+        let mut payouts = loop {
+            let total_weight = self
+                .weights
+                .iter()
+                .zip(&eligible)
+                .filter(|(_, e)| **e)
+                .fold(Uint::ZERO, |acc, (w, _)| acc + &w.val);
+
+            let payouts: Vec<Uint> = self
+                .weights
+                .iter()
+                .zip(&eligible)
+                .map(|(w, e)| {
+                    if *e {
+                        &self.pot.val * &w.val / &total_weight
+                    } else {
+                        Uint::ZERO
+                    }
+                })
+                .collect();
+
+            let mut changed = false;
+            for (p, e) in payouts.iter().zip(eligible.iter_mut()) {
+                if *e && *p < self.min_payout.val {
+                    *e = false;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break payouts;
+            }
+        };
+
+        let paid = payouts.iter().fold(Uint::ZERO, |acc, it| acc + it);
+        let remainder = &self.pot.val - paid;
+
+        let mut carried = Uint::ZERO;
+        let mut burned = Uint::ZERO;
+
+        match self.policy {
+            RemainderPolicy::Carry => carried = remainder,
+            RemainderPolicy::Burn => burned = remainder,
+            RemainderPolicy::LargestShare => {
+                let largest = eligible
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| **e)
+                    .map(|(i, _)| i)
+                    .reduce(|a, b| if payouts[b] > payouts[a] { b } else { a });
+
+                // nobody to give it to
+                match largest {
+                    Some(i) => payouts[i] += remainder,
+                    None => carried = remainder,
+                }
+            }
+        }
+
+        Payouts {
+            payouts: payouts.into_iter().map(ECs::new).collect(),
+            carried: ECs::new(carried),
+            burned: ECs::new(burned),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw<const D: usize>(values: &[u64]) -> Vec<ECs<D>> {
+        values.iter().map(|it| ECs::from(*it)).collect()
+    }
+
+    fn total<const D: usize>(p: &Payouts<D>) -> ECs<D> {
+        p.payouts.iter().sum::<ECs<D>>() + &p.carried + &p.burned
+    }
+
+    #[test]
+    fn allocate_spreads_the_remainder_from_the_start() {
+        let parts = allocate::<8>(&ECs::from(10u64), 3).unwrap();
+        assert_eq!(parts, raw(&[4, 3, 3]));
+
+        assert_eq!(
+            allocate::<8>(&ECs::from(2u64), 4).unwrap(),
+            raw(&[1, 1, 0, 0])
+        );
+        assert_eq!(allocate::<8>(&ECs::zero(), 2).unwrap(), raw(&[0, 0]));
+        assert_eq!(allocate::<8>(&ECs::from(10u64), 0), None);
+
+        let big = ECs::<8>::new(num_bigint::BigUint::from(u128::MAX) * 7u32 + 5u32);
+        let parts = allocate(&big, 7).unwrap();
+        assert_eq!(parts.iter().sum::<ECs<8>>(), big);
+        assert_eq!(&parts[4].val - &parts[5].val, Uint::from(1u64));
+    }
+
+    #[test]
+    fn allocate_weighted_uses_largest_remainders() {
+        // 100 * [1, 1, 1] / 3 = 33.33 each, the first part wins the tie
+        let parts = allocate_weighted::<8, 8>(&ECs::from(100u64), &raw(&[1, 1, 1])).unwrap();
+        assert_eq!(parts, raw(&[34, 33, 33]));
+
+        // exact shares leave nothing over
+        let parts = allocate_weighted::<8, 8>(&ECs::from(10u64), &raw(&[2, 3, 5])).unwrap();
+        assert_eq!(parts, raw(&[2, 3, 5]));
+
+        // 1.1, 3.3 and 6.6: the biggest remainder gets the unit
+        let parts = allocate_weighted::<8, 8>(&ECs::from(11u64), &raw(&[1, 3, 6])).unwrap();
+        assert_eq!(parts, raw(&[1, 3, 7]));
+
+        // zero weights get nothing, even the leftover
+        let parts = allocate_weighted::<8, 8>(&ECs::from(5u64), &raw(&[0, 1, 0, 1])).unwrap();
+        assert_eq!(parts, raw(&[0, 3, 0, 2]));
+    }
+
+    #[test]
+    fn allocate_weighted_sums_to_the_total() {
+        let weights = raw::<6>(&[7, 13, 0, 1, 999, 42, 5]);
+
+        for total in [0u64, 1, 6, 1_067, 99_999_999, u64::MAX] {
+            let total = ECs::<8>::from(total);
+            let parts = allocate_weighted(&total, &weights).unwrap();
+
+            assert_eq!(parts.len(), weights.len());
+            assert_eq!(parts.iter().sum::<ECs<8>>(), total);
+        }
+
+        assert_eq!(
+            allocate_weighted::<8, 8>(&ECs::from(5u64), &raw(&[0, 0])),
+            None
+        );
+        assert_eq!(allocate_weighted::<8, 8>(&ECs::from(5u64), &[]), None);
+    }
+
+    #[test]
+    fn distribution_excludes_small_payouts() {
+        let p = Distribution::<8, 8>::new(ECs::from(1_000u64), raw(&[1, 1, 98]))
+            .min_payout(ECs::from(50u64))
+            .build();
+
+        // 10 and 10 are below the minimum, so the last recipient takes the whole pot
+        assert_eq!(p.payouts, raw(&[0, 0, 1_000]));
+        assert_eq!(p.carried, ECs::zero());
+    }
+
+    #[test]
+    fn distribution_redistributes_excluded_shares() {
+        let p = Distribution::<8, 8>::new(ECs::from(1_000u64), raw(&[5, 30, 65]))
+            .min_payout(ECs::from(100u64))
+            .build();
+
+        // 50 is below the minimum, 1'000 is then split 30:65
+        assert_eq!(p.payouts, raw(&[0, 315, 684]));
+        assert_eq!(p.carried, ECs::from(1u64));
+    }
+
+    #[test]
+    fn distribution_with_everybody_excluded() {
+        for policy in [
+            RemainderPolicy::Carry,
+            RemainderPolicy::Burn,
+            RemainderPolicy::LargestShare,
+        ] {
+            let p = Distribution::<8, 8>::new(ECs::from(100u64), raw(&[1, 1]))
+                .min_payout(ECs::from(60u64))
+                .remainder(policy)
+                .build();
+
+            assert_eq!(p.payouts, raw(&[0, 0]));
+            assert_eq!(total(&p), ECs::from(100u64));
+        }
+    }
+
+    #[test]
+    fn distribution_with_zero_weights() {
+        let p = Distribution::<8, 8>::new(ECs::from(100u64), raw(&[0, 0, 0]))
+            .remainder(RemainderPolicy::LargestShare)
+            .build();
+
+        // nobody to give the remainder to, so it goes back to the pot
+        assert_eq!(p.payouts, raw(&[0, 0, 0]));
+        assert_eq!(p.carried, ECs::from(100u64));
+
+        let p = Distribution::<8, 8>::new(ECs::from(100u64), vec![]).build();
+        assert!(p.payouts.is_empty());
+        assert_eq!(p.carried, ECs::from(100u64));
+    }
+
+    #[test]
+    fn distribution_remainder_policies() {
+        let build = |policy| {
+            Distribution::<8, 8>::new(ECs::from(100u64), raw(&[1, 1, 1]))
+                .remainder(policy)
+                .build()
+        };
+
+        let carried = build(RemainderPolicy::Carry);
+        assert_eq!(carried.payouts, raw(&[33, 33, 33]));
+        assert_eq!(carried.carried, ECs::from(1u64));
+        assert_eq!(carried.burned, ECs::zero());
+
+        let burned = build(RemainderPolicy::Burn);
+        assert_eq!(burned.payouts, raw(&[33, 33, 33]));
+        assert_eq!(burned.carried, ECs::zero());
+        assert_eq!(burned.burned, ECs::from(1u64));
+
+        let largest = build(RemainderPolicy::LargestShare);
+        assert_eq!(largest.payouts, raw(&[34, 33, 33]));
+        assert_eq!(largest.carried, ECs::zero());
+    }
+
+    #[test]
+    fn distribution_sums_to_the_pot() {
+        let weights = [
+            raw::<12>(&[1, 2, 3, 4, 5, 6, 7]),
+            raw::<12>(&[999_999_999, 1, 1]),
+            raw::<12>(&[0, 17, 0, 23, u64::MAX]),
+        ];
+
+        for pot in [0u64, 1, 7, 1_000, 123_456_789, u64::MAX] {
+            for w in &weights {
+                for min in [0u64, 1, 100, 1_000_000] {
+                    for policy in [
+                        RemainderPolicy::Carry,
+                        RemainderPolicy::Burn,
+                        RemainderPolicy::LargestShare,
+                    ] {
+                        let p = Distribution::<8, 12>::new(ECs::from(pot), w.clone())
+                            .min_payout(ECs::from(min))
+                            .remainder(policy)
+                            .build();
+
+                        assert_eq!(p.payouts.len(), w.len());
+                        assert_eq!(total(&p), ECs::from(pot));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Constant-product (`x * y = k`) swap math, Uniswap V2 style. Intermediates are kept at full
+//! precision and every result is rounded in the pool's favor.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use num_bigint::BigUint;
+
+use crate::{
+    bps::{Bps, BPS_DENOMINATOR},
+    c::ECs,
+    rounding::{div_rounded, RoundingMode},
+};
+
+fn fee_multiplier(fee: Bps) -> Result<BigUint, String> {
+    if fee.0 >= BPS_DENOMINATOR {
+        return Err(format!("Invalid swap fee {}", fee));
+    }
+
+    Ok(BigUint::from(BPS_DENOMINATOR - fee.0))
+}
+
+/// How much is received for `amount_in`, the fee is taken from the input. Rounded down.
+pub fn get_amount_out<const DI: usize, const DO: usize>(
+    amount_in: &ECs<DI>,
+    reserve_in: &ECs<DI>,
+    reserve_out: &ECs<DO>,
+    fee: Bps,
+) -> Result<ECs<DO>, String> {
+    if reserve_in.val.is_zero() || reserve_out.val.is_zero() {
+        return Err("Insufficient liquidity".to_string());
+    }
+
+    let in_with_fee = &amount_in.val * fee_multiplier(fee)?;
+    let n = &in_with_fee * &reserve_out.val;
+    let d = &reserve_in.val * BigUint::from(BPS_DENOMINATOR) + in_with_fee;
+
+    Ok(ECs::new(n / d))
+}
+
+/// How much has to be paid to receive exactly `amount_out`, the fee is taken from the input.
+/// Rounded up. Fails, if the pool doesn't have enough liquidity.
+pub fn get_amount_in<const DI: usize, const DO: usize>(
+    amount_out: &ECs<DO>,
+    reserve_in: &ECs<DI>,
+    reserve_out: &ECs<DO>,
+    fee: Bps,
+) -> Result<ECs<DI>, String> {
+    if reserve_in.val.is_zero() || amount_out.val >= reserve_out.val {
+        return Err("Insufficient liquidity".to_string());
+    }
+
+    let n = &reserve_in.val * &amount_out.val * BigUint::from(BPS_DENOMINATOR);
+    let d = (&reserve_out.val - &amount_out.val) * fee_multiplier(fee)?;
+
+    Ok(ECs::new(div_rounded(&n, &d, RoundingMode::Up)))
+}
+
+/// The smallest acceptable output of a trade quoted at `expected`, given the slippage
+/// tolerance: `expected * (1 - tolerance)`, rounded up to protect the user
+pub fn min_output_with_slippage<const D: usize>(expected: &ECs<D>, tolerance: Bps) -> ECs<D> {
+    let multiplier = BigUint::from(BPS_DENOMINATOR.saturating_sub(tolerance.0));
+
+    ECs::new(div_rounded(
+        &(&expected.val * multiplier),
+        &BigUint::from(BPS_DENOMINATOR),
+        RoundingMode::Up,
+    ))
+}
+
+/// The biggest acceptable input of a trade quoted at `expected`, given the slippage
+/// tolerance: `expected * (1 + tolerance)`, rounded down to protect the user
+pub fn max_input_with_slippage<const D: usize>(expected: &ECs<D>, tolerance: Bps) -> ECs<D> {
+    let multiplier = BigUint::from(BPS_DENOMINATOR) + BigUint::from(tolerance.0);
+
+    ECs::new(&expected.val * multiplier / BPS_DENOMINATOR)
+}
+
+/// Price impact of swapping `amount_in` into a constant-product pool, as a fraction:
+/// `1 - execution_price / spot_price`, which is `amount_in / (reserve_in + amount_in)`
+/// (fees excluded). Rounded up, so the impact is never understated.
+pub fn price_impact<const D: usize>(amount_in: &ECs<D>, reserve_in: &ECs<D>) -> ECs<D> {
+    let d = &reserve_in.val + &amount_in.val;
+    if d.is_zero() {
+        return ECs::zero();
+    }
+
+    ECs::new(div_rounded(
+        &(&amount_in.val * ECs::<D>::base()),
+        &d.big(),
+        RoundingMode::Up,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::{E6s, E8s};
+
+    const FEE: Bps = Bps(30);
+
+    #[test]
+    fn amount_out_takes_the_fee_and_rounds_down() {
+        let (r_in, r_out) = (E8s::new(100_000_000_000u64), E6s::new(2_000_000_000u64));
+
+        // 1 of 1000 in, 0.3% fee: 2000 * 0.997 / 1000.997 = 1.99201396... out
+        let out = get_amount_out(&E8s::new(100_000_000u64), &r_in, &r_out, FEE).unwrap();
+        assert_eq!(out, E6s::new(1_992_013u64));
+
+        let no_fee = get_amount_out(&E8s::new(100_000_000u64), &r_in, &r_out, Bps(0)).unwrap();
+        assert_eq!(no_fee, E6s::new(1_998_001u64));
+        assert_eq!(
+            get_amount_out(&E8s::zero(), &r_in, &r_out, FEE).unwrap(),
+            E6s::zero()
+        );
+    }
+
+    #[test]
+    fn amount_in_round_trip_favors_the_pool() {
+        let (r_in, r_out) = (E8s::new(123_456_789_012u64), E6s::new(987_654_321u64));
+
+        for out in [1u64, 7, 1_000, 123_457, 50_000_000, 987_654_320] {
+            let out = E6s::new(out);
+            let amount_in = get_amount_in(&out, &r_in, &r_out, FEE).unwrap();
+
+            // paying the quoted input gives at least the requested output...
+            assert!(get_amount_out(&amount_in, &r_in, &r_out, FEE).unwrap() >= out);
+            // ...and it's the smallest such input
+            let less = &amount_in - &E8s::new(1u64);
+            assert!(get_amount_out(&less, &r_in, &r_out, FEE).unwrap() < out);
+
+            // `k` never decreases
+            let k = &r_in.val * &r_out.val;
+            assert!((&r_in.val + &amount_in.val) * (&r_out.val - &out.val) >= k);
+        }
+    }
+
+    #[test]
+    fn fails_without_liquidity_or_with_invalid_fee() {
+        let one = E8s::new(100_000_000u64);
+
+        assert!(get_amount_out(&one, &E8s::zero(), &one, FEE).is_err());
+        assert!(get_amount_out(&one, &one, &E8s::zero(), FEE).is_err());
+        assert!(get_amount_in(&one, &E8s::zero(), &one, FEE).is_err());
+        // can't drain the pool
+        assert!(get_amount_in(&one, &one, &one, FEE).is_err());
+        assert!(get_amount_out(&one, &one, &one, Bps(10_000)).is_err());
+        assert!(get_amount_in(&E8s::new(1u64), &one, &one, Bps(10_001)).is_err());
+    }
+
+    #[test]
+    fn slippage_bounds_protect_the_user() {
+        let expected = E8s::new(1_001u64);
+
+        // 1001 * 0.995 = 995.995
+        assert_eq!(
+            min_output_with_slippage(&expected, Bps(50)),
+            E8s::new(996u64)
+        );
+        // 1001 * 1.005 = 1006.005
+        assert_eq!(
+            max_input_with_slippage(&expected, Bps(50)),
+            E8s::new(1_006u64)
+        );
+
+        assert_eq!(min_output_with_slippage(&expected, Bps(0)), expected);
+        assert_eq!(max_input_with_slippage(&expected, Bps(0)), expected);
+        assert_eq!(
+            min_output_with_slippage(&expected, Bps(10_000)),
+            E8s::zero()
+        );
+        assert_eq!(
+            min_output_with_slippage(&expected, Bps(20_000)),
+            E8s::zero()
+        );
+        assert_eq!(
+            max_input_with_slippage(&expected, Bps(10_000)),
+            E8s::new(2_002u64)
+        );
+    }
+
+    #[test]
+    fn price_impact_is_rounded_up() {
+        let reserve = E8s::new(300_000_000u64);
+
+        // 1 / 4
+        assert_eq!(
+            price_impact(&E8s::new(100_000_000u64), &reserve),
+            E8s::new(25_000_000u64)
+        );
+        // 1 / 3 = 0.333333333...
+        assert_eq!(
+            price_impact(&E8s::new(150_000_000u64), &reserve),
+            E8s::new(33_333_334u64)
+        );
+        assert_eq!(price_impact(&E8s::zero(), &reserve), E8s::zero());
+        assert_eq!(price_impact(&E8s::zero(), &E8s::zero()), E8s::zero());
+        assert_eq!(price_impact(&E8s::new(1u64), &E8s::zero()), E8s::one());
+    }
+}
@@ -0,0 +1,295 @@
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    hash::Hash,
+    marker::PhantomData,
+    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+};
+
+use crate::{c::ECs, rate::Rate};
+
+/// Marker of a token, e.g. `struct Icp; impl TokenTag for Icp { const SYMBOL: &'static str = "ICP"; }`
+pub trait TokenTag {
+    const SYMBOL: &'static str;
+}
+
+/// An amount of a specific token. Amounts of different tokens can't be mixed in arithmetic,
+/// they can only be converted into each other at some rate.
+pub struct Amount<T: TokenTag, const D: usize>(pub ECs<D>, PhantomData<fn() -> T>);
+
+impl<T: TokenTag, const D: usize> Amount<T, D> {
+    pub fn new(value: ECs<D>) -> Self {
+        Self(value, PhantomData)
+    }
+
+    pub fn zero() -> Self {
+        Self::new(ECs::zero())
+    }
+
+    pub fn inner(&self) -> &ECs<D> {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> ECs<D> {
+        self.0
+    }
+
+    /// Converts into another token at the price of one `T` in `U`, rounding down only once,
+    /// see `Rate::convert`
+    pub fn convert<U: TokenTag, const DR: usize, const D1: usize>(
+        &self,
+        rate: &Rate<T, U, DR>,
+    ) -> Amount<U, D1> {
+        rate.convert(self)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Clone for Amount<T, D> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<T: TokenTag, const D: usize> Debug for Amount<T, D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Amount")
+            .field(&T::SYMBOL)
+            .field(&self.0)
+            .finish()
+    }
+}
+
+impl<T: TokenTag, const D: usize> Display for Amount<T, D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} {}", self.0, T::SYMBOL)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Default for Amount<T, D> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<T: TokenTag, const D: usize> PartialEq for Amount<T, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T: TokenTag, const D: usize> Eq for Amount<T, D> {}
+
+impl<T: TokenTag, const D: usize> PartialOrd for Amount<T, D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: TokenTag, const D: usize> Ord for Amount<T, D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Hash for Amount<T, D> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl<T: TokenTag, const D: usize> From<ECs<D>> for Amount<T, D> {
+    fn from(value: ECs<D>) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Add for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Amount::new(&self.0 + &rhs.0)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Add for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        (&self).add(&rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Add<&Amount<T, D>> for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn add(self, rhs: &Amount<T, D>) -> Self::Output {
+        (&self).add(rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Add<Amount<T, D>> for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn add(self, rhs: Amount<T, D>) -> Self::Output {
+        self.add(&rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> AddAssign<&Amount<T, D>> for Amount<T, D> {
+    fn add_assign(&mut self, rhs: &Amount<T, D>) {
+        self.0.add_assign(&rhs.0)
+    }
+}
+
+impl<T: TokenTag, const D: usize> AddAssign for Amount<T, D> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.add_assign(&rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Sub for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Amount::new(&self.0 - &rhs.0)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Sub for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        (&self).sub(&rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Sub<&Amount<T, D>> for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn sub(self, rhs: &Amount<T, D>) -> Self::Output {
+        (&self).sub(rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Sub<Amount<T, D>> for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn sub(self, rhs: Amount<T, D>) -> Self::Output {
+        self.sub(&rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> SubAssign<&Amount<T, D>> for Amount<T, D> {
+    fn sub_assign(&mut self, rhs: &Amount<T, D>) {
+        self.0.sub_assign(&rhs.0)
+    }
+}
+
+impl<T: TokenTag, const D: usize> SubAssign for Amount<T, D> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.sub_assign(&rhs)
+    }
+}
+
+/// Scaling by a plain (token-less) factor
+impl<T: TokenTag, const D: usize> Mul<&ECs<D>> for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn mul(self, rhs: &ECs<D>) -> Self::Output {
+        Amount::new(&self.0 * rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Mul<ECs<D>> for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn mul(self, rhs: ECs<D>) -> Self::Output {
+        (&self).mul(&rhs)
+    }
+}
+
+/// Scaling by a plain (token-less) factor
+impl<T: TokenTag, const D: usize> Div<&ECs<D>> for &Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn div(self, rhs: &ECs<D>) -> Self::Output {
+        Amount::new(&self.0 / rhs)
+    }
+}
+
+impl<T: TokenTag, const D: usize> Div<ECs<D>> for Amount<T, D> {
+    type Output = Amount<T, D>;
+
+    fn div(self, rhs: ECs<D>) -> Self::Output {
+        (&self).div(&rhs)
+    }
+}
+
+/// Ratio of two amounts of the same token is a plain (token-less) value
+impl<T: TokenTag, const D: usize> Div for &Amount<T, D> {
+    type Output = ECs<D>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        &self.0 / &rhs.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{format, string::ToString};
+
+    use super::*;
+    use crate::c::E8s;
+
+    struct Icp;
+
+    impl TokenTag for Icp {
+        const SYMBOL: &'static str = "ICP";
+    }
+
+    type IcpAmount = Amount<Icp, 8>;
+
+    fn icp(raw: u64) -> IcpAmount {
+        Amount::new(E8s::new(raw))
+    }
+
+    #[test]
+    fn same_token_math() {
+        assert_eq!(&icp(150) + &icp(50), icp(200));
+        assert_eq!(icp(150) - icp(50), icp(100));
+
+        let mut acc = IcpAmount::zero();
+        acc += icp(7);
+        acc -= &icp(2);
+        assert_eq!(acc, icp(5));
+        assert!(icp(2) > icp(1));
+        assert_eq!(IcpAmount::default(), IcpAmount::zero());
+
+        let half = E8s::new(50_000_000u64);
+        assert_eq!(&icp(300) * &half, icp(150));
+        assert_eq!(icp(300) / half, icp(600));
+        // the ratio of the same token is a plain value
+        assert_eq!(&icp(300) / &icp(200), E8s::new(150_000_000u64));
+    }
+
+    #[test]
+    fn formats_with_the_symbol() {
+        let a = icp(150_000_000);
+
+        assert_eq!(a.to_string(), "1.50000000 ICP");
+        assert_eq!(
+            format!("{:?}", a),
+            "Amount(\"ICP\", ECs { val: 150000000 })"
+        );
+        assert_eq!(a.inner(), &E8s::new(150_000_000u64));
+        assert_eq!(a.into_inner(), E8s::new(150_000_000u64));
+    }
+
+    #[test]
+    fn is_zero_cost() {
+        assert_eq!(
+            core::mem::size_of::<IcpAmount>(),
+            core::mem::size_of::<E8s>()
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Dutch auction pricing: the price moves from `start_price` at `start_ts` to `end_price` at
+//! `end_ts` and stays at the bounds outside of this interval. The endpoints are returned exactly
+//! and intermediate prices never leave the `[start_price, end_price]` range, whatever the
+//! rounding mode. Timestamps are in arbitrary, but consistent units (e.g. seconds or nanos).
+
+use num_bigint::BigUint;
+
+use crate::{
+    c::ECs,
+    math::{powf_raw, ten_pow, GUARD_DIGITS},
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+};
+
+/// How the price moves between the bounds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Decay {
+    /// By the same amount every unit of time
+    #[default]
+    Linear,
+    /// By the same fraction every unit of time: `start * (end / start)^(elapsed / duration)`
+    Exponential,
+}
+
+/// Price of a Dutch auction at `now`, rounded according to the mode. Traps, if `end_ts` is
+/// before `start_ts` or if an exponential decay has a zero price at either bound.
+pub fn dutch_price<const D: usize>(
+    start_price: &ECs<D>,
+    end_price: &ECs<D>,
+    start_ts: u64,
+    end_ts: u64,
+    now: u64,
+    decay: Decay,
+    mode: RoundingMode,
+) -> ECs<D> {
+    if end_ts < start_ts {
+        unreachable!("Auction can't end before it starts");
+    }
+
+    if decay == Decay::Exponential && (start_price.is_zero() || end_price.is_zero()) {
+        unreachable!("Exponential decay needs non-zero prices");
+    }
+
+    if now <= start_ts {
+        return start_price.clone();
+    }
+
+    if now >= end_ts {
+        return end_price.clone();
+    }
+
+    let elapsed = BigUint::from(now - start_ts);
+    let duration = BigUint::from(end_ts - start_ts);
+    let (start, end) = (&start_price.val, &end_price.val);
+
+    let price = match decay {
+        Decay::Linear => {
+            // start * (duration - elapsed) / duration + end * elapsed / duration
+            let n = start * (&duration - &elapsed) + end * &elapsed;
+
+            div_rounded(&n, &duration, mode)
+        }
+        Decay::Exponential => {
+            // enough decimal points for the factor to resolve a single unit of the start price
+            let decimals = start.max(end).bits() as usize * 31 / 100 + 1 + GUARD_DIGITS;
+            let scale = ten_pow(decimals);
+
+            let ratio = end * &scale / start;
+            let t = &elapsed * &scale / &duration;
+            let factor = powf_raw(&ratio, &t, decimals);
+
+            div_rounded(&(start * factor), &scale, mode)
+        }
+    };
+
+    ECs::new(Uint::from(price).clamp(start.min(end).clone(), start.max(end).clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn price(start: u64, end: u64, now: u64, decay: Decay, mode: RoundingMode) -> E8s {
+        dutch_price(
+            &E8s::from_whole(start),
+            &E8s::from_whole(end),
+            1_000,
+            1_100,
+            now,
+            decay,
+            mode,
+        )
+    }
+
+    #[test]
+    fn linear_decay() {
+        let at = |now| price(100, 50, now, Decay::Linear, RoundingMode::Down);
+
+        assert_eq!(at(0), E8s::from_whole(100));
+        assert_eq!(at(1_000), E8s::from_whole(100));
+        assert_eq!(at(1_025), E8s::new(8_750_000_000u64));
+        assert_eq!(at(1_050), E8s::from_whole(75));
+        assert_eq!(at(1_100), E8s::from_whole(50));
+        assert_eq!(at(u64::MAX), E8s::from_whole(50));
+
+        // rising prices work the same way
+        assert_eq!(
+            price(50, 100, 1_050, Decay::Linear, RoundingMode::Down),
+            E8s::from_whole(75)
+        );
+    }
+
+    #[test]
+    fn linear_rounding_modes() {
+        let at = |mode| dutch_price(&E8s::new(10u64), &E8s::zero(), 0, 3, 1, Decay::Linear, mode);
+
+        assert_eq!(at(RoundingMode::Down), E8s::new(6u64));
+        assert_eq!(at(RoundingMode::Up), E8s::new(7u64));
+        assert_eq!(at(RoundingMode::HalfEven), E8s::new(7u64));
+    }
+
+    #[test]
+    fn exponential_decay() {
+        let at = |now, mode| price(100, 25, now, Decay::Exponential, mode);
+
+        // 100 * 0.25^0.5
+        let mid_down = at(1_050, RoundingMode::Down);
+        let mid_up = at(1_050, RoundingMode::Up);
+        assert!(mid_down <= E8s::from_whole(50) && mid_up >= E8s::from_whole(50));
+        assert!(&mid_up.val - &mid_down.val <= Uint::from(1u64));
+
+        assert_eq!(at(1_000, RoundingMode::Up), E8s::from_whole(100));
+        assert_eq!(at(1_100, RoundingMode::Down), E8s::from_whole(25));
+
+        let mut prev = E8s::from_whole(100);
+        for now in 1_000..=1_100 {
+            let p = at(now, RoundingMode::Up);
+            assert!(p <= prev && p >= E8s::from_whole(25));
+            prev = p;
+        }
+    }
+
+    #[test]
+    fn zero_duration_jumps_to_the_end() {
+        let at = |now| {
+            dutch_price(
+                &E8s::from_whole(10),
+                &E8s::from_whole(5),
+                7,
+                7,
+                now,
+                Decay::Exponential,
+                RoundingMode::Down,
+            )
+        };
+
+        assert_eq!(at(7), E8s::from_whole(10));
+        assert_eq!(at(8), E8s::from_whole(5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Auction can't end before it starts")]
+    fn end_before_start_traps() {
+        dutch_price(
+            &E8s::one(),
+            &E8s::zero(),
+            10,
+            9,
+            9,
+            Decay::Linear,
+            RoundingMode::Down,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Exponential decay needs non-zero prices")]
+    fn exponential_to_zero_traps() {
+        price(1, 0, 1_050, Decay::Exponential, RoundingMode::Down);
+    }
+}
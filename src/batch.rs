@@ -0,0 +1,43 @@
+//! In-place operations over slices of amounts, avoiding temporary values where possible.
+//! Slices of different lengths trap.
+
+use num_bigint::BigUint;
+
+use crate::c::ECs;
+
+fn check_len(a: usize, b: usize) {
+    if a != b {
+        unreachable!("Slices of different length: {} and {}", a, b);
+    }
+}
+
+/// `a[i] += b[i]`
+pub fn add_slices<const D: usize>(a: &mut [ECs<D>], b: &[ECs<D>]) {
+    check_len(a.len(), b.len());
+
+    for (x, y) in a.iter_mut().zip(b) {
+        x.val += &y.val;
+    }
+}
+
+/// `a[i] *= factor`, each element is rounded down
+pub fn scale_slice<const D: usize>(a: &mut [ECs<D>], factor: &ECs<D>) {
+    let base = ECs::<D>::base();
+
+    for x in a.iter_mut() {
+        x.val *= &factor.val;
+        x.val /= base;
+    }
+}
+
+/// `Σ a[i] * b[i]`, rounded down once at the end
+pub fn dot<const D: usize>(a: &[ECs<D>], b: &[ECs<D>]) -> ECs<D> {
+    check_len(a.len(), b.len());
+
+    let mut acc = BigUint::ZERO;
+    for (x, y) in a.iter().zip(b) {
+        acc += &x.val * &y.val;
+    }
+
+    ECs::new(acc / ECs::<D>::base())
+}
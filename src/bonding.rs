@@ -0,0 +1,178 @@
+//! Bonding curves, pricing a token (with `DS` decimals) in a reserve token (with `DP` decimals)
+//! as a function of the token's supply. Costs are exact integrals of the price over the
+//! minted/burned range, rounded once: up when minting and down when burning, so the reserve
+//! always covers every refund.
+
+use alloc::{format, string::String};
+
+use num_bigint::BigUint;
+
+use crate::{
+    c::ECs,
+    rounding::{div_rounded, RoundingMode},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BondingCurve<const DS: usize, const DP: usize> {
+    /// `price = slope * supply + intercept`
+    Linear { slope: ECs<DP>, intercept: ECs<DP> },
+    /// `price = coefficient * supply^exponent`
+    Power { coefficient: ECs<DP>, exponent: u32 },
+}
+
+impl<const DS: usize, const DP: usize> BondingCurve<DS, DP> {
+    /// Price of a whole token at the supply, rounded down
+    pub fn price_at(&self, supply: &ECs<DS>) -> ECs<DP> {
+        let base = ECs::<DS>::base();
+
+        match self {
+            Self::Linear { slope, intercept } => {
+                ECs::new(&slope.val * &supply.val / base + &intercept.val)
+            }
+            Self::Power {
+                coefficient,
+                exponent,
+            } => ECs::new(&coefficient.val * supply.val.pow(*exponent) / base.pow(*exponent)),
+        }
+    }
+
+    /// Integral of the price from `from` to `to` (raw numerator and denominator)
+    fn integral(&self, from: &BigUint, to: &BigUint) -> (BigUint, BigUint) {
+        let base = ECs::<DS>::base();
+
+        match self {
+            Self::Linear { slope, intercept } => {
+                // slope * (to^2 - from^2) / 2 + intercept * (to - from)
+                let n = &slope.val * (to * to - from * from)
+                    + &intercept.val * (to - from) * base * 2u32;
+                let d = base * base * 2u32;
+
+                (n, d)
+            }
+            Self::Power {
+                coefficient,
+                exponent,
+            } => {
+                // coefficient * (to^(n+1) - from^(n+1)) / (n+1)
+                let e = exponent + 1;
+                let n = &coefficient.val * (to.pow(e) - from.pow(e));
+                let d = base.pow(e) * BigUint::from(e);
+
+                (n, d)
+            }
+        }
+    }
+
+    /// Cost of minting `amount` tokens at the current supply, rounded up
+    pub fn cost_to_mint(&self, supply: &ECs<DS>, amount: &ECs<DS>) -> ECs<DP> {
+        let (n, d) = self.integral(&supply.val.big(), &(&supply.val + &amount.val).big());
+
+        ECs::new(div_rounded(&n, &d, RoundingMode::Up))
+    }
+
+    /// Refund for burning `amount` tokens at the current supply, rounded down.
+    /// Fails, if the amount is bigger than the supply.
+    pub fn refund_for_burn(&self, supply: &ECs<DS>, amount: &ECs<DS>) -> Result<ECs<DP>, String> {
+        if amount.val > supply.val {
+            return Err(format!("Can't burn {} out of {} supply", amount, supply));
+        }
+
+        let (n, d) = self.integral(&(&supply.val - &amount.val).big(), &supply.val.big());
+
+        Ok(ECs::new(n / d))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn linear() -> BondingCurve<8, 8> {
+        // 0.001 per token of supply, starting at 0.1
+        BondingCurve::Linear {
+            slope: E8s::new(100_000u64),
+            intercept: E8s::new(10_000_000u64),
+        }
+    }
+
+    #[test]
+    fn linear_curve_integrals() {
+        let curve = linear();
+        let supply = E8s::from_whole(1_000);
+
+        assert_eq!(curve.price_at(&E8s::zero()), E8s::new(10_000_000u64));
+        assert_eq!(curve.price_at(&supply), E8s::new(110_000_000u64));
+
+        // 0.001 * 1000^2 / 2 + 0.1 * 1000
+        assert_eq!(
+            curve.cost_to_mint(&E8s::zero(), &supply),
+            E8s::from_whole(600)
+        );
+        assert_eq!(
+            curve.refund_for_burn(&supply, &supply),
+            Ok(E8s::from_whole(600))
+        );
+        assert_eq!(curve.cost_to_mint(&supply, &E8s::zero()), E8s::zero());
+    }
+
+    #[test]
+    fn power_curve_integrals() {
+        let curve = BondingCurve::<8, 8>::Power {
+            coefficient: E8s::one(),
+            exponent: 2,
+        };
+        let three = E8s::from_whole(3);
+
+        assert_eq!(curve.price_at(&three), E8s::from_whole(9));
+        // 3^3 / 3
+        assert_eq!(curve.cost_to_mint(&E8s::zero(), &three), E8s::from_whole(9));
+        // (3^3 - 2^3) / 3 = 6.333...
+        assert_eq!(
+            curve.cost_to_mint(&E8s::from_whole(2), &E8s::one()),
+            E8s::new(633_333_334u64)
+        );
+        assert_eq!(
+            curve.refund_for_burn(&three, &E8s::one()),
+            Ok(E8s::new(633_333_333u64))
+        );
+    }
+
+    #[test]
+    fn reserve_covers_every_refund() {
+        // half a raw unit per token: minting rounds it up, burning down
+        let curve = BondingCurve::<0, 8>::Linear {
+            slope: E8s::new(1u64),
+            intercept: E8s::zero(),
+        };
+        let one = ECs::<0>::new(1u64);
+
+        assert_eq!(curve.cost_to_mint(&ECs::zero(), &one), E8s::new(1u64));
+        assert_eq!(curve.refund_for_burn(&one, &one), Ok(E8s::zero()));
+
+        let curve = linear();
+        let step = E8s::new(123_456_789u64);
+        let mut supply = E8s::zero();
+        let mut reserve = E8s::zero();
+
+        for _ in 0..10 {
+            reserve += curve.cost_to_mint(&supply, &step);
+            supply += &step;
+        }
+        for _ in 0..10 {
+            reserve -= curve.refund_for_burn(&supply, &step).unwrap();
+            supply -= &step;
+        }
+
+        // `-=` would trap if a refund wasn't covered, and only rounding dust is left
+        assert!(reserve <= E8s::new(20u64));
+        assert_eq!(supply, E8s::zero());
+    }
+
+    #[test]
+    fn cant_burn_more_than_the_supply() {
+        assert!(linear()
+            .refund_for_burn(&E8s::one(), &E8s::from_whole(2))
+            .is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use core::fmt::Display;
+
+#[cfg(feature = "candid")]
+use candid::CandidType;
+use num_bigint::BigUint;
+use serde::Deserialize;
+#[cfg(not(feature = "candid"))]
+use serde::Serialize;
+
+use crate::{
+    abs_diff_raw,
+    c::ECs,
+    rounding::{div_rounded, RoundingMode},
+};
+
+/// Basis points in a whole
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Basis points (1 bps = 0.01%), as fees and slippage parameters are usually expressed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Deserialize)]
+#[cfg_attr(feature = "candid", derive(CandidType))]
+#[cfg_attr(not(feature = "candid"), derive(Serialize))]
+pub struct Bps(pub u32);
+
+impl Bps {
+    /// The bps portion of the amount, e.g. the fee: `amount * bps / 10_000`, rounded down
+    pub fn apply<const D: usize>(&self, amount: &ECs<D>) -> ECs<D> {
+        ECs::new(&amount.val * self.0 / BPS_DENOMINATOR)
+    }
+
+    /// Gross-up: an amount that is still at least `net` after the bps portion is taken out,
+    /// `net * 10_000 / (10_000 - bps)`, rounded up. Traps, if bps is 10'000 or more.
+    pub fn remove<const D: usize>(&self, net: &ECs<D>) -> ECs<D> {
+        if self.0 >= BPS_DENOMINATOR {
+            unreachable!("Can't gross-up by 100% or more");
+        }
+
+        ECs::new(div_rounded(
+            &(&net.val * BigUint::from(BPS_DENOMINATOR)),
+            &BigUint::from(BPS_DENOMINATOR - self.0),
+            RoundingMode::Up,
+        ))
+    }
+
+    /// The fraction these bps represent, e.g. `0.0025` for 25 bps
+    pub fn to_fraction<const D: usize>(&self) -> ECs<D> {
+        ECs::new(ECs::<D>::base() * BigUint::from(self.0) / BigUint::from(BPS_DENOMINATOR))
+    }
+}
+
+impl Display for Bps {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} bps", self.0)
+    }
+}
+
+impl From<u32> for Bps {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+/// Deviation of `candidate` from `reference` in bps of the reference, rounded up (so a price
+/// that is slightly over a limit never shows up as exactly at it), saturating at `u32::MAX`.
+/// `None`, if the reference is zero and the candidate isn't.
+pub fn deviation_bps<const D: usize>(reference: &ECs<D>, candidate: &ECs<D>) -> Option<Bps> {
+    let diff = abs_diff_raw(&reference.val, &candidate.val);
+    if diff.is_zero() {
+        return Some(Bps(0));
+    }
+
+    if reference.is_zero() {
+        return None;
+    }
+
+    let bps = div_rounded(
+        &(diff * BPS_DENOMINATOR).big(),
+        &reference.val.big(),
+        RoundingMode::Up,
+    );
+
+    Some(Bps(u32::try_from(bps).unwrap_or(u32::MAX)))
+}
+
+/// Whether `candidate` deviates from `reference` by at most `max_deviation` of the reference,
+/// in either direction, e.g. to reject oracle updates and trade prices outside of the band.
+/// Exact, no rounding involved. A zero reference only accepts a zero candidate.
+pub fn within_band<const D: usize>(
+    reference: &ECs<D>,
+    candidate: &ECs<D>,
+    max_deviation: Bps,
+) -> bool {
+    abs_diff_raw(&reference.val, &candidate.val) * BPS_DENOMINATOR
+        <= &reference.val * max_deviation.0
+}
@@ -1,32 +1,60 @@
-use std::{
-    borrow::Cow,
+use alloc::{format, string::String};
+use core::{
+    cmp::Ordering,
     fmt::Display,
+    iter::{Product, Sum},
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign},
+    str::FromStr,
 };
 
+#[cfg(feature = "candid")]
 use candid::{CandidType, Nat};
+#[cfg(feature = "stable-structures")]
 use ic_stable_structures::{storable::Bound, Storable};
 use num_bigint::BigUint;
+#[cfg(feature = "candid")]
 use serde::Deserialize;
 
-use crate::{d::EDs, ES_BASES};
+#[cfg(feature = "stable-structures")]
+use crate::storable::{decode_ecs, encode_ecs};
+#[cfg(feature = "candid")]
+use crate::with_nat;
+use crate::{
+    abs_diff_raw,
+    bps::Bps,
+    cmp_raw_u64,
+    d::{EDs, EDsRef},
+    fmt_fixed,
+    math::ten_pow,
+    mul_base_raw, parse_decimal_str, quot_base_raw,
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+    within_bps_raw, ES_BASES, MAX_DECIMALS,
+};
 
+pub type E6s = ECs<6>;
 pub type E8s = ECs<8>;
+pub type E12s = ECs<12>;
+pub type E18s = ECs<18>;
 
-/// Fixed-point decimals with primitive math (+-*/) implemented correctly
+/// Fixed-point decimals with primitive math (+-*/) implemented correctly.
+///
+/// The raw value is a `Uint`, which is stored inline while it fits into `u128`, so amounts of
+/// any real token don't allocate at all, and arithmetic on them is carried out on `u128`.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+#[repr(transparent)]
 pub struct ECs<const DECIMALS: usize> {
-    pub val: BigUint,
+    pub val: Uint,
 }
 
 impl<const D: usize> ECs<D> {
-    pub fn new(val: BigUint) -> Self {
-        Self { val }
+    pub fn new(val: impl Into<Uint>) -> Self {
+        Self { val: val.into() }
     }
 
     pub fn base() -> &'static BigUint {
-        if D > 31 {
-            unreachable!("Decimal points after 31 are not supported");
+        if D > MAX_DECIMALS {
+            unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
         }
 
         // SAFETY: already checked
@@ -34,8 +62,8 @@ impl<const D: usize> ECs<D> {
     }
 
     pub fn base_d(decimals: u8) -> &'static BigUint {
-        if decimals > 31 {
-            unreachable!("Decimal points after 31 are not supported");
+        if decimals as usize > MAX_DECIMALS {
+            unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
         }
 
         // SAFETY: already checked
@@ -43,71 +71,165 @@ impl<const D: usize> ECs<D> {
     }
 
     pub fn zero() -> Self {
-        Self::new(BigUint::ZERO)
+        Self::new(Uint::ZERO)
     }
 
     pub fn one() -> Self {
-        Self {
-            val: Self::base().clone(),
+        Self::new(Self::base())
+    }
+
+    /// Whole tokens, e.g. `E8s::from_whole(3)` is `3.00000000`.
+    /// Unlike `From<u64>`, which takes raw units.
+    pub fn from_whole(tokens: u64) -> Self {
+        Self::new(Self::base() * tokens)
+    }
+
+    /// Adds whole tokens, see `from_whole`
+    pub fn add_whole(&self, tokens: u64) -> Self {
+        Self::new(&self.val + Self::base() * tokens)
+    }
+
+    /// Subtracts whole tokens, see `from_whole`. Traps, if the result would be negative.
+    pub fn sub_whole(&self, tokens: u64) -> Self {
+        Self::new(&self.val - Self::base() * tokens)
+    }
+
+    /// `numerator / denominator` at the type's scale, rounded according to the mode.
+    /// Traps, if the denominator is zero.
+    pub fn from_ratio(numerator: u64, denominator: u64, mode: RoundingMode) -> Self {
+        if denominator == 0 {
+            unreachable!("Zero denominator");
         }
+
+        Self::new(div_rounded(
+            &(Self::base() * BigUint::from(numerator)),
+            &BigUint::from(denominator),
+            mode,
+        ))
     }
 
     pub fn f0_1() -> Self {
-        Self::new(Self::base() / BigUint::from(10u64))
+        Self::from_ratio(1, 10, RoundingMode::Down)
     }
 
     pub fn f0_2() -> Self {
-        Self::new(Self::base() / BigUint::from(5u64))
+        Self::from_ratio(1, 5, RoundingMode::Down)
     }
 
     pub fn f0_25() -> Self {
-        Self::new(Self::base() / BigUint::from(4u64))
+        Self::from_ratio(1, 4, RoundingMode::Down)
     }
 
     pub fn f0_3() -> Self {
-        Self::new(Self::base() * BigUint::from(3u64) / BigUint::from(10u64))
+        Self::from_ratio(3, 10, RoundingMode::Down)
     }
 
     pub fn f0_33() -> Self {
-        Self::new(Self::base() / BigUint::from(3u64))
+        Self::from_ratio(1, 3, RoundingMode::Down)
     }
 
     pub fn f0_4() -> Self {
-        Self::new(Self::base() * BigUint::from(2u64) / BigUint::from(5u64))
+        Self::from_ratio(2, 5, RoundingMode::Down)
     }
 
     pub fn f0_5() -> Self {
-        Self::new(Self::base() / BigUint::from(2u64))
+        Self::from_ratio(1, 2, RoundingMode::Down)
     }
 
     pub fn f0_6() -> Self {
-        Self::new(Self::base() * BigUint::from(3u64) / BigUint::from(5u64))
+        Self::from_ratio(3, 5, RoundingMode::Down)
     }
 
     pub fn f0_67() -> Self {
-        Self::new(Self::base() * BigUint::from(2u64) / BigUint::from(3u64))
+        Self::from_ratio(2, 3, RoundingMode::Down)
     }
 
     pub fn f0_7() -> Self {
-        Self::new(Self::base() * BigUint::from(7u64) / BigUint::from(10u64))
+        Self::from_ratio(7, 10, RoundingMode::Down)
     }
 
     pub fn f0_75() -> Self {
-        Self::new(Self::base() * BigUint::from(3u64) / BigUint::from(4u64))
+        Self::from_ratio(3, 4, RoundingMode::Down)
     }
 
     pub fn f0_8() -> Self {
-        Self::new(Self::base() * BigUint::from(4u64) / BigUint::from(5u64))
+        Self::from_ratio(4, 5, RoundingMode::Down)
     }
 
     pub fn f0_9() -> Self {
-        Self::new(Self::base() * BigUint::from(9u64) / BigUint::from(10u64))
+        Self::from_ratio(9, 10, RoundingMode::Down)
     }
 
     pub fn two() -> Self {
         Self::new(Self::base() * BigUint::from(2u64))
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.val.is_zero()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.val == *Self::base()
+    }
+
+    /// The whole part, e.g. `12` for `12.34`
+    pub fn whole(&self) -> BigUint {
+        &self.val / Self::base()
+    }
+
+    /// Raw value of the fractional part, e.g. `34` for `12.34` with 2 decimal points
+    pub fn fract_raw(&self) -> BigUint {
+        &self.val % Self::base()
+    }
+
+    /// Significant bits of the raw value, 0 for zero
+    pub fn bits(&self) -> u64 {
+        self.val.bits()
+    }
+
+    /// Minimal number of bytes holding the raw value, 0 for zero
+    pub fn byte_len(&self) -> usize {
+        self.val.bits().div_ceil(8) as usize
+    }
+
+    /// Whether the raw value can be passed as `u64`, e.g. to the ICP ledger
+    pub fn fits_u64(&self) -> bool {
+        self.val.bits() <= 64
+    }
+
+    /// Whether the raw value can be converted to `u128`
+    pub fn fits_u128(&self) -> bool {
+        self.val.bits() <= 128
+    }
+
+    /// A fraction from percents, e.g. `from_percent(5)` is `0.05`
+    pub fn from_percent(percent: u32) -> Self {
+        Self::new(Self::base() * BigUint::from(percent) / BigUint::from(100u64))
+    }
+
+    /// A fraction from permille, e.g. `from_permille(25)` is `0.025` (2.5%)
+    pub fn from_permille(permille: u32) -> Self {
+        Self::new(Self::base() * BigUint::from(permille) / BigUint::from(1_000u64))
+    }
+
+    /// Formats the fraction as percents with `precision` decimal points, rounded down,
+    /// e.g. `0.025` is "2.50%" with precision 2
+    pub fn to_percent_string(&self, precision: usize) -> String {
+        let precision_base = ten_pow(precision);
+        let scaled = &self.val * BigUint::from(100u64) * &precision_base / Self::base();
+
+        if precision == 0 {
+            return format!("{}%", scaled);
+        }
+
+        format!(
+            "{}.{:0>width$}%",
+            &scaled / &precision_base,
+            &scaled % &precision_base,
+            width = precision
+        )
+    }
+
     pub fn sqrt(&self) -> Self {
         let base = Self::base();
         let whole = &self.val / base;
@@ -116,10 +238,44 @@ impl<const D: usize> ECs<D> {
         Self::new(sqrt_whole * base)
     }
 
+    /// Square root at full scale (`isqrt(val * base)`), rounded down.
+    /// Unlike `sqrt`, keeps the fractional part: `sqrt_precise(2.25) = 1.5`.
+    pub fn sqrt_precise(&self) -> Self {
+        Self::new((&self.val * Self::base()).sqrt())
+    }
+
+    /// Cube root at full scale, rounded down, see `nth_root`
+    pub fn cbrt(&self) -> Self {
+        self.nth_root(3)
+    }
+
+    /// `n`-th root at full scale (`iroot(val * base^(n-1), n)`), rounded down. Traps, if `n` is 0.
+    pub fn nth_root(&self, n: u32) -> Self {
+        if n == 0 {
+            unreachable!("Zeroth root is undefined");
+        }
+
+        Self::new((&self.val * Self::base().pow(n - 1)).nth_root(n))
+    }
+
     pub fn to_dynamic(self) -> EDs {
         EDs::new(self.val, D as u8)
     }
 
+    /// Borrowing `to_dynamic`, without moving or cloning the value
+    pub fn as_dynamic(&self) -> EDsRef<'_> {
+        EDsRef {
+            val: &self.val,
+            decimals: D as u8,
+        }
+    }
+
+    /// Views a raw value as `ECs<D>`, without cloning it
+    pub fn from_raw_ref(val: &Uint) -> &Self {
+        // SAFETY: `ECs` is a `repr(transparent)` wrapper around `Uint`
+        unsafe { &*(val as *const Uint as *const Self) }
+    }
+
     pub fn to_decimals<const D1: usize>(self) -> ECs<D1> {
         if D1 == D {
             return ECs::<D1>::new(self.val);
@@ -139,13 +295,193 @@ impl<const D: usize> ECs<D> {
             ECs::<D1>::new(self.val / base)
         }
     }
+
+    /// Applies NNS/SNS-style maturity modulation: `self * (10_000 + bps) / 10_000`, rounding down.
+    /// Modulation of -100% or lower results in zero.
+    pub fn apply_modulation_bps(&self, bps: i32) -> Self {
+        let multiplier = (10_000i64 + bps as i64).max(0) as u64;
+
+        Self::new(&self.val * multiplier / 10_000u64)
+    }
+
+    /// Lossless conversion to more (or the same amount of) decimal points,
+    /// fails to compile if `D1 < D`
+    pub fn widen<const D1: usize>(self) -> ECs<D1> {
+        const { assert!(D1 >= D, "Widening can't reduce decimal points") };
+
+        self.to_decimals::<D1>()
+    }
+
+    /// Conversion to less (or the same amount of) decimal points, dropping the extra precision,
+    /// fails to compile if `D1 > D`
+    pub fn narrow_lossy<const D1: usize>(self) -> ECs<D1> {
+        const { assert!(D1 <= D, "Narrowing can't increase decimal points") };
+
+        self.to_decimals::<D1>()
+    }
+
+    /// Same as `to_decimals`, but rounds according to the mode when decimal points are dropped
+    pub fn to_decimals_rounded<const D1: usize>(self, mode: RoundingMode) -> ECs<D1> {
+        if D1 >= D {
+            return self.to_decimals::<D1>();
+        }
+
+        let base = Self::base_d((D - D1) as u8);
+
+        ECs::<D1>::new(div_rounded(&self.val.big(), base, mode))
+    }
+
+    /// Multiplies the value by 10^n, keeping the decimal points (unlike `to_decimals`)
+    pub fn scale_up(&self, n: u8) -> Self {
+        Self::new(&self.val * ten_pow(n as usize))
+    }
+
+    /// Divides the value by 10^n, keeping the decimal points (unlike `to_decimals`)
+    pub fn scale_down(&self, n: u8, mode: RoundingMode) -> Self {
+        Self::new(div_rounded(&self.val.big(), &ten_pow(n as usize), mode))
+    }
+
+    /// Exact product with `D + D2` decimal points, nothing is truncated.
+    /// Round it explicitly afterwards, e.g. via `EDs::to_decimals`.
+    /// Fails to compile if `D + D2` is above `MAX_DECIMALS`, e.g. for two `ECs<40>`.
+    pub fn mul_full<const D2: usize>(&self, other: &ECs<D2>) -> EDs {
+        const {
+            assert!(
+                D + D2 <= MAX_DECIMALS,
+                "The product has too many decimal points"
+            )
+        };
+
+        EDs::new(&self.val * &other.val, (D + D2) as u8)
+    }
+
+    /// `self * mul / div` at full intermediate precision with a single rounding step
+    pub fn muldiv(&self, mul: &Self, div: &Self, mode: RoundingMode) -> Self {
+        Self::new(div_rounded(
+            &(&self.val * &mul.val).big(),
+            &div.val.big(),
+            mode,
+        ))
+    }
+
+    /// `self * rhs`, written into `out`
+    pub fn mul_into(&self, rhs: &Self, out: &mut Self) {
+        out.val = mul_base_raw(&self.val, &rhs.val, D);
+    }
+
+    /// `self / rhs`, written into `out`
+    pub fn div_into(&self, rhs: &Self, out: &mut Self) {
+        out.val = quot_base_raw(&self.val, &rhs.val, D);
+    }
+
+    /// Linear interpolation between `self` (at `t = 0`) and `other` (at `t = 1`),
+    /// rounded once according to the mode. Traps, if `t` is above 1.
+    pub fn lerp(&self, other: &Self, t: &Self, mode: RoundingMode) -> Self {
+        let base = Self::base();
+        if t.val > *base {
+            unreachable!("Interpolation parameter should be in [0, 1]");
+        }
+
+        let n = &self.val * (base - &t.val) + &other.val * &t.val;
+
+        Self::new(div_rounded(&n, base, mode))
+    }
+
+    /// `self * mul + add`, truncated once at the end
+    pub fn fma(&self, mul: &Self, add: &Self) -> Self {
+        Self::new(&self.val * &mul.val / Self::base() + &add.val)
+    }
+
+    /// Symmetric relative difference `|self - other| / max(self, other)` as a fraction in [0, 1],
+    /// rounded down. Zero, if both are zero.
+    pub fn rel_diff(&self, other: &Self) -> Self {
+        let (min, max) = if self.val <= other.val {
+            (&self.val, &other.val)
+        } else {
+            (&other.val, &self.val)
+        };
+
+        if *max == BigUint::ZERO {
+            return Self::zero();
+        }
+
+        Self::new((max - min) * Self::base() / max)
+    }
+
+    /// Compares values with different decimal points, scaling the less precise one up
+    pub fn cmp_scaled<const D2: usize>(&self, other: &ECs<D2>) -> Ordering {
+        match D.cmp(&D2) {
+            Ordering::Equal => self.val.cmp(&other.val),
+            Ordering::Less => (&self.val * Self::base_d((D2 - D) as u8)).cmp(&other.val.big()),
+            Ordering::Greater => {
+                (self.val.big().as_ref()).cmp(&(&other.val * Self::base_d((D - D2) as u8)))
+            }
+        }
+    }
+
+    /// Equality of values with different decimal points, see `cmp_scaled`
+    pub fn eq_scaled<const D2: usize>(&self, other: &ECs<D2>) -> bool {
+        self.cmp_scaled(other) == Ordering::Equal
+    }
+
+    /// `|self - other| <= epsilon`, e.g. for reconciling tracked balances with the ledger ones
+    pub fn approx_eq(&self, other: &Self, epsilon: &Self) -> bool {
+        abs_diff_raw(&self.val, &other.val) <= epsilon.val
+    }
+
+    /// Whether the values differ by at most `bps` of the bigger one, see `rel_diff`
+    pub fn within_bps(&self, other: &Self, bps: Bps) -> bool {
+        within_bps_raw(&self.val, &other.val, bps.0)
+    }
+
+    /// By-ref `Ord::min`, returning an owned value
+    pub fn min_ref(&self, other: &Self) -> Self {
+        if other < self {
+            other.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// By-ref `Ord::max`, returning an owned value
+    pub fn max_ref(&self, other: &Self) -> Self {
+        if other > self {
+            other.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// By-ref `Ord::clamp`, returning an owned value. Traps if `lo > hi`.
+    pub fn clamp_ref(&self, lo: &Self, hi: &Self) -> Self {
+        if lo > hi {
+            unreachable!("Invalid clamp bounds: {} > {}", lo, hi);
+        }
+
+        if self < lo {
+            lo.clone()
+        } else if self > hi {
+            hi.clone()
+        } else {
+            self.clone()
+        }
+    }
 }
 
+/// Exactly `D` zero-padded digits after the point, e.g. "1.05000000", and no point if `D` is 0
 impl<const D: usize> Display for ECs<D> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let base = ECs::<D>::base();
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt_fixed(&self.val, D, f)
+    }
+}
+
+impl<const D: usize> FromStr for ECs<D> {
+    type Err = String;
 
-        f.write_str(&format!("{}.{}", &self.val / base, &self.val % base))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (val, _) = parse_decimal_str(s, Some(D))?;
+
+        Ok(Self::new(val))
     }
 }
 
@@ -241,7 +577,7 @@ impl<const D: usize> Mul for &ECs<D> {
     type Output = ECs<D>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        ECs::<D>::new(&self.val * &rhs.val / ECs::<D>::base())
+        ECs::<D>::new(mul_base_raw(&self.val, &rhs.val, D))
     }
 }
 
@@ -271,7 +607,7 @@ impl<const D: usize> Mul<ECs<D>> for &ECs<D> {
 
 impl<const D: usize> MulAssign<&ECs<D>> for ECs<D> {
     fn mul_assign(&mut self, rhs: &ECs<D>) {
-        self.val = &self.val * &rhs.val / ECs::<D>::base()
+        self.val = mul_base_raw(&self.val, &rhs.val, D);
     }
 }
 
@@ -285,7 +621,7 @@ impl<const D: usize> Div for &ECs<D> {
     type Output = ECs<D>;
 
     fn div(self, rhs: Self) -> Self::Output {
-        ECs::<D>::new(&self.val * ECs::<D>::base() / &rhs.val)
+        ECs::<D>::new(quot_base_raw(&self.val, &rhs.val, D))
     }
 }
 
@@ -315,7 +651,7 @@ impl<const D: usize> Div<ECs<D>> for &ECs<D> {
 
 impl<const D: usize> DivAssign<&ECs<D>> for ECs<D> {
     fn div_assign(&mut self, rhs: &ECs<D>) {
-        self.val = &self.val * ECs::<D>::base() / &rhs.val;
+        self.val = quot_base_raw(&self.val, &rhs.val, D);
     }
 }
 
@@ -325,6 +661,87 @@ impl<const D: usize> DivAssign for ECs<D> {
     }
 }
 
+/// Scalar `*` and `/` by raw integers, e.g. `amount * 3` or `amount / 2`: the scalar is a plain
+/// number, not a fixed-point value, so there is no rescaling. Division truncates. With both `u64`
+/// and `u128` impls, literals need a suffix: `amount * 3u64`.
+macro_rules! impl_scalar_ops {
+    ($t:ty) => {
+        impl<const D: usize> Mul<$t> for &ECs<D> {
+            type Output = ECs<D>;
+
+            fn mul(self, rhs: $t) -> Self::Output {
+                ECs::new(&self.val * rhs)
+            }
+        }
+
+        impl<const D: usize> Mul<$t> for ECs<D> {
+            type Output = ECs<D>;
+
+            fn mul(self, rhs: $t) -> Self::Output {
+                ECs::new(self.val * rhs)
+            }
+        }
+
+        impl<const D: usize> MulAssign<$t> for ECs<D> {
+            fn mul_assign(&mut self, rhs: $t) {
+                self.val *= rhs;
+            }
+        }
+
+        impl<const D: usize> Div<$t> for &ECs<D> {
+            type Output = ECs<D>;
+
+            fn div(self, rhs: $t) -> Self::Output {
+                ECs::new(&self.val / rhs)
+            }
+        }
+
+        impl<const D: usize> Div<$t> for ECs<D> {
+            type Output = ECs<D>;
+
+            fn div(self, rhs: $t) -> Self::Output {
+                ECs::new(self.val / rhs)
+            }
+        }
+
+        impl<const D: usize> DivAssign<$t> for ECs<D> {
+            fn div_assign(&mut self, rhs: $t) {
+                self.val /= rhs;
+            }
+        }
+    };
+}
+
+impl_scalar_ops!(u64);
+impl_scalar_ops!(u128);
+
+impl<const D: usize> Sum for ECs<D> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, it| acc + it)
+    }
+}
+
+impl<'a, const D: usize> Sum<&'a ECs<D>> for ECs<D> {
+    fn sum<I: Iterator<Item = &'a ECs<D>>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |acc, it| acc + it)
+    }
+}
+
+/// Fixed-point product, truncated after each multiplication
+impl<const D: usize> Product for ECs<D> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::one(), |acc, it| acc * it)
+    }
+}
+
+/// Fixed-point product, truncated after each multiplication
+impl<'a, const D: usize> Product<&'a ECs<D>> for ECs<D> {
+    fn product<I: Iterator<Item = &'a ECs<D>>>(iter: I) -> Self {
+        iter.fold(Self::one(), |acc, it| acc * it)
+    }
+}
+
+#[cfg(feature = "candid")]
 impl<const D: usize> CandidType for ECs<D> {
     fn _ty() -> candid::types::Type {
         Nat::_ty()
@@ -334,10 +751,11 @@ impl<const D: usize> CandidType for ECs<D> {
     where
         S: candid::types::Serializer,
     {
-        Nat::idl_serialize(&Nat(self.val.clone()), serializer)
+        with_nat(&self.val, |nat| nat.idl_serialize(serializer))
     }
 }
 
+#[cfg(feature = "candid")]
 impl<'de, const C: usize> Deserialize<'de> for ECs<C> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -347,29 +765,214 @@ impl<'de, const C: usize> Deserialize<'de> for ECs<C> {
     }
 }
 
+/// Without Candid, serialized as a string of raw units, e.g. `"150000000"` for `1.5` E8s
+#[cfg(not(feature = "candid"))]
+impl<const D: usize> serde::Serialize for ECs<D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&crate::serde_raw::Raw(&self.val), serializer)
+    }
+}
+
+/// Without Candid, accepts a string of raw units or an integer
+#[cfg(not(feature = "candid"))]
+impl<'de, const C: usize> serde::Deserialize<'de> for ECs<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::serde_raw::deserialize(deserializer).map(ECs::new)
+    }
+}
+
+/// Raw units, e.g. `E8s::from(150_000_000)` is `1.5`. For whole tokens see `from_whole`.
 impl<const D: usize> From<u64> for ECs<D> {
     fn from(value: u64) -> Self {
-        Self::new(BigUint::from(value))
+        Self::new(value)
     }
 }
 
+/// Raw units, see `From<u64>`
 impl<const D: usize> From<u128> for ECs<D> {
     fn from(value: u128) -> Self {
-        Self::new(BigUint::from(value))
+        Self::new(value)
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<const D: usize> From<Nat> for ECs<D> {
+    fn from(value: Nat) -> Self {
+        Self::new(value.0)
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<const D: usize> From<ECs<D>> for Nat {
+    fn from(value: ECs<D>) -> Self {
+        Nat(value.val.into_big())
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<const D: usize> From<&ECs<D>> for Nat {
+    fn from(value: &ECs<D>) -> Self {
+        Nat(value.val.big().into_owned())
+    }
+}
+
+/// Compares raw units, e.g. `if amount < MIN_RAW_E8S`
+impl<const D: usize> PartialEq<u64> for ECs<D> {
+    fn eq(&self, other: &u64) -> bool {
+        cmp_raw_u64(&self.val, *other) == Ordering::Equal
     }
 }
 
+impl<const D: usize> PartialOrd<u64> for ECs<D> {
+    fn partial_cmp(&self, other: &u64) -> Option<Ordering> {
+        Some(cmp_raw_u64(&self.val, *other))
+    }
+}
+
+/// Compares raw units, e.g. `if balance >= ledger_fee_nat`
+#[cfg(feature = "candid")]
+impl<const D: usize> PartialEq<Nat> for ECs<D> {
+    fn eq(&self, other: &Nat) -> bool {
+        self.val == other.0
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<const D: usize> PartialOrd<Nat> for ECs<D> {
+    fn partial_cmp(&self, other: &Nat) -> Option<Ordering> {
+        self.val.partial_cmp(&other.0)
+    }
+}
+
+/// Encoded as LE magnitude bytes followed by a version trailer, see `storable::ECS_LAYOUT_VERSION`.
+/// The magnitude grows with the value, so the encoding is unbounded. The bare LE bytes stored by
+/// previous versions are still decoded.
+///
+/// Values stored in maps by previous versions decode as before, but map keys don't: the old
+/// fixed bound stored them without a length prefix. Load such maps with `storable::ECsLegacy<D>`
+/// as the key type and re-populate their entries into freshly initialized maps in `post_upgrade`.
+#[cfg(feature = "stable-structures")]
 impl<const D: usize> Storable for ECs<D> {
-    fn to_bytes(&self) -> Cow<[u8]> {
-        Cow::Owned(self.val.to_bytes_le())
+    fn to_bytes(&self) -> alloc::borrow::Cow<'_, [u8]> {
+        alloc::borrow::Cow::Owned(encode_ecs(self))
     }
 
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        Self::new(BigUint::from_bytes_le(&bytes))
+    fn from_bytes(bytes: alloc::borrow::Cow<[u8]>) -> Self {
+        decode_ecs(&bytes)
     }
 
-    const BOUND: Bound = Bound::Bounded {
-        max_size: D as u32, // TODO: fix this, since it won't work for big numbers (same limit works for ICP though)
-        is_fixed_size: true,
-    };
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+
+    fn e8s(raw: u64) -> E8s {
+        E8s::from(raw)
+    }
+
+    /// `r^2 <= val * base < (r + 1)^2`
+    fn assert_floor_sqrt<const D: usize>(val: &ECs<D>, r: &ECs<D>) {
+        let n = &val.val * ECs::<D>::base();
+        let next = &r.val + 1u32;
+
+        assert!(&r.val * &r.val <= n);
+        assert!(&next * &next > n);
+    }
+
+    #[test]
+    fn sqrt_precise_of_zero_and_one_unit() {
+        assert_eq!(E8s::zero().sqrt_precise(), E8s::zero());
+        // sqrt(0.00000001) = 0.0001
+        assert_eq!(e8s(1).sqrt_precise(), e8s(10_000));
+        // sqrt(0.0000001) = 0.000316227766..., rounded down
+        assert_eq!(
+            ECs::<7>::from(1u64).sqrt_precise(),
+            ECs::<7>::from(3_162u64)
+        );
+    }
+
+    #[test]
+    fn sqrt_precise_of_perfect_squares() {
+        assert_eq!(E8s::one().sqrt_precise(), E8s::one());
+        assert_eq!(e8s(225_000_000).sqrt_precise(), e8s(150_000_000));
+        assert_eq!(e8s(400_000_000).sqrt_precise(), e8s(200_000_000));
+        assert_eq!(e8s(1_000_000).sqrt_precise(), e8s(10_000_000));
+        assert_eq!(
+            E8s::from_whole(1_000_000).sqrt_precise(),
+            E8s::from_whole(1_000)
+        );
+    }
+
+    #[test]
+    fn sqrt_precise_just_below_perfect_squares() {
+        // sqrt(3.99999999) = 1.99999999749...
+        assert_eq!(e8s(399_999_999).sqrt_precise(), e8s(199_999_999));
+        // sqrt(0.99999999) = 0.99999999499...
+        assert_eq!(e8s(99_999_999).sqrt_precise(), e8s(99_999_999));
+        // sqrt(2.24999999) = 1.49999999666...
+        assert_eq!(e8s(224_999_999).sqrt_precise(), e8s(149_999_999));
+    }
+
+    #[test]
+    fn sqrt_precise_at_max_decimals() {
+        type E76 = ECs<76>;
+
+        assert_eq!(E76::from_whole(4).sqrt_precise(), E76::from_whole(2));
+        assert_eq!(
+            E76::from(1u64).sqrt_precise().val,
+            BigUint::from(10u32).pow(38)
+        );
+
+        let max = E76::new((BigUint::from(1u32) << 256u32) - 1u32);
+        assert_floor_sqrt(&max, &max.sqrt_precise());
+
+        let below_four = E76::from_whole(4) - E76::from(1u64);
+        let r = below_four.sqrt_precise();
+        assert!(r < E76::from_whole(2));
+        assert_floor_sqrt(&below_four, &r);
+    }
+
+    #[test]
+    fn amounts_fitting_u128_stay_inline() {
+        let a = E18s::from(1_500_000_000_000_000_000u128);
+        let b = E18s::from(1_000_001_000_000_000_000u128);
+
+        for it in [&a * &b, &a / &b, &a + &b, &a - &b, a.clone() * 3u64] {
+            assert!(it.val.is_inline());
+        }
+
+        // the product overflows u128 on the way, the result doesn't
+        let big = E18s::from(u128::MAX / 2);
+        let half = E18s::from(500_000_000_000_000_000u128);
+        assert_eq!((&big * &half).val, Uint::from(u128::MAX / 4));
+        assert!((&big * &half).val.is_inline());
+
+        let wide = &big * &E18s::from_whole(4);
+        assert!(!wide.val.is_inline());
+        assert_eq!(wide.val, BigUint::from(u128::MAX / 2) * 4u32);
+    }
+
+    #[test]
+    fn mul_full_up_to_max_decimals() {
+        let a = ECs::<38>::new(EDs::base(38) * 3u32);
+        let b = ECs::<38>::new(EDs::base(37) * 5u32);
+        let product = a.mul_full(&b);
+
+        assert_eq!(product.decimals as usize, MAX_DECIMALS);
+        assert_eq!(product.to_decimals(1), EDs::new(15u32, 1));
+
+        let c = ECs::<70>::new(EDs::base(70) * 2u32);
+        let d = ECs::<6>::new(1_500_000u64);
+        assert_eq!(c.mul_full(&d).to_decimals(0), EDs::new(3u32, 0));
+    }
 }
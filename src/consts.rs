@@ -0,0 +1,70 @@
+//! Mathematical constants at any supported decimal points, truncated (rounded down)
+//! from precomputed digits, e.g. `consts::pi::<8>()` or `consts::pi_d(8)`.
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs};
+
+/// Fractional digits every constant is stored with
+const PRECISION: usize = 80;
+
+const PI_DIGITS: &str =
+    "314159265358979323846264338327950288419716939937510582097494459230781640628620899";
+const E_DIGITS: &str =
+    "271828182845904523536028747135266249775724709369995957496696762772407663035354759";
+const LN2_DIGITS: &str =
+    "069314718055994530941723212145817656807550013436025525412068000949339362196969471";
+const SQRT2_DIGITS: &str =
+    "141421356237309504880168872420969807856967187537694807317667973799073247846210703";
+
+/// The whole part is a single digit for every constant
+fn from_digits(digits: &str, decimals: u8) -> BigUint {
+    if decimals as usize > PRECISION {
+        unreachable!(
+            "Constants are only stored with {} decimal points",
+            PRECISION
+        );
+    }
+
+    BigUint::parse_bytes(&digits.as_bytes()[..1 + decimals as usize], 10).unwrap()
+}
+
+/// `pi`
+pub fn pi<const D: usize>() -> ECs<D> {
+    ECs::new(from_digits(PI_DIGITS, D as u8))
+}
+
+/// `pi`
+pub fn pi_d(decimals: u8) -> EDs {
+    EDs::new(from_digits(PI_DIGITS, decimals), decimals)
+}
+
+/// Euler's number `e`
+pub fn e<const D: usize>() -> ECs<D> {
+    ECs::new(from_digits(E_DIGITS, D as u8))
+}
+
+/// Euler's number `e`
+pub fn e_d(decimals: u8) -> EDs {
+    EDs::new(from_digits(E_DIGITS, decimals), decimals)
+}
+
+/// `ln(2)`
+pub fn ln2<const D: usize>() -> ECs<D> {
+    ECs::new(from_digits(LN2_DIGITS, D as u8))
+}
+
+/// `ln(2)`
+pub fn ln2_d(decimals: u8) -> EDs {
+    EDs::new(from_digits(LN2_DIGITS, decimals), decimals)
+}
+
+/// `sqrt(2)`
+pub fn sqrt2<const D: usize>() -> ECs<D> {
+    ECs::new(from_digits(SQRT2_DIGITS, D as u8))
+}
+
+/// `sqrt(2)`
+pub fn sqrt2_d(decimals: u8) -> EDs {
+    EDs::new(from_digits(SQRT2_DIGITS, decimals), decimals)
+}
@@ -0,0 +1,117 @@
+use alloc::string::String;
+
+use num_bigint::BigUint;
+
+use crate::{
+    d::EDs,
+    rounding::{div_rounded, RoundingMode},
+    MAX_DECIMALS,
+};
+
+/// Decimal points and rounding policy of a service, passed around instead of a bare `u8`.
+/// Values it produces always have its decimal points, and every operation that drops precision
+/// rounds according to its mode. Operands with other decimal points are converted first.
+///
+/// ```ignore
+/// const CTX: DecimalContext = DecimalContext::new(8, RoundingMode::HalfEven);
+///
+/// let fee = CTX.mul(&amount, &CTX.parse("0.003")?);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub struct DecimalContext {
+    pub decimals: u8,
+    pub rounding: RoundingMode,
+}
+
+impl DecimalContext {
+    /// Traps, if there are more than `MAX_DECIMALS` decimal points
+    pub const fn new(decimals: u8, rounding: RoundingMode) -> Self {
+        if decimals as usize > MAX_DECIMALS {
+            panic!("Too many decimal points");
+        }
+
+        Self { decimals, rounding }
+    }
+
+    pub fn zero(&self) -> EDs {
+        EDs::zero(self.decimals)
+    }
+
+    pub fn one(&self) -> EDs {
+        EDs::one(self.decimals)
+    }
+
+    /// Whole tokens, see `EDs::from_whole`
+    pub fn from_whole(&self, tokens: u64) -> EDs {
+        EDs::from_whole(tokens, self.decimals)
+    }
+
+    /// `numerator / denominator`, rounded by the context
+    pub fn from_ratio(&self, numerator: u64, denominator: u64) -> EDs {
+        EDs::from_ratio(numerator, denominator, self.decimals, self.rounding)
+    }
+
+    /// Parses a decimal string, e.g. "1.5". Fails on more significant decimal points than the
+    /// context has, instead of rounding them.
+    pub fn parse(&self, s: &str) -> Result<EDs, String> {
+        EDs::from_str_d(s, self.decimals)
+    }
+
+    /// Converts the value to the context decimal points, rounding if some are dropped
+    pub fn convert(&self, value: &EDs) -> EDs {
+        if value.decimals <= self.decimals {
+            return value.clone().to_decimals(self.decimals);
+        }
+
+        EDs::new(
+            div_rounded(
+                &value.val.big(),
+                EDs::base(value.decimals - self.decimals),
+                self.rounding,
+            ),
+            self.decimals,
+        )
+    }
+
+    pub fn add(&self, a: &EDs, b: &EDs) -> EDs {
+        self.convert(a) + self.convert(b)
+    }
+
+    /// Traps, if the result would be negative
+    pub fn sub(&self, a: &EDs, b: &EDs) -> EDs {
+        self.convert(a) - self.convert(b)
+    }
+
+    /// `a * b`, rounded once
+    pub fn mul(&self, a: &EDs, b: &EDs) -> EDs {
+        let (a, b) = (self.convert(a), self.convert(b));
+
+        EDs::new(
+            div_rounded(
+                &(a.val * b.val).big(),
+                EDs::base(self.decimals),
+                self.rounding,
+            ),
+            self.decimals,
+        )
+    }
+
+    /// `a / b`, rounded. Traps, if `b` is zero.
+    pub fn div(&self, a: &EDs, b: &EDs) -> EDs {
+        self.muldiv(a, &self.one(), b)
+    }
+
+    /// `a * mul / div`, rounded once. Traps, if `div` is zero.
+    pub fn muldiv(&self, a: &EDs, mul: &EDs, div: &EDs) -> EDs {
+        let (a, mul, div) = (self.convert(a), self.convert(mul), self.convert(div));
+
+        a.muldiv(&mul, &div, self.rounding)
+    }
+
+    /// Sum of the values, each converted to the context first
+    pub fn sum<'a>(&self, values: impl IntoIterator<Item = &'a EDs>) -> EDs {
+        let val: BigUint = values.into_iter().map(|it| self.convert(it).val).sum();
+
+        EDs::new(val, self.decimals)
+    }
+}
@@ -0,0 +1,64 @@
+use alloc::{format, string::String};
+
+use num_bigint::BigUint;
+
+use crate::c::{E12s, E8s};
+
+/// Cycles are represented as `E12s`, where `1.0` is 1 trillion cycles (1 TC) and the raw value is cycles
+impl E12s {
+    pub fn from_tc(tc: u64) -> Self {
+        Self::new(BigUint::from(tc) * Self::base())
+    }
+
+    pub fn from_cycles(cycles: u128) -> Self {
+        Self::from(cycles)
+    }
+
+    /// Raw cycles, as expected by `ic_cdk` calls. Fails, if the value doesn't fit into `u128`.
+    pub fn to_cycles(&self) -> Result<u128, String> {
+        u128::try_from(&self.val).map_err(|_| format!("{} TC doesn't fit into u128 cycles", self))
+    }
+
+    pub fn to_tc_string(&self) -> String {
+        format!("{} TC", self)
+    }
+
+    /// Converts ICP into cycles at the CMC rate (`xdr_permyriad_per_icp`), given that 1 XDR is 1 TC
+    pub fn from_icp(icp: &E8s, xdr_permyriad_per_icp: u64) -> Self {
+        // 1 ICP = rate / 10^4 TC = rate * 10^8 cycles, so 1 e8s = rate cycles
+        Self::new(&icp.val * xdr_permyriad_per_icp)
+    }
+
+    /// Converts cycles into ICP at the CMC rate (`xdr_permyriad_per_icp`), rounding down.
+    /// Traps if the rate is zero, the CMC never reports one.
+    pub fn to_icp(&self, xdr_permyriad_per_icp: u64) -> E8s {
+        if xdr_permyriad_per_icp == 0 {
+            unreachable!("ICP to XDR rate is zero");
+        }
+
+        E8s::new(&self.val / xdr_permyriad_per_icp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icp_round_trip() {
+        // 1 ICP = 4.5 XDR
+        let icp = E8s::new(150_000_000u64);
+        let cycles = E12s::from_icp(&icp, 45_000);
+
+        assert_eq!(cycles, E12s::new(6_750_000_000_000u64));
+        assert_eq!(cycles.to_icp(45_000), icp);
+        // rounds down
+        assert_eq!(E12s::from_cycles(44_999).to_icp(45_000), E8s::zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "ICP to XDR rate is zero")]
+    fn to_icp_traps_on_zero_rate() {
+        E12s::from_tc(1).to_icp(0);
+    }
+}
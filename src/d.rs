@@ -1,34 +1,60 @@
-use std::{
+use alloc::string::String;
+#[cfg(all(feature = "std", feature = "candid"))]
+use core::cell::RefCell;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
     fmt::Display,
+    hash::{Hash, Hasher},
+    iter::Sum,
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign},
+    str::FromStr,
 };
 
-use candid::{decode_one, encode_one, CandidType, Nat};
+#[cfg(feature = "candid")]
+use candid::{CandidType, Nat};
+#[cfg(feature = "stable-structures")]
 use ic_stable_structures::{storable::Bound, Storable};
 use num_bigint::BigUint;
 use serde::Deserialize;
 
-use crate::{c::ECs, ES_BASES};
+#[cfg(feature = "stable-structures")]
+use crate::storable::{decode_any, encode_compact};
+use crate::{
+    abs_diff_raw,
+    bps::Bps,
+    c::ECs,
+    cmp_raw_u64, fmt_fixed,
+    math::ten_pow,
+    mul_base_raw, parse_decimal_str, quot_base_raw,
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+    within_bps_raw, ES_BASES, MAX_DECIMALS,
+};
 
-/// Fixed-point decimals with primitive math (+-*/) implemented correctly
+/// Fixed-point decimals with primitive math (+-*/) implemented correctly.
+/// The raw value is a `Uint`, stored inline while it fits into `u128`, see `ECs`.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
 pub struct EDs {
-    pub val: BigUint,
+    pub val: Uint,
     pub decimals: u8,
 }
 
 impl EDs {
-    pub fn new(val: BigUint, decimals: u8) -> Self {
-        if decimals > 31 {
-            unreachable!("Decimal points after 31 are not supported");
+    pub fn new(val: impl Into<Uint>, decimals: u8) -> Self {
+        if decimals as usize > MAX_DECIMALS {
+            unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
         }
 
-        Self { val, decimals }
+        Self {
+            val: val.into(),
+            decimals,
+        }
     }
 
     pub fn base(decimals: u8) -> &'static BigUint {
-        if decimals > 31 {
-            unreachable!("Decimal points after 31 are not supported");
+        if decimals as usize > MAX_DECIMALS {
+            unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
         }
 
         // SAFETY: already checked
@@ -36,96 +62,146 @@ impl EDs {
     }
 
     pub fn zero(decimals: u8) -> Self {
-        Self::new(BigUint::ZERO, decimals)
+        Self::new(Uint::ZERO, decimals)
     }
 
     pub fn one(decimals: u8) -> Self {
-        Self {
-            val: Self::base(decimals).clone(),
-            decimals,
+        Self::new(Self::base(decimals), decimals)
+    }
+
+    /// Whole tokens, e.g. `EDs::from_whole(3, 6)` is `3.000000`.
+    /// Unlike `From<(u64, u8)>`, which takes raw units.
+    pub fn from_whole(tokens: u64, decimals: u8) -> Self {
+        Self::new(Self::base(decimals) * tokens, decimals)
+    }
+
+    /// Adds whole tokens, see `from_whole`
+    pub fn add_whole(&self, tokens: u64) -> Self {
+        Self::new(
+            &self.val + Self::base(self.decimals) * tokens,
+            self.decimals,
+        )
+    }
+
+    /// Subtracts whole tokens, see `from_whole`. Traps, if the result would be negative.
+    pub fn sub_whole(&self, tokens: u64) -> Self {
+        Self::new(
+            &self.val - Self::base(self.decimals) * tokens,
+            self.decimals,
+        )
+    }
+
+    /// `numerator / denominator` with the given decimal points, rounded according to the mode.
+    /// Traps, if the denominator is zero.
+    pub fn from_ratio(numerator: u64, denominator: u64, decimals: u8, mode: RoundingMode) -> Self {
+        if denominator == 0 {
+            unreachable!("Zero denominator");
         }
+
+        Self::new(
+            div_rounded(
+                &(Self::base(decimals) * BigUint::from(numerator)),
+                &BigUint::from(denominator),
+                mode,
+            ),
+            decimals,
+        )
     }
 
     pub fn f0_1(decimals: u8) -> Self {
-        Self::new(Self::base(decimals) / BigUint::from(10u64), decimals)
+        Self::from_ratio(1, 10, decimals, RoundingMode::Down)
     }
 
     pub fn f0_2(decimals: u8) -> Self {
-        Self::new(Self::base(decimals) / BigUint::from(5u64), decimals)
+        Self::from_ratio(1, 5, decimals, RoundingMode::Down)
     }
 
     pub fn f0_25(decimals: u8) -> Self {
-        Self::new(Self::base(decimals) / BigUint::from(4u64), decimals)
+        Self::from_ratio(1, 4, decimals, RoundingMode::Down)
     }
 
     pub fn f0_3(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(3u64) / BigUint::from(10u64),
-            decimals,
-        )
+        Self::from_ratio(3, 10, decimals, RoundingMode::Down)
     }
 
     pub fn f0_33(decimals: u8) -> Self {
-        Self::new(Self::base(decimals) / BigUint::from(3u64), decimals)
+        Self::from_ratio(1, 3, decimals, RoundingMode::Down)
     }
 
     pub fn f0_4(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(2u64) / BigUint::from(5u64),
-            decimals,
-        )
+        Self::from_ratio(2, 5, decimals, RoundingMode::Down)
     }
 
     pub fn f0_5(decimals: u8) -> Self {
-        Self::new(Self::base(decimals) / BigUint::from(2u64), decimals)
+        Self::from_ratio(1, 2, decimals, RoundingMode::Down)
     }
 
     pub fn f0_6(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(3u64) / BigUint::from(5u64),
-            decimals,
-        )
+        Self::from_ratio(3, 5, decimals, RoundingMode::Down)
     }
 
     pub fn f0_67(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(2u64) / BigUint::from(3u64),
-            decimals,
-        )
+        Self::from_ratio(2, 3, decimals, RoundingMode::Down)
     }
 
     pub fn f0_7(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(7u64) / BigUint::from(10u64),
-            decimals,
-        )
+        Self::from_ratio(7, 10, decimals, RoundingMode::Down)
     }
 
     pub fn f0_75(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(3u64) / BigUint::from(4u64),
-            decimals,
-        )
+        Self::from_ratio(3, 4, decimals, RoundingMode::Down)
     }
 
     pub fn f0_8(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(4u64) / BigUint::from(5u64),
-            decimals,
-        )
+        Self::from_ratio(4, 5, decimals, RoundingMode::Down)
     }
 
     pub fn f0_9(decimals: u8) -> Self {
-        Self::new(
-            Self::base(decimals) * BigUint::from(9u64) / BigUint::from(10u64),
-            decimals,
-        )
+        Self::from_ratio(9, 10, decimals, RoundingMode::Down)
     }
 
     pub fn two(decimals: u8) -> Self {
         Self::new(Self::base(decimals) * BigUint::from(2u64), decimals)
     }
 
+    pub fn is_zero(&self) -> bool {
+        self.val.is_zero()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.val == *Self::base(self.decimals)
+    }
+
+    /// The whole part, e.g. `12` for `12.34`
+    pub fn whole(&self) -> BigUint {
+        &self.val / Self::base(self.decimals)
+    }
+
+    /// Raw value of the fractional part, e.g. `34` for `12.34` with 2 decimal points
+    pub fn fract_raw(&self) -> BigUint {
+        &self.val % Self::base(self.decimals)
+    }
+
+    /// Significant bits of the raw value, 0 for zero
+    pub fn bits(&self) -> u64 {
+        self.val.bits()
+    }
+
+    /// Minimal number of bytes holding the raw value, 0 for zero
+    pub fn byte_len(&self) -> usize {
+        self.val.bits().div_ceil(8) as usize
+    }
+
+    /// Whether the raw value can be passed as `u64`, e.g. to the ICP ledger
+    pub fn fits_u64(&self) -> bool {
+        self.val.bits() <= 64
+    }
+
+    /// Whether the raw value can be converted to `u128`
+    pub fn fits_u128(&self) -> bool {
+        self.val.bits() <= 128
+    }
+
     pub fn sqrt(&self) -> Self {
         let base = Self::base(self.decimals);
         let whole = &self.val / base;
@@ -134,6 +210,65 @@ impl EDs {
         Self::new(sqrt_whole * base, self.decimals)
     }
 
+    /// Square root at full scale (`isqrt(val * base)`), rounded down.
+    /// Unlike `sqrt`, keeps the fractional part: `sqrt_precise(2.25) = 1.5`.
+    pub fn sqrt_precise(&self) -> Self {
+        Self::new(
+            (&self.val * Self::base(self.decimals)).sqrt(),
+            self.decimals,
+        )
+    }
+
+    /// Cube root at full scale, rounded down, see `nth_root`
+    pub fn cbrt(&self) -> Self {
+        self.nth_root(3)
+    }
+
+    /// `n`-th root at full scale (`iroot(val * base^(n-1), n)`), rounded down. Traps, if `n` is 0.
+    pub fn nth_root(&self, n: u32) -> Self {
+        if n == 0 {
+            unreachable!("Zeroth root is undefined");
+        }
+
+        Self::new(
+            (&self.val * Self::base(self.decimals).pow(n - 1)).nth_root(n),
+            self.decimals,
+        )
+    }
+
+    /// Applies NNS/SNS-style maturity modulation: `self * (10_000 + bps) / 10_000`, rounding down.
+    /// Modulation of -100% or lower results in zero.
+    pub fn apply_modulation_bps(&self, bps: i32) -> Self {
+        let multiplier = (10_000i64 + bps as i64).max(0) as u64;
+
+        Self::new(&self.val * multiplier / 10_000u64, self.decimals)
+    }
+
+    /// Parses a "123.456" string into a value with exactly `decimals` decimal points
+    pub fn from_str_d(s: &str, decimals: u8) -> Result<Self, String> {
+        let (val, _) = parse_decimal_str(s, Some(decimals as usize))?;
+
+        Ok(Self::new(val, decimals))
+    }
+
+    /// Borrowing `to_const`: the value as `ECs<D>` without moving or cloning it,
+    /// `None` if the decimal points differ
+    pub fn as_const<const D: usize>(&self) -> Option<&ECs<D>> {
+        if self.decimals as usize != D {
+            return None;
+        }
+
+        Some(ECs::from_raw_ref(&self.val))
+    }
+
+    /// Borrowed view of the value, see `EDsRef`
+    pub fn as_dynamic(&self) -> EDsRef<'_> {
+        EDsRef {
+            val: &self.val,
+            decimals: self.decimals,
+        }
+    }
+
     pub fn to_const<const D: usize>(self) -> ECs<D> {
         if self.decimals != D as u8 {
             unreachable!(
@@ -157,23 +292,331 @@ impl EDs {
         };
 
         let base = Self::base(dif);
-        self.val = if mul {
-            self.val * base
+        if mul {
+            self.val *= base;
         } else {
-            self.val / base
-        };
+            self.val /= base;
+        }
 
         self.decimals = new_decimals;
 
         self
     }
+
+    /// Multiplies the value by 10^n, keeping the decimal points (unlike `to_decimals`)
+    pub fn scale_up(&self, n: u8) -> Self {
+        Self::new(&self.val * ten_pow(n as usize), self.decimals)
+    }
+
+    /// Divides the value by 10^n, keeping the decimal points (unlike `to_decimals`)
+    pub fn scale_down(&self, n: u8, mode: RoundingMode) -> Self {
+        Self::new(
+            div_rounded(&self.val.big(), &ten_pow(n as usize), mode),
+            self.decimals,
+        )
+    }
+
+    /// Exact product with the sum of both decimal points, nothing is truncated.
+    /// Round it explicitly afterwards, e.g. via `to_decimals`.
+    /// Traps if the sum is above `MAX_DECIMALS`, e.g. for two 40-decimals values.
+    pub fn mul_full(&self, other: &Self) -> Self {
+        let decimals = self.decimals as usize + other.decimals as usize;
+        if decimals > MAX_DECIMALS {
+            unreachable!("The product has more than {} decimal points", MAX_DECIMALS);
+        }
+
+        Self::new(&self.val * &other.val, decimals as u8)
+    }
+
+    /// `self * mul / div` at full intermediate precision with a single rounding step
+    pub fn muldiv(&self, mul: &Self, div: &Self, mode: RoundingMode) -> Self {
+        if self.decimals != mul.decimals || self.decimals != div.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        Self::new(
+            div_rounded(&(&self.val * &mul.val).big(), &div.val.big(), mode),
+            self.decimals,
+        )
+    }
+
+    /// `self * rhs`, written into `out`. `out` takes the decimal points of `self`.
+    pub fn mul_into(&self, rhs: &Self, out: &mut Self) {
+        if self.decimals != rhs.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        out.val = mul_base_raw(&self.val, &rhs.val, self.decimals as usize);
+        out.decimals = self.decimals;
+    }
+
+    /// `self / rhs`, written into `out`. `out` takes the decimal points of `self`.
+    pub fn div_into(&self, rhs: &Self, out: &mut Self) {
+        if self.decimals != rhs.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        out.val = quot_base_raw(&self.val, &rhs.val, self.decimals as usize);
+        out.decimals = self.decimals;
+    }
+
+    /// Linear interpolation between `self` (at `t = 0`) and `other` (at `t = 1`),
+    /// rounded once according to the mode. Traps, if `t` is above 1.
+    pub fn lerp(&self, other: &Self, t: &Self, mode: RoundingMode) -> Self {
+        if self.decimals != other.decimals || self.decimals != t.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        let base = Self::base(self.decimals);
+        if t.val > *base {
+            unreachable!("Interpolation parameter should be in [0, 1]");
+        }
+
+        let n = &self.val * (base - &t.val) + &other.val * &t.val;
+
+        Self::new(div_rounded(&n, base, mode), self.decimals)
+    }
+
+    /// `self * mul + add`, truncated once at the end
+    pub fn fma(&self, mul: &Self, add: &Self) -> Self {
+        if self.decimals != mul.decimals || self.decimals != add.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        Self::new(
+            &self.val * &mul.val / Self::base(self.decimals) + &add.val,
+            self.decimals,
+        )
+    }
+
+    /// Compares numeric values regardless of decimal points, so `1.0` at 8 decimals
+    /// equals `1.0` at 6 decimals. The derived `Ord` compares raw fields instead.
+    pub fn cmp_value(&self, other: &Self) -> Ordering {
+        match self.decimals.cmp(&other.decimals) {
+            Ordering::Equal => self.val.cmp(&other.val),
+            Ordering::Less => {
+                (&self.val * Self::base(other.decimals - self.decimals)).cmp(&other.val.big())
+            }
+            Ordering::Greater => (self.val.big().as_ref())
+                .cmp(&(&other.val * Self::base(self.decimals - other.decimals))),
+        }
+    }
+
+    /// Numeric equality regardless of decimal points, see `cmp_value`
+    pub fn eq_value(&self, other: &Self) -> bool {
+        self.cmp_value(other) == Ordering::Equal
+    }
+
+    /// Hashes the numeric value with trailing zeros stripped, consistent with `eq_value`
+    pub fn hash_value<H: Hasher>(&self, state: &mut H) {
+        let mut val = self.val.clone();
+        let mut decimals = self.decimals;
+
+        while decimals > 0 && (&val % 10u64).is_zero() {
+            val /= 10u64;
+            decimals -= 1;
+        }
+
+        val.hash(state);
+        decimals.hash(state);
+    }
+
+    fn check_decimals(&self, other: &Self) {
+        if self.decimals != other.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+    }
+
+    /// `|self - other| <= epsilon`, e.g. for reconciling tracked balances with the ledger ones.
+    /// Traps on incompatible decimal points.
+    pub fn approx_eq(&self, other: &Self, epsilon: &Self) -> bool {
+        self.check_decimals(other);
+        self.check_decimals(epsilon);
+
+        abs_diff_raw(&self.val, &other.val) <= epsilon.val
+    }
+
+    /// Whether the values differ by at most `bps` of the bigger one. Traps on incompatible
+    /// decimal points.
+    pub fn within_bps(&self, other: &Self, bps: Bps) -> bool {
+        self.check_decimals(other);
+
+        within_bps_raw(&self.val, &other.val, bps.0)
+    }
+
+    /// By-ref `Ord::min`, returning an owned value. Traps on incompatible decimal points.
+    pub fn min_ref(&self, other: &Self) -> Self {
+        self.check_decimals(other);
+
+        if other.val < self.val {
+            other.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// By-ref `Ord::max`, returning an owned value. Traps on incompatible decimal points.
+    pub fn max_ref(&self, other: &Self) -> Self {
+        self.check_decimals(other);
+
+        if other.val > self.val {
+            other.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// By-ref `Ord::clamp`, returning an owned value. Traps on incompatible decimal points
+    /// or if `lo > hi`.
+    pub fn clamp_ref(&self, lo: &Self, hi: &Self) -> Self {
+        self.check_decimals(lo);
+        self.check_decimals(hi);
+
+        if lo.val > hi.val {
+            unreachable!("Invalid clamp bounds: {} > {}", lo, hi);
+        }
+
+        if self.val < lo.val {
+            lo.clone()
+        } else if self.val > hi.val {
+            hi.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Rescales both operands to the higher of their decimal points
+    fn aligned(&self, other: &Self) -> (Self, Self) {
+        let decimals = self.decimals.max(other.decimals);
+
+        (
+            self.clone().to_decimals(decimals),
+            other.clone().to_decimals(decimals),
+        )
+    }
+
+    /// Like `+`, but aligns decimal points instead of trapping on mismatch.
+    /// The result has the higher of the two decimal points.
+    pub fn add_aligned(&self, other: &Self) -> Self {
+        let (a, b) = self.aligned(other);
+
+        a + b
+    }
+
+    /// Like `-`, but aligns decimal points instead of trapping on mismatch.
+    /// The result has the higher of the two decimal points.
+    pub fn sub_aligned(&self, other: &Self) -> Self {
+        let (a, b) = self.aligned(other);
+
+        a - b
+    }
+
+    /// Like `*`, but aligns decimal points instead of trapping on mismatch.
+    /// The result has the higher of the two decimal points.
+    pub fn mul_aligned(&self, other: &Self) -> Self {
+        let (a, b) = self.aligned(other);
+
+        a * b
+    }
+
+    /// Like `/`, but aligns decimal points instead of trapping on mismatch.
+    /// The result has the higher of the two decimal points.
+    pub fn div_aligned(&self, other: &Self) -> Self {
+        let (a, b) = self.aligned(other);
+
+        a / b
+    }
+
+    /// Sums values with the given decimal points, so an empty iterator gives
+    /// `EDs::zero(decimals)`, unlike `Sum`, which can't know them and gives 0 decimals.
+    /// Traps if any value has different decimal points.
+    pub fn sum_with_decimals<I, T>(iter: I, decimals: u8) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Borrow<EDs>,
+    {
+        iter.into_iter().fold(Self::zero(decimals), |mut acc, it| {
+            acc += it.borrow();
+            acc
+        })
+    }
 }
 
+/// Exactly `decimals` zero-padded digits after the point, e.g. "1.05000000", and no point if
+/// there are no decimal points
 impl Display for EDs {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let base = Self::base(self.decimals);
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt_fixed(&self.val, self.decimals as usize, f)
+    }
+}
 
-        f.write_str(&format!("{}.{}", &self.val / base, &self.val % base))
+/// `EDs` compared, ordered and hashed by numeric value regardless of decimal points,
+/// e.g. to use amounts of different tokens as keys of the same ordered map
+#[derive(Clone, Debug, Default)]
+pub struct EDsByValue(pub EDs);
+
+impl PartialEq for EDsByValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_value(&other.0)
+    }
+}
+
+impl Eq for EDsByValue {}
+
+impl PartialOrd for EDsByValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EDsByValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp_value(&other.0)
+    }
+}
+
+impl Hash for EDsByValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash_value(state)
+    }
+}
+
+impl From<EDs> for EDsByValue {
+    fn from(value: EDs) -> Self {
+        Self(value)
+    }
+}
+
+impl Display for EDsByValue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Sorts amounts with (possibly) different decimal points by numeric value, see `cmp_value`.
+/// The sort is stable, so equal values keep their order.
+pub fn sort_by_value(values: &mut [EDs]) {
+    values.sort_by(|a, b| a.cmp_value(b));
+}
+
+/// The numerically biggest amount (the last one on ties), see `cmp_value`
+pub fn max_by_value(values: &[EDs]) -> Option<&EDs> {
+    values.iter().max_by(|a, b| a.cmp_value(b))
+}
+
+/// The numerically smallest amount (the first one on ties), see `cmp_value`
+pub fn min_by_value(values: &[EDs]) -> Option<&EDs> {
+    values.iter().min_by(|a, b| a.cmp_value(b))
+}
+
+/// Decimal points are inferred from the string, so "1.50" is parsed into a 2-decimals value
+impl FromStr for EDs {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (val, decimals) = parse_decimal_str(s, None)?;
+
+        Ok(Self::new(val, decimals as u8))
     }
 }
 
@@ -290,7 +733,7 @@ impl Mul for &EDs {
         }
 
         EDs::new(
-            &self.val * &rhs.val / EDs::base(self.decimals),
+            mul_base_raw(&self.val, &rhs.val, self.decimals as usize),
             self.decimals,
         )
     }
@@ -324,7 +767,7 @@ impl Mul<u64> for &EDs {
     type Output = EDs;
 
     fn mul(self, rhs: u64) -> Self::Output {
-        EDs::new(&self.val * BigUint::from(rhs), self.decimals)
+        EDs::new(&self.val * rhs, self.decimals)
     }
 }
 
@@ -332,7 +775,7 @@ impl Mul<u64> for EDs {
     type Output = EDs;
 
     fn mul(self, rhs: u64) -> Self::Output {
-        EDs::new(self.val * BigUint::from(rhs), self.decimals)
+        EDs::new(self.val * rhs, self.decimals)
     }
 }
 
@@ -342,7 +785,7 @@ impl MulAssign<&EDs> for EDs {
             unreachable!("Incompatible decimal points");
         }
 
-        self.val = &self.val * &rhs.val / EDs::base(self.decimals)
+        self.val = mul_base_raw(&self.val, &rhs.val, self.decimals as usize);
     }
 }
 
@@ -354,7 +797,7 @@ impl MulAssign for EDs {
 
 impl MulAssign<u64> for EDs {
     fn mul_assign(&mut self, rhs: u64) {
-        self.val *= BigUint::from(rhs);
+        self.val *= rhs;
     }
 }
 
@@ -367,7 +810,7 @@ impl Div for &EDs {
         }
 
         EDs::new(
-            &self.val * EDs::base(self.decimals) / &rhs.val,
+            quot_base_raw(&self.val, &rhs.val, self.decimals as usize),
             self.decimals,
         )
     }
@@ -385,7 +828,7 @@ impl Div<u64> for EDs {
     type Output = EDs;
 
     fn div(self, rhs: u64) -> Self::Output {
-        EDs::new(self.val / BigUint::from(rhs), self.decimals)
+        EDs::new(self.val / rhs, self.decimals)
     }
 }
 
@@ -393,7 +836,7 @@ impl Div<u64> for &EDs {
     type Output = EDs;
 
     fn div(self, rhs: u64) -> Self::Output {
-        EDs::new(&self.val / BigUint::from(rhs), self.decimals)
+        EDs::new(&self.val / rhs, self.decimals)
     }
 }
 
@@ -419,7 +862,7 @@ impl DivAssign<&EDs> for EDs {
             unreachable!("Incompatible decimal points");
         }
 
-        *(&mut self.val) = &self.val * EDs::base(self.decimals) / &rhs.val;
+        self.val = quot_base_raw(&self.val, &rhs.val, self.decimals as usize);
     }
 }
 
@@ -431,34 +874,169 @@ impl DivAssign for EDs {
 
 impl DivAssign<u64> for EDs {
     fn div_assign(&mut self, rhs: u64) {
-        self.val /= BigUint::from(rhs);
+        self.val /= rhs;
+    }
+}
+
+/// Borrowed `EDs`, e.g. from `ECs::as_dynamic`, for code taking dynamic values without
+/// requiring an owned `EDs`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EDsRef<'a> {
+    pub val: &'a Uint,
+    pub decimals: u8,
+}
+
+impl<'a> EDsRef<'a> {
+    pub fn to_eds(&self) -> EDs {
+        EDs::new(self.val.clone(), self.decimals)
+    }
+
+    /// See `EDs::as_const`
+    pub fn as_const<const D: usize>(&self) -> Option<&'a ECs<D>> {
+        if self.decimals as usize != D {
+            return None;
+        }
+
+        Some(ECs::from_raw_ref(self.val))
     }
 }
 
+impl Display for EDsRef<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt_fixed(self.val, self.decimals as usize, f)
+    }
+}
+
+impl<'a> From<&'a EDs> for EDsRef<'a> {
+    fn from(value: &'a EDs) -> Self {
+        value.as_dynamic()
+    }
+}
+
+impl From<EDsRef<'_>> for EDs {
+    fn from(value: EDsRef<'_>) -> Self {
+        value.to_eds()
+    }
+}
+
+/// Traps on incompatible decimal points. An empty iterator sums to zero with 0 decimals,
+/// which traps when added to a value with decimal points; if the iterator may be empty, use
+/// `EDs::sum_with_decimals` instead.
+impl Sum for EDs {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|acc, it| acc + it).unwrap_or_default()
+    }
+}
+
+/// Traps on incompatible decimal points. An empty iterator sums to zero with 0 decimals,
+/// which traps when added to a value with decimal points; if the iterator may be empty, use
+/// `EDs::sum_with_decimals` instead.
+impl<'a> Sum<&'a EDs> for EDs {
+    fn sum<I: Iterator<Item = &'a EDs>>(mut iter: I) -> Self {
+        match iter.next() {
+            Some(first) => iter.fold(first.clone(), |acc, it| acc + it),
+            None => Self::default(),
+        }
+    }
+}
+
+/// Raw units and decimal points, e.g. `EDs::from((1_500_000, 6))` is `1.5`.
+/// For whole tokens see `from_whole`.
 impl From<(u64, u8)> for EDs {
     fn from((value, decimals): (u64, u8)) -> Self {
         Self::new(BigUint::from(value), decimals)
     }
 }
 
-impl Into<Nat> for EDs {
-    fn into(self) -> Nat {
-        Nat(self.val)
+#[cfg(feature = "candid")]
+impl From<EDs> for Nat {
+    fn from(value: EDs) -> Self {
+        Nat(value.val.into_big())
+    }
+}
+
+#[cfg(feature = "candid")]
+impl From<&EDs> for Nat {
+    fn from(value: &EDs) -> Self {
+        Nat(value.val.big().into_owned())
+    }
+}
+
+/// Compares raw units, e.g. `if amount < MIN_RAW_E8S`
+impl PartialEq<u64> for EDs {
+    fn eq(&self, other: &u64) -> bool {
+        cmp_raw_u64(&self.val, *other) == Ordering::Equal
     }
 }
 
-impl Into<Nat> for &EDs {
-    fn into(self) -> Nat {
-        Nat(self.val.clone())
+impl PartialOrd<u64> for EDs {
+    fn partial_cmp(&self, other: &u64) -> Option<Ordering> {
+        Some(cmp_raw_u64(&self.val, *other))
     }
 }
 
+/// Compares raw units, e.g. `if balance >= ledger_fee_nat`
+#[cfg(feature = "candid")]
+impl PartialEq<Nat> for EDs {
+    fn eq(&self, other: &Nat) -> bool {
+        self.val == other.0
+    }
+}
+
+#[cfg(feature = "candid")]
+impl PartialOrd<Nat> for EDs {
+    fn partial_cmp(&self, other: &Nat) -> Option<Ordering> {
+        self.val.partial_cmp(&other.0)
+    }
+}
+
+#[cfg(feature = "candid")]
 #[derive(CandidType, Deserialize)]
 pub struct EDsCandid {
     pub val: Nat,
     pub decimals: u8,
 }
 
+#[cfg(all(feature = "std", feature = "candid"))]
+thread_local! {
+    /// Reused by serialization, so encoding doesn't allocate a `Nat` per value, see `with_nat`
+    static EDS_CANDID_SCRATCH: RefCell<EDsCandid> = const {
+        RefCell::new(EDsCandid {
+            val: Nat(BigUint::ZERO),
+            decimals: 0,
+        })
+    };
+}
+
+#[cfg(feature = "candid")]
+impl EDs {
+    /// Passes the value as `EDsCandid`, reusing a thread-local scratch one, see `with_nat`
+    #[cfg(feature = "std")]
+    fn with_candid<R>(&self, f: impl FnOnce(&EDsCandid) -> R) -> R {
+        EDS_CANDID_SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+            Ok(mut candid) => {
+                self.val.clone_into_big(&mut candid.val.0);
+                candid.decimals = self.decimals;
+                f(&candid)
+            }
+            Err(_) => f(&self.to_candid()),
+        })
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_candid<R>(&self, f: impl FnOnce(&EDsCandid) -> R) -> R {
+        f(&self.to_candid())
+    }
+
+    fn to_candid(&self) -> EDsCandid {
+        EDsCandid {
+            val: Nat(self.val.big().into_owned()),
+            decimals: self.decimals,
+        }
+    }
+}
+
+#[cfg(feature = "candid")]
 impl CandidType for EDs {
     fn _ty() -> candid::types::Type {
         EDsCandid::_ty()
@@ -468,14 +1046,11 @@ impl CandidType for EDs {
     where
         S: candid::types::Serializer,
     {
-        (EDsCandid {
-            val: Nat(self.val.clone()),
-            decimals: self.decimals,
-        })
-        .idl_serialize(serializer)
+        self.with_candid(|candid| candid.idl_serialize(serializer))
     }
 }
 
+#[cfg(feature = "candid")]
 impl<'de> Deserialize<'de> for EDs {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -487,14 +1062,124 @@ impl<'de> Deserialize<'de> for EDs {
     }
 }
 
+/// Without Candid, `EDs` keeps the same `{ val, decimals }` shape, but `val` is a string of
+/// raw units, e.g. `{ "val": "150000000", "decimals": 8 }`
+#[cfg(not(feature = "candid"))]
+#[derive(Deserialize)]
+struct EDsRepr {
+    #[serde(deserialize_with = "crate::serde_raw::deserialize")]
+    val: BigUint,
+    decimals: u8,
+}
+
+#[cfg(not(feature = "candid"))]
+impl serde::Serialize for EDs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut s = serializer.serialize_struct("EDs", 2)?;
+        s.serialize_field("val", &crate::serde_raw::Raw(&self.val))?;
+        s.serialize_field("decimals", &self.decimals)?;
+        s.end()
+    }
+}
+
+#[cfg(not(feature = "candid"))]
+impl<'de> Deserialize<'de> for EDs {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let a = EDsRepr::deserialize(deserializer)?;
+
+        Ok(Self::new(a.val, a.decimals))
+    }
+}
+
+/// Stored in the compact layout (version byte, decimals, LE magnitude bytes).
+/// Values stored by previous versions of the crate in Candid are still decoded.
+#[cfg(feature = "stable-structures")]
 impl Storable for EDs {
-    fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
-        std::borrow::Cow::Owned(encode_one(self).expect("Unable to encode"))
+    fn to_bytes(&self) -> alloc::borrow::Cow<'_, [u8]> {
+        alloc::borrow::Cow::Owned(encode_compact(self))
     }
 
-    fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
-        decode_one(&bytes).expect("Unable to decode")
+    fn from_bytes(bytes: alloc::borrow::Cow<[u8]>) -> Self {
+        decode_any(&bytes)
     }
 
     const BOUND: Bound = Bound::Unbounded;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eds(raw: u64, decimals: u8) -> EDs {
+        EDs::new(BigUint::from(raw), decimals)
+    }
+
+    #[test]
+    fn sqrt_precise_edge_cases() {
+        assert_eq!(EDs::zero(8).sqrt_precise(), EDs::zero(8));
+        assert_eq!(eds(1, 8).sqrt_precise(), eds(10_000, 8));
+        assert_eq!(eds(225, 2).sqrt_precise(), eds(150, 2));
+        assert_eq!(eds(399_999_999, 8).sqrt_precise(), eds(199_999_999, 8));
+        // keeps the decimals of the argument
+        assert_eq!(eds(4, 0).sqrt_precise(), eds(2, 0));
+        assert_eq!(eds(3, 0).sqrt_precise(), eds(1, 0));
+    }
+
+    #[test]
+    fn sqrt_precise_at_max_decimals() {
+        let four = EDs::new(EDs::base(76) * 4u32, 76);
+        assert_eq!(four.sqrt_precise(), EDs::new(EDs::base(76) * 2u32, 76));
+
+        let max = EDs::new((BigUint::from(1u32) << 256u32) - 1u32, 76);
+        let r = max.sqrt_precise();
+        let n = &max.val * EDs::base(76);
+        let next = &r.val + 1u32;
+
+        assert!(&r.val * &r.val <= n);
+        assert!(&next * &next > n);
+    }
+
+    #[test]
+    fn sum_with_decimals_keeps_decimals_when_empty() {
+        let empty: [EDs; 0] = [];
+
+        let sum = EDs::sum_with_decimals(&empty, 8);
+        assert_eq!(sum, EDs::zero(8));
+        assert_eq!(sum.decimals, 8);
+        // unlike `Sum`, the empty sum can be added to other 8-decimals values
+        assert_eq!(sum + eds(5, 8), eds(5, 8));
+        assert_eq!(empty.iter().sum::<EDs>().decimals, 0);
+
+        let values = [eds(150, 8), eds(250, 8)];
+        assert_eq!(EDs::sum_with_decimals(&values, 8), eds(400, 8));
+        assert_eq!(EDs::sum_with_decimals(values, 8), eds(400, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible decimal points")]
+    fn sum_with_decimals_traps_on_other_decimals() {
+        EDs::sum_with_decimals([eds(1, 8), eds(1, 6)], 8);
+    }
+
+    #[test]
+    fn mul_full_sums_decimals() {
+        assert_eq!(eds(15, 1).mul_full(&eds(25, 2)), eds(375, 3));
+
+        let a = EDs::new(EDs::base(38) * 2u32, 38);
+        assert_eq!(a.mul_full(&a), EDs::new(EDs::base(76) * 4u32, 76));
+    }
+
+    #[test]
+    #[should_panic(expected = "The product has more than 76 decimal points")]
+    fn mul_full_traps_above_max_decimals() {
+        eds(1, 40).mul_full(&eds(1, 40));
+    }
+}
@@ -0,0 +1,283 @@
+use crate::{c::ECs, uint::Uint};
+
+/// 365 days
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+/// Emission per interval of an annual total, rounded down.
+/// Summing it up loses dust every interval, use `EmissionIter` to emit exactly.
+pub fn emission_per_interval<const D: usize>(
+    total_per_year: &ECs<D>,
+    interval_secs: u64,
+) -> ECs<D> {
+    ECs::new(&total_per_year.val * interval_secs / SECONDS_PER_YEAR)
+}
+
+/// Emissions for consecutive intervals, carrying the rounding remainder over, so
+/// everything emitted after `t` seconds is exactly `total_per_year * t / SECONDS_PER_YEAR`
+/// rounded down. In particular, a year worth of intervals emits exactly `total_per_year`,
+/// when the interval divides the year.
+#[derive(Clone, Debug)]
+pub struct EmissionIter<const D: usize> {
+    total_per_year: ECs<D>,
+    interval_secs: u64,
+    elapsed_secs: u64,
+    emitted: Uint,
+}
+
+impl<const D: usize> EmissionIter<D> {
+    /// Traps, if the interval is zero
+    pub fn new(total_per_year: ECs<D>, interval_secs: u64) -> Self {
+        if interval_secs == 0 {
+            unreachable!("Emission interval can't be zero");
+        }
+
+        Self {
+            total_per_year,
+            interval_secs,
+            elapsed_secs: 0,
+            emitted: Uint::ZERO,
+        }
+    }
+
+    /// Everything emitted so far
+    pub fn emitted(&self) -> ECs<D> {
+        ECs::new(self.emitted.clone())
+    }
+
+    pub fn elapsed_secs(&self) -> u64 {
+        self.elapsed_secs
+    }
+}
+
+impl<const D: usize> Iterator for EmissionIter<D> {
+    type Item = ECs<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.elapsed_secs = self.elapsed_secs.checked_add(self.interval_secs)?;
+
+        let cumulative = &self.total_per_year.val * self.elapsed_secs / SECONDS_PER_YEAR;
+        let amount = &cumulative - &self.emitted;
+        self.emitted = cumulative;
+
+        Some(ECs::new(amount))
+    }
+}
+
+/// Emission of `epoch` (starting at 0) when it halves every `halving_epochs` epochs:
+/// `initial_per_epoch / 2^(epoch / halving_epochs)`, rounded down. Traps, if `halving_epochs` is 0.
+pub fn halving_emission<const D: usize>(
+    initial_per_epoch: &ECs<D>,
+    halving_epochs: u64,
+    epoch: u64,
+) -> ECs<D> {
+    if halving_epochs == 0 {
+        unreachable!("Halving interval can't be zero");
+    }
+
+    ECs::new(&initial_per_epoch.val >> (epoch / halving_epochs))
+}
+
+/// Total emission of the first `epochs` epochs under `halving_emission`, exactly the sum of them
+pub fn halving_cumulative<const D: usize>(
+    initial_per_epoch: &ECs<D>,
+    halving_epochs: u64,
+    epochs: u64,
+) -> ECs<D> {
+    if halving_epochs == 0 {
+        unreachable!("Halving interval can't be zero");
+    }
+
+    let full_periods = epochs / halving_epochs;
+    let rest = epochs % halving_epochs;
+
+    let mut sum = Uint::ZERO;
+    let mut period = 0u64;
+
+    while period < full_periods {
+        let per_epoch = &initial_per_epoch.val >> period;
+        if per_epoch.is_zero() {
+            break;
+        }
+
+        sum += per_epoch * halving_epochs;
+        period += 1;
+    }
+
+    if period == full_periods {
+        sum += (&initial_per_epoch.val >> full_periods) * rest;
+    }
+
+    ECs::new(sum)
+}
+
+/// Total emission of the first `epochs` epochs, when every epoch emits `decay_per_epoch`
+/// less than the previous one, starting with `initial_per_epoch`:
+/// `initial * (1 - q^epochs) / (1 - q)`, where `q = 1 - decay_per_epoch`, rounded down once.
+/// Evaluated exactly, so the cost grows with the amount of epochs.
+/// Traps, if `decay_per_epoch` is zero or above 1.
+pub fn decay_cumulative<const D: usize>(
+    initial_per_epoch: &ECs<D>,
+    decay_per_epoch: &ECs<D>,
+    epochs: u32,
+) -> ECs<D> {
+    let base = ECs::<D>::base();
+    if decay_per_epoch.val.is_zero() || decay_per_epoch.val > *base {
+        unreachable!("Decay should be in (0, 1]");
+    }
+
+    let q = base - &decay_per_epoch.val;
+    let base_n = base.pow(epochs);
+    let q_n = q.pow(epochs);
+
+    ECs::new(&initial_per_epoch.val * (&base_n - q_n) * base / (base_n * &decay_per_epoch.val))
+}
+
+/// Emission of `epoch` (starting at 0) under `decay_cumulative`, defined as the difference
+/// of cumulative totals, so that per-epoch emissions always sum up to the cumulative one
+pub fn decay_emission<const D: usize>(
+    initial_per_epoch: &ECs<D>,
+    decay_per_epoch: &ECs<D>,
+    epoch: u32,
+) -> ECs<D> {
+    let next = epoch
+        .checked_add(1)
+        .unwrap_or_else(|| unreachable!("Epoch is too big"));
+
+    decay_cumulative(initial_per_epoch, decay_per_epoch, next)
+        - decay_cumulative(initial_per_epoch, decay_per_epoch, epoch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    #[test]
+    fn a_year_of_intervals_emits_exactly_the_total() {
+        // not divisible by the amount of intervals
+        let total = E8s::new(1_000_000_000_007u64);
+
+        for interval in [60, 3_600, 86_400] {
+            let intervals = SECONDS_PER_YEAR / interval;
+            let mut iter = EmissionIter::new(total.clone(), interval);
+
+            let sum: E8s = iter.by_ref().take(intervals as usize).sum();
+
+            assert_eq!(sum, total);
+            assert_eq!(iter.emitted(), total);
+            assert_eq!(iter.elapsed_secs(), SECONDS_PER_YEAR);
+
+            // while the naive per-interval amount loses dust
+            let naive = emission_per_interval(&total, interval);
+            assert!(&naive.val * intervals < total.val);
+        }
+    }
+
+    #[test]
+    fn emitted_after_t_is_rounded_down_once() {
+        let total = E8s::new(1_000u64);
+        let mut iter = EmissionIter::new(total.clone(), 1_000_000);
+
+        let first = iter.next().unwrap();
+        let second = iter.next().unwrap();
+
+        assert_eq!(first, emission_per_interval(&total, 1_000_000));
+        assert_eq!(first, E8s::new(31u64));
+        // 2_000_000 seconds emit 63.4 in total
+        assert_eq!(second, E8s::new(32u64));
+        assert_eq!(iter.emitted(), E8s::new(63u64));
+    }
+
+    #[test]
+    fn stops_when_time_overflows() {
+        let mut iter = EmissionIter::new(E8s::new(1u64), u64::MAX);
+
+        assert!(iter.next().is_some());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Emission interval can't be zero")]
+    fn zero_interval_traps() {
+        EmissionIter::new(E8s::new(1u64), 0);
+    }
+
+    #[test]
+    fn halving_cumulative_sums_halving_emissions() {
+        let initial = E8s::new(1_000_000_007u64);
+
+        assert_eq!(halving_emission(&initial, 10, 9), initial);
+        assert_eq!(halving_emission(&initial, 10, 10), E8s::new(500_000_003u64));
+        assert_eq!(halving_emission(&initial, 10, 25), E8s::new(250_000_001u64));
+
+        let mut sum = E8s::zero();
+        for epoch in 0..400 {
+            assert_eq!(halving_cumulative(&initial, 7, epoch), sum);
+            sum += halving_emission(&initial, 7, epoch);
+        }
+    }
+
+    #[test]
+    fn halving_runs_out() {
+        let initial = E8s::new(1_000u64);
+        // 1000 + 500 + 250 + 125 + 62 + 31 + 15 + 7 + 3 + 1
+        let total = E8s::new(1_994u64);
+
+        assert_eq!(halving_emission(&initial, 1, u64::MAX), E8s::zero());
+        assert_eq!(halving_cumulative(&initial, 1, u64::MAX), total);
+        assert_eq!(
+            halving_cumulative(&initial, 3, u64::MAX),
+            E8s::new(5_982u64)
+        );
+        assert_eq!(
+            halving_cumulative(&initial, u64::MAX, u64::MAX - 1),
+            E8s::new(1_000u128 * (u64::MAX - 1) as u128)
+        );
+    }
+
+    #[test]
+    fn decay_emissions_sum_to_cumulative() {
+        let initial = E8s::new(100_000_000u64);
+        let half = E8s::new(50_000_000u64);
+
+        assert_eq!(decay_cumulative(&initial, &half, 0), E8s::zero());
+        assert_eq!(decay_cumulative(&initial, &half, 1), initial);
+        assert_eq!(
+            decay_cumulative(&initial, &half, 3),
+            E8s::new(175_000_000u64)
+        );
+        assert_eq!(decay_emission(&initial, &half, 2), E8s::new(25_000_000u64));
+
+        let decay = E8s::new(3_000_000u64);
+        let mut sum = E8s::zero();
+        for epoch in 0..200 {
+            sum += decay_emission(&initial, &decay, epoch);
+        }
+        assert_eq!(sum, decay_cumulative(&initial, &decay, 200));
+        // approaches `initial / decay`
+        assert!(sum < E8s::new(3_333_333_334u64));
+
+        // full decay emits only the first epoch
+        let one = E8s::new(100_000_000u64);
+        assert_eq!(decay_cumulative(&initial, &one, 50), initial);
+        assert_eq!(decay_emission(&initial, &one, 1), E8s::zero());
+    }
+
+    #[test]
+    #[should_panic(expected = "Decay should be in (0, 1]")]
+    fn zero_decay_traps() {
+        decay_cumulative(&E8s::new(1u64), &E8s::zero(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Decay should be in (0, 1]")]
+    fn decay_above_one_traps() {
+        decay_cumulative(&E8s::new(1u64), &E8s::new(100_000_001u64), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Halving interval can't be zero")]
+    fn zero_halving_interval_traps() {
+        halving_cumulative(&E8s::new(1u64), 0, 1);
+    }
+}
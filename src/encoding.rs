@@ -0,0 +1,108 @@
+//! Candid encoding of large homogeneous vectors of amounts. The output is a regular Candid
+//! message of type `vec nat`, written directly into a pre-sized buffer.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use candid::Nat;
+use num_bigint::BigUint;
+
+use crate::{c::ECs, uint::Uint};
+
+/// "DIDL", a single `vec nat` type table entry, a single argument of that type
+const VEC_NAT_HEADER: [u8; 9] = [b'D', b'I', b'D', b'L', 1, 0x6d, 0x7d, 1, 0];
+
+fn write_leb128_u128(mut val: u128, buf: &mut Vec<u8>) {
+    loop {
+        let byte = (val & 0x7f) as u8;
+        val >>= 7;
+
+        if val == 0 {
+            buf.push(byte);
+            return;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_leb128(val: &Uint, buf: &mut Vec<u8>) {
+    if let Some(val) = val.to_u128() {
+        return write_leb128_u128(val, buf);
+    }
+
+    let groups = val.big().to_radix_le(128);
+    let last = groups.len() - 1;
+
+    for (i, group) in groups.into_iter().enumerate() {
+        buf.push(if i == last { group } else { group | 0x80 });
+    }
+}
+
+fn read_leb128(bytes: &[u8], pos: &mut usize) -> Result<BigUint, String> {
+    let start = *pos;
+
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| "Unexpected end of input".to_string())?;
+        *pos += 1;
+
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+
+    let groups: Vec<u8> = bytes[start..*pos].iter().map(|it| it & 0x7f).collect();
+
+    // all groups are below the radix
+    Ok(BigUint::from_radix_le(&groups, 128).unwrap())
+}
+
+/// Encodes the amounts as a Candid `vec nat`
+pub fn encode_amounts<const D: usize>(amounts: &[ECs<D>]) -> Vec<u8> {
+    // most amounts fit into 9 bytes (u64)
+    let mut buf = Vec::with_capacity(VEC_NAT_HEADER.len() + 10 + amounts.len() * 9);
+
+    buf.extend_from_slice(&VEC_NAT_HEADER);
+    write_leb128_u128(amounts.len() as u128, &mut buf);
+
+    for it in amounts {
+        write_leb128(&it.val, &mut buf);
+    }
+
+    buf
+}
+
+/// Decodes a Candid `vec nat`. Messages produced by `encode_amounts` are parsed directly,
+/// anything else goes through the generic Candid decoder.
+pub fn decode_amounts<const D: usize>(bytes: &[u8]) -> Result<Vec<ECs<D>>, String> {
+    if !bytes.starts_with(&VEC_NAT_HEADER) {
+        return candid::decode_one::<Vec<Nat>>(bytes)
+            .map(|it| it.into_iter().map(ECs::from).collect())
+            .map_err(|e| e.to_string());
+    }
+
+    let mut pos = VEC_NAT_HEADER.len();
+    let len = usize::try_from(read_leb128(bytes, &mut pos)?)
+        .map_err(|_| "Vector length doesn't fit into usize".to_string())?;
+
+    // each element takes at least one byte
+    if len > bytes.len() - pos {
+        return Err(format!("Vector length {} exceeds the input", len));
+    }
+
+    let mut amounts = Vec::with_capacity(len);
+    for _ in 0..len {
+        amounts.push(ECs::new(read_leb128(bytes, &mut pos)?));
+    }
+
+    if pos != bytes.len() {
+        return Err(format!("{} trailing bytes", bytes.len() - pos));
+    }
+
+    Ok(amounts)
+}
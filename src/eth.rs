@@ -0,0 +1,45 @@
+use alloc::{format, string::String};
+
+use num_bigint::BigUint;
+
+use crate::c::{E18s, E8s};
+
+/// ETH (ckETH) amounts are represented as `E18s`, where the raw value is wei
+impl E18s {
+    pub fn from_wei(wei: u128) -> Self {
+        Self::from(wei)
+    }
+
+    /// Fails, if the value doesn't fit into `u128` wei
+    pub fn to_wei(&self) -> Result<u128, String> {
+        u128::try_from(&self.val).map_err(|_| format!("{} ETH doesn't fit into u128 wei", self))
+    }
+
+    /// Reads a big-endian 256-bit wei value, as found in EVM words
+    pub fn from_wei_u256_be(wei: &[u8; 32]) -> Self {
+        Self::new(BigUint::from_bytes_be(wei))
+    }
+
+    /// Writes the wei value as a big-endian 256-bit word. Fails, if the value doesn't fit into 256 bits.
+    pub fn to_wei_u256_be(&self) -> Result<[u8; 32], String> {
+        let bytes = self.val.to_bytes_be();
+        if bytes.len() > 32 {
+            return Err(format!("{} ETH doesn't fit into u256 wei", self));
+        }
+
+        let mut res = [0u8; 32];
+        res[32 - bytes.len()..].copy_from_slice(&bytes);
+
+        Ok(res)
+    }
+
+    /// Transaction fee for `gas` units at the given gas price (in wei per gas unit)
+    pub fn gas_cost(gas: u64, gas_price_wei: u128) -> Self {
+        Self::new(BigUint::from(gas) * BigUint::from(gas_price_wei))
+    }
+
+    /// Narrows the value to 8 decimals for display purposes, rounding down
+    pub fn to_e8s_lossy(&self) -> E8s {
+        self.clone().to_decimals::<8>()
+    }
+}
@@ -0,0 +1,216 @@
+use alloc::{format, string::String, vec::Vec};
+
+use num_bigint::BigUint;
+
+use crate::{
+    bps::{Bps, BPS_DENOMINATOR},
+    c::{E8s, ECs},
+    d::EDs,
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+};
+
+/// ICP ledger transfer fee
+pub const ICP_FEE_E8S: u64 = 10_000;
+/// ckBTC ledger transfer fee
+pub const CKBTC_FEE_E8S: u64 = 10;
+/// ckETH ledger transfer fee (in wei)
+pub const CKETH_FEE_WEI: u64 = 2_000_000_000_000;
+/// ckUSDC ledger transfer fee (in e6s)
+pub const CKUSDC_FEE_E6S: u64 = 10_000;
+
+pub fn icp_fee() -> E8s {
+    E8s::from(ICP_FEE_E8S)
+}
+
+fn saturating_sub(a: &Uint, b: &Uint) -> Uint {
+    if a > b {
+        a - b
+    } else {
+        Uint::ZERO
+    }
+}
+
+impl<const D: usize> ECs<D> {
+    /// What the recipient gets, if the fee is paid out of this (gross) amount. Zero, if the fee is bigger.
+    pub fn after_fee(&self, fee: &Self) -> Self {
+        Self::new(saturating_sub(&self.val, &fee.val))
+    }
+
+    /// What has to be spent, for the recipient to get exactly this (net) amount
+    pub fn gross_for_net(&self, fee: &Self) -> Self {
+        self + fee
+    }
+
+    /// The biggest amount that can be transferred from this balance, paying the fee on top
+    pub fn max_sendable(balance: &Self, fee: &Self) -> Self {
+        balance.after_fee(fee)
+    }
+}
+
+impl EDs {
+    /// What the recipient gets, if the fee is paid out of this (gross) amount. Zero, if the fee is bigger.
+    pub fn after_fee(&self, fee: &Self) -> Self {
+        if self.decimals != fee.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        Self::new(saturating_sub(&self.val, &fee.val), self.decimals)
+    }
+
+    /// What has to be spent, for the recipient to get exactly this (net) amount
+    pub fn gross_for_net(&self, fee: &Self) -> Self {
+        self + fee
+    }
+
+    /// The biggest amount that can be transferred from this balance, paying the fee on top
+    pub fn max_sendable(balance: &Self, fee: &Self) -> Self {
+        balance.after_fee(fee)
+    }
+}
+
+/// Tiered fees: the whole amount is charged at the bps of the highest tier whose threshold
+/// it reaches (thresholds are inclusive). Amounts below the first threshold are free.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct FeeSchedule<const D: usize> {
+    tiers: Vec<(ECs<D>, Bps)>,
+}
+
+impl<const D: usize> FeeSchedule<D> {
+    /// Tiers are `(threshold, bps)` pairs in any order. Fails on duplicate thresholds.
+    pub fn new(mut tiers: Vec<(ECs<D>, Bps)>) -> Result<Self, String> {
+        tiers.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for pair in tiers.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(format!("Duplicate fee tier threshold {}", pair[0].0));
+            }
+        }
+
+        Ok(Self { tiers })
+    }
+
+    pub fn tiers(&self) -> &[(ECs<D>, Bps)] {
+        &self.tiers
+    }
+
+    /// Bps applicable to the amount
+    pub fn bps_for(&self, amount: &ECs<D>) -> Bps {
+        let idx = self
+            .tiers
+            .partition_point(|(threshold, _)| threshold <= amount);
+        if idx == 0 {
+            return Bps(0);
+        }
+
+        self.tiers[idx - 1].1
+    }
+
+    /// Fee for the amount, rounded up
+    pub fn fee_for(&self, amount: &ECs<D>) -> ECs<D> {
+        let bps = self.bps_for(amount);
+
+        ECs::new(div_rounded(
+            &(&amount.val * BigUint::from(bps.0)),
+            &BigUint::from(BPS_DENOMINATOR),
+            RoundingMode::Up,
+        ))
+    }
+
+    /// What's left of the amount after the fee. Zero, if the fee is bigger.
+    pub fn net_after_fees(&self, amount: &ECs<D>) -> ECs<D> {
+        amount.after_fee(&self.fee_for(amount))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn net_and_gross_round_trip() {
+        let fee = icp_fee();
+        let gross = E8s::from_whole(1);
+
+        let net = gross.after_fee(&fee);
+        assert_eq!(net, E8s::new(99_990_000u64));
+        assert_eq!(net.gross_for_net(&fee), gross);
+
+        // the fee eats everything
+        assert_eq!(E8s::new(9_999u64).after_fee(&fee), E8s::zero());
+        assert_eq!(fee.after_fee(&fee), E8s::zero());
+        assert_eq!(E8s::max_sendable(&E8s::new(5u64), &fee), E8s::zero());
+        assert_eq!(E8s::max_sendable(&gross, &fee), net);
+    }
+
+    #[test]
+    fn eds_fees() {
+        let fee = EDs::new(CKUSDC_FEE_E6S, 6);
+        let balance = EDs::new(1_000_000u64, 6);
+
+        assert_eq!(balance.after_fee(&fee), EDs::new(990_000u64, 6));
+        assert_eq!(EDs::new(990_000u64, 6).gross_for_net(&fee), balance);
+        assert_eq!(EDs::max_sendable(&fee, &fee), EDs::zero(6));
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible decimal points")]
+    fn eds_fee_with_other_decimals_traps() {
+        EDs::new(1u64, 6).after_fee(&EDs::new(1u64, 8));
+    }
+
+    #[test]
+    fn fee_schedule_tier_boundaries() {
+        let schedule = FeeSchedule::new(vec![
+            (E8s::from_whole(1_000), Bps(10)),
+            (E8s::from_whole(100), Bps(30)),
+        ])
+        .unwrap();
+
+        assert_eq!(schedule.tiers()[0].0, E8s::from_whole(100));
+        assert_eq!(schedule.bps_for(&E8s::new(9_999_999_999u64)), Bps(0));
+        // thresholds are inclusive
+        assert_eq!(schedule.bps_for(&E8s::from_whole(100)), Bps(30));
+        assert_eq!(schedule.bps_for(&E8s::new(99_999_999_999u64)), Bps(30));
+        assert_eq!(schedule.bps_for(&E8s::from_whole(1_000)), Bps(10));
+
+        // the whole amount is charged at the reached tier
+        assert_eq!(
+            schedule.fee_for(&E8s::from_whole(100)),
+            E8s::new(30_000_000u64)
+        );
+        assert_eq!(
+            schedule.fee_for(&E8s::from_whole(1_000)),
+            E8s::from_whole(1)
+        );
+        assert_eq!(schedule.fee_for(&E8s::from_whole(99)), E8s::zero());
+        assert_eq!(
+            schedule.net_after_fees(&E8s::from_whole(1_000)),
+            E8s::from_whole(999)
+        );
+    }
+
+    #[test]
+    fn fee_schedule_rounds_up() {
+        let schedule = FeeSchedule::new(vec![(E8s::zero(), Bps(30))]).unwrap();
+
+        // 0.3% of 1 raw unit
+        assert_eq!(schedule.fee_for(&E8s::new(1u64)), E8s::new(1u64));
+        assert_eq!(schedule.net_after_fees(&E8s::new(1u64)), E8s::zero());
+        assert_eq!(schedule.fee_for(&E8s::new(1_001u64)), E8s::new(4u64));
+
+        assert_eq!(
+            FeeSchedule::<8>::default().fee_for(&E8s::one()),
+            E8s::zero()
+        );
+    }
+
+    #[test]
+    fn fee_schedule_rejects_duplicate_thresholds() {
+        let tiers = vec![(E8s::one(), Bps(10)), (E8s::one(), Bps(20))];
+
+        assert!(FeeSchedule::new(tiers).is_err());
+    }
+}
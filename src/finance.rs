@@ -0,0 +1,308 @@
+//! Interest formulas. Rates are fractions (`0.05` is 5%) and every formula is evaluated
+//! with guard digits, then rounded down once.
+
+use alloc::vec::Vec;
+
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::{
+    c::ECs,
+    i::ICs,
+    math::{exp_raw, ln_scaled, pow_overflow, pow_raw, ten_pow, GUARD_DIGITS, MAX_POW_BITS},
+    rounding::{div_rounded, RoundingMode},
+};
+
+/// `principal * (1 + rate_per_period)^periods`. Traps, if the growth factor is above
+/// `2^MAX_POW_BITS` (see `math::MAX_POW_BITS`).
+pub fn compound<const D: usize>(
+    principal: &ECs<D>,
+    rate_per_period: &ECs<D>,
+    periods: u32,
+) -> ECs<D> {
+    let scale = ten_pow(D + GUARD_DIGITS);
+    let one_plus_rate = (ECs::<D>::base() + &rate_per_period.val) * ten_pow(GUARD_DIGITS);
+    let factor = pow_raw(&one_plus_rate, D + GUARD_DIGITS, periods, MAX_POW_BITS)
+        .unwrap_or_else(pow_overflow);
+
+    ECs::new(&principal.val * factor / scale)
+}
+
+/// Stake after auto-compounding rewards of `rate_per_epoch` for `epochs` epochs, paying
+/// `fee_per_compound` out of every compounded reward. Rewards are rounded down, so the stake
+/// is never over-credited. Compounding stops once the reward doesn't cover the fee.
+pub fn compounded_stake<const D: usize>(
+    principal: &ECs<D>,
+    rate_per_epoch: &ECs<D>,
+    epochs: u32,
+    fee_per_compound: &ECs<D>,
+) -> ECs<D> {
+    let base = ECs::<D>::base();
+    let mut stake = principal.val.clone();
+
+    for _ in 0..epochs {
+        let reward = &stake * &rate_per_epoch.val / base;
+        if reward <= fee_per_compound.val {
+            break;
+        }
+
+        stake += reward - &fee_per_compound.val;
+    }
+
+    ECs::new(stake)
+}
+
+/// A single period of an amortization schedule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmortizationRow<const D: usize> {
+    pub payment: ECs<D>,
+    pub interest: ECs<D>,
+    pub principal: ECs<D>,
+    /// Remaining balance after the payment
+    pub balance: ECs<D>,
+}
+
+/// Equal-payment (annuity) amortization schedule: `payment = P * r / (1 - (1 + r)^-n)`.
+/// The payment is rounded up and interest is rounded down, the last payment is adjusted,
+/// so principal portions always sum up to exactly `principal` and the final balance is zero.
+/// Returns an empty schedule for zero periods. Traps, if `(1 + r)^n` is above `2^MAX_POW_BITS`
+/// (see `math::MAX_POW_BITS`).
+pub fn amortization_schedule<const D: usize>(
+    principal: &ECs<D>,
+    rate_per_period: &ECs<D>,
+    periods: u32,
+) -> Vec<AmortizationRow<D>> {
+    if periods == 0 {
+        return Vec::new();
+    }
+
+    let base = ECs::<D>::base();
+
+    let payment = if rate_per_period.val.is_zero() {
+        div_rounded(
+            &principal.val.big(),
+            &BigUint::from(periods),
+            RoundingMode::Up,
+        )
+    } else {
+        let scale = ten_pow(D + GUARD_DIGITS);
+        let one_plus_rate = (base + &rate_per_period.val) * ten_pow(GUARD_DIGITS);
+        let factor = pow_raw(&one_plus_rate, D + GUARD_DIGITS, periods, MAX_POW_BITS)
+            .unwrap_or_else(pow_overflow);
+
+        let n = &principal.val * &rate_per_period.val * &factor;
+        let d = (factor - scale) * base;
+
+        div_rounded(&n, &d, RoundingMode::Up)
+    };
+
+    let mut balance = principal.val.big().into_owned();
+    let mut schedule = Vec::with_capacity(periods as usize);
+
+    for i in 0..periods {
+        let interest = &balance * &rate_per_period.val / base;
+
+        let principal_part = if i + 1 == periods {
+            balance.clone()
+        } else {
+            let part = if payment > interest {
+                &payment - &interest
+            } else {
+                BigUint::ZERO
+            };
+
+            part.min(balance.clone())
+        };
+
+        balance -= &principal_part;
+
+        schedule.push(AmortizationRow {
+            payment: ECs::new(&interest + &principal_part),
+            interest: ECs::new(interest),
+            principal: ECs::new(principal_part),
+            balance: ECs::new(balance.clone()),
+        });
+    }
+
+    schedule
+}
+
+/// APY of an APR compounded `periods_per_year` times a year: `(1 + apr / n)^n - 1`.
+/// Traps, if `periods_per_year` is 0 or if `(1 + apr / n)^n` is above `2^MAX_POW_BITS`.
+pub fn apr_to_apy<const D: usize>(apr: &ECs<D>, periods_per_year: u32) -> ECs<D> {
+    if periods_per_year == 0 {
+        unreachable!("There should be at least one compounding period per year");
+    }
+
+    let scale = ten_pow(D + GUARD_DIGITS);
+    let rate = &apr.val * ten_pow(GUARD_DIGITS) / BigUint::from(periods_per_year);
+    let factor = pow_raw(
+        &(&scale + rate),
+        D + GUARD_DIGITS,
+        periods_per_year,
+        MAX_POW_BITS,
+    )
+    .unwrap_or_else(pow_overflow);
+
+    ECs::new((factor - scale) / ten_pow(GUARD_DIGITS))
+}
+
+/// APR which, compounded `periods_per_year` times a year, yields the APY:
+/// `n * ((1 + apy)^(1/n) - 1)`. Traps, if `periods_per_year` is 0.
+pub fn apy_to_apr<const D: usize>(apy: &ECs<D>, periods_per_year: u32) -> ECs<D> {
+    if periods_per_year == 0 {
+        unreachable!("There should be at least one compounding period per year");
+    }
+
+    let scale = ten_pow(D + GUARD_DIGITS);
+    let one_plus_apy = ECs::<D>::base() + &apy.val;
+
+    // (1 + apy)^(1/n) = exp(ln(1 + apy) / n)
+    let (_, ln) = ln_scaled(&one_plus_apy, D, GUARD_DIGITS);
+    let root = exp_raw(&(ln / BigUint::from(periods_per_year)), D + GUARD_DIGITS);
+
+    // the root can't be below 1, but guard against truncation artifacts
+    let rate = if root > scale {
+        root - scale
+    } else {
+        BigUint::ZERO
+    };
+
+    ECs::new(rate * BigUint::from(periods_per_year) / ten_pow(GUARD_DIGITS))
+}
+
+/// Net present value of cashflows, the first one happening now (undiscounted):
+/// `sum(cf_t / (1 + rate)^t)`. Traps, if `rate` is -1 or lower.
+pub fn npv<const D: usize>(rate: &ICs<D>, cashflows: &[ICs<D>]) -> ICs<D> {
+    let guard = ten_pow(GUARD_DIGITS);
+    let scale = ten_pow(D + GUARD_DIGITS);
+
+    let one_plus_rate = BigInt::from(scale.clone()) + &rate.val * BigInt::from(guard.clone());
+    let one_plus_rate = match one_plus_rate.to_biguint() {
+        Some(v) if v != BigUint::ZERO => v,
+        _ => unreachable!("Discount rate should be above -1"),
+    };
+
+    // discount factor 1 / (1 + rate), multiplied instead of dividing by (1 + rate)^t
+    let factor = &scale * &scale / one_plus_rate;
+    let scale_i = BigInt::from(scale.clone());
+    let guard_i = BigInt::from(guard);
+
+    let mut discount = scale.clone();
+    let mut sum = BigInt::ZERO;
+
+    for cf in cashflows {
+        sum += &cf.val * &guard_i * BigInt::from(discount.clone()) / &scale_i;
+        discount = discount * &factor / &scale;
+    }
+
+    ICs::new(sum / guard_i)
+}
+
+/// Internal rate of return: the rate at which `npv` of the cashflows is zero, found by bisection
+/// between -1 and 1'000'000. Stops once `|npv| <= tolerance` or the bracket can't be narrowed
+/// further. Returns `None`, if the NPV doesn't change its sign in that range
+/// or the solver doesn't converge in `max_iterations`.
+pub fn irr<const D: usize>(
+    cashflows: &[ICs<D>],
+    tolerance: &ECs<D>,
+    max_iterations: u32,
+) -> Option<ICs<D>> {
+    let sign = |rate: &ICs<D>| npv(rate, cashflows).val.sign();
+    let one = ICs::<D>::one();
+    let max_rate = ICs::<D>::new(BigInt::from(1_000_000) * ICs::<D>::base());
+
+    let mut lo = ICs::<D>::new(BigInt::from(1) - ICs::<D>::base());
+    let mut hi = one;
+
+    let lo_sign = sign(&lo);
+    if lo_sign == Sign::NoSign {
+        return Some(lo);
+    }
+
+    loop {
+        let hi_sign = sign(&hi);
+        if hi_sign == Sign::NoSign {
+            return Some(hi);
+        }
+
+        if hi_sign != lo_sign {
+            break;
+        }
+
+        if hi >= max_rate {
+            return None;
+        }
+
+        hi = (&hi + &hi).min(max_rate.clone());
+    }
+
+    let two = BigInt::from(2);
+
+    for _ in 0..max_iterations {
+        let mid = ICs::new((&lo.val + &hi.val) / &two);
+        let value = npv(&mid, cashflows);
+
+        if value.val.magnitude() <= &tolerance.val || &hi.val - &lo.val <= BigInt::from(1) {
+            return Some(mid);
+        }
+
+        if value.val.sign() == lo_sign {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use num_integer::Integer;
+
+    use super::*;
+
+    fn cashflows(whole: &[i64]) -> Vec<ICs<8>> {
+        whole
+            .iter()
+            .map(|it| ICs::new(BigInt::from(*it) * ICs::<8>::base()))
+            .collect()
+    }
+
+    /// IRR in whole percents, rounded to the nearest
+    fn irr_pct(whole: &[i64]) -> Option<i64> {
+        let rate = irr(&cashflows(whole), &ECs::from(1u64), 200)?;
+        let base = ICs::<8>::base();
+        let pct: BigInt = (rate.val * BigInt::from(100) + &base / BigInt::from(2)).div_floor(&base);
+
+        Some(i64::try_from(pct).unwrap())
+    }
+
+    #[test]
+    fn irr_below_100_percent() {
+        assert_eq!(irr_pct(&[-100, 110]), Some(10));
+        assert_eq!(irr_pct(&[-100, 0, 121]), Some(10));
+    }
+
+    #[test]
+    fn irr_above_100_percent() {
+        assert_eq!(irr_pct(&[-100, 300]), Some(200));
+        assert_eq!(irr_pct(&[-100, 1_000]), Some(900));
+        assert_eq!(irr_pct(&[-1, 50_000]), Some(4_999_900));
+    }
+
+    #[test]
+    fn irr_negative() {
+        assert_eq!(irr_pct(&[-100, 50]), Some(-50));
+        assert_eq!(irr_pct(&[-100, 0, 25]), Some(-50));
+    }
+
+    #[test]
+    fn irr_out_of_range() {
+        // same signs, the NPV never crosses zero
+        assert_eq!(irr_pct(&[100, 100]), None);
+        // a rate of 100'000 is found, 2'000'000 is above the upper bound
+        assert_eq!(irr_pct(&[-1, 100_001]), Some(10_000_000));
+        assert_eq!(irr_pct(&[-1, 2_000_001]), None);
+    }
+}
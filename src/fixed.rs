@@ -0,0 +1,142 @@
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    ops::{Add, Div, Mul, Sub},
+};
+
+use num_bigint::BigUint;
+
+use crate::{
+    c::ECs,
+    d::EDs,
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+};
+
+/// Common interface of `ECs<D>` and `EDs`, so generic accounting code can be written once.
+/// Constructors take an existing value, to pick up its decimal points.
+/// There's no `Ord` bound, since the derived `Ord` of `EDs` isn't numeric across decimal points,
+/// compare with `cmp_value` instead.
+pub trait FixedDecimal:
+    Sized
+    + Clone
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    fn decimals(&self) -> u8;
+
+    /// Raw value, scaled by `10^decimals`
+    fn raw(&self) -> &Uint;
+
+    /// A value with the same decimal points from a raw value
+    fn with_raw(&self, val: Uint) -> Self;
+
+    fn base_of(&self) -> &'static BigUint {
+        EDs::base(self.decimals())
+    }
+
+    fn zero_like(&self) -> Self {
+        self.with_raw(Uint::ZERO)
+    }
+
+    fn one_like(&self) -> Self {
+        self.with_raw(self.base_of().into())
+    }
+
+    /// Numeric comparison regardless of decimal points, so `1.5` at 1 decimal point equals
+    /// `1.50` at 2 decimal points
+    fn cmp_value(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.decimals(), other.decimals());
+
+        match a.cmp(&b) {
+            Ordering::Equal => self.raw().cmp(other.raw()),
+            Ordering::Less => (self.raw() * EDs::base(b - a)).cmp(&other.raw().big()),
+            Ordering::Greater => (self.raw().big().as_ref()).cmp(&(other.raw() * EDs::base(a - b))),
+        }
+    }
+
+    /// Rounds the value to `dp` decimal points, keeping its own decimal points
+    fn round_dp(&self, dp: u8, mode: RoundingMode) -> Self {
+        if dp >= self.decimals() {
+            return self.clone();
+        }
+
+        let step = EDs::base(self.decimals() - dp);
+
+        self.with_raw((div_rounded(&self.raw().big(), step, mode) * step).into())
+    }
+}
+
+impl<const D: usize> FixedDecimal for ECs<D> {
+    fn decimals(&self) -> u8 {
+        D as u8
+    }
+
+    fn raw(&self) -> &Uint {
+        &self.val
+    }
+
+    fn with_raw(&self, val: Uint) -> Self {
+        Self::new(val)
+    }
+}
+
+impl FixedDecimal for EDs {
+    fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    fn raw(&self) -> &Uint {
+        &self.val
+    }
+
+    fn with_raw(&self, val: Uint) -> Self {
+        Self::new(val, self.decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn max_of<T: FixedDecimal>(values: &[T]) -> Option<&T> {
+        values.iter().max_by(|a, b| a.cmp_value(b))
+    }
+
+    #[test]
+    fn cmp_value_is_numeric_across_decimals() {
+        let values = [
+            EDs::new(15u64, 1),
+            EDs::new(149u64, 2),
+            EDs::new(1_500_001u64, 6),
+        ];
+
+        assert_eq!(max_of(&values), Some(&values[2]));
+        assert_eq!(
+            FixedDecimal::cmp_value(&EDs::new(15u64, 1), &EDs::new(150u64, 2)),
+            Ordering::Equal
+        );
+        // the derived `Ord` would put the 1-decimal value first
+        assert_eq!(
+            FixedDecimal::cmp_value(&EDs::new(20u64, 1), &EDs::new(150u64, 2)),
+            Ordering::Greater
+        );
+
+        let e8s = [E8s::new(2u64), E8s::new(1u64)];
+        assert_eq!(max_of(&e8s), Some(&e8s[0]));
+    }
+
+    #[test]
+    fn round_dp_keeps_decimals() {
+        let v = EDs::new(1_555u64, 3);
+
+        assert_eq!(v.round_dp(2, RoundingMode::HalfUp), EDs::new(1_560u64, 3));
+        assert_eq!(v.round_dp(0, RoundingMode::Down), EDs::new(1_000u64, 3));
+        assert_eq!(v.round_dp(5, RoundingMode::Down), v);
+    }
+}
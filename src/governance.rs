@@ -0,0 +1,145 @@
+//! Neuron voting power, as computed by the NNS and SNS governance canisters: a dissolve delay
+//! bonus, then an age bonus on top of it, both growing linearly up to their maximums. Every step
+//! is truncated exactly as the governance canisters do it, so results match to the e8.
+
+use num_bigint::BigUint;
+
+use crate::c::E8s;
+
+/// Seconds in a year, as the NNS counts them (365.25 days)
+pub const ONE_YEAR_SECONDS: u64 = (4 * 365 + 1) * 24 * 60 * 60 / 4;
+
+/// Voting power bonus parameters, see `NNS` or build them from the SNS nervous system parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VotingPowerParams {
+    /// Neurons with a shorter dissolve delay can't vote
+    pub min_dissolve_delay_s: u64,
+    /// Dissolve delay giving the maximum bonus, longer ones give the same
+    pub max_dissolve_delay_s: u64,
+    /// Dissolve delay bonus at `max_dissolve_delay_s`, in percents of the stake
+    pub max_dissolve_delay_bonus_percentage: u64,
+    /// Age giving the maximum bonus, older neurons get the same
+    pub max_age_s: u64,
+    /// Age bonus at `max_age_s`, in percents of the stake with the dissolve delay bonus
+    pub max_age_bonus_percentage: u64,
+}
+
+impl VotingPowerParams {
+    /// NNS: 6 months minimum, +100% at 8 years of dissolve delay, +25% at 4 years of age
+    pub const NNS: Self = Self {
+        min_dissolve_delay_s: ONE_YEAR_SECONDS / 2,
+        max_dissolve_delay_s: 8 * ONE_YEAR_SECONDS,
+        max_dissolve_delay_bonus_percentage: 100,
+        max_age_s: 4 * ONE_YEAR_SECONDS,
+        max_age_bonus_percentage: 25,
+    };
+}
+
+/// `stake * percentage * min(x, max) / (100 * max)`, truncated. Zero, if `max` is zero.
+fn linear_bonus(stake: &BigUint, x: u64, max: u64, percentage: u64) -> BigUint {
+    if max == 0 {
+        return BigUint::ZERO;
+    }
+
+    stake * x.min(max) * percentage / (100u128 * max as u128)
+}
+
+/// Voting power of a neuron. The stake should include staked maturity, as the governance
+/// canisters count it. Zero, if the dissolve delay is below the minimum.
+pub fn voting_power(
+    stake_e8s: &E8s,
+    dissolve_delay_s: u64,
+    age_s: u64,
+    params: &VotingPowerParams,
+) -> E8s {
+    if dissolve_delay_s < params.min_dissolve_delay_s {
+        return E8s::zero();
+    }
+
+    let stake = &*stake_e8s.val.big();
+    let d_stake = stake
+        + linear_bonus(
+            stake,
+            dissolve_delay_s,
+            params.max_dissolve_delay_s,
+            params.max_dissolve_delay_bonus_percentage,
+        );
+
+    let ad_stake = &d_stake
+        + linear_bonus(
+            &d_stake,
+            age_s,
+            params.max_age_s,
+            params.max_age_bonus_percentage,
+        );
+
+    E8s::new(ad_stake)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NNS: VotingPowerParams = VotingPowerParams::NNS;
+
+    #[test]
+    fn nns_bonuses() {
+        let icp = E8s::from_whole(1);
+
+        assert_eq!(
+            voting_power(&icp, NNS.min_dissolve_delay_s - 1, 0, &NNS),
+            E8s::zero()
+        );
+        // 1 + 0.5 / 8
+        assert_eq!(
+            voting_power(&icp, NNS.min_dissolve_delay_s, 0, &NNS),
+            E8s::new(106_250_000u64)
+        );
+        // (1 + 1) * (1 + 0.25)
+        assert_eq!(
+            voting_power(&icp, NNS.max_dissolve_delay_s, NNS.max_age_s, &NNS),
+            E8s::new(250_000_000u64)
+        );
+        // both bonuses are capped
+        assert_eq!(
+            voting_power(&icp, u64::MAX, u64::MAX, &NNS),
+            E8s::new(250_000_000u64)
+        );
+    }
+
+    #[test]
+    fn every_step_is_truncated() {
+        let stake = E8s::new(3u64);
+
+        // 3 + floor(1.5), then 4 + floor(1.0)
+        assert_eq!(
+            voting_power(&stake, 4 * ONE_YEAR_SECONDS, 0, &NNS),
+            E8s::new(4u64)
+        );
+        assert_eq!(
+            voting_power(&stake, 4 * ONE_YEAR_SECONDS, NNS.max_age_s, &NNS),
+            E8s::new(5u64)
+        );
+        assert_eq!(
+            voting_power(&E8s::zero(), u64::MAX, u64::MAX, &NNS),
+            E8s::zero()
+        );
+    }
+
+    #[test]
+    fn zero_maximums_give_no_bonus() {
+        let params = VotingPowerParams {
+            min_dissolve_delay_s: 0,
+            max_dissolve_delay_s: 0,
+            max_dissolve_delay_bonus_percentage: 100,
+            max_age_s: 0,
+            max_age_bonus_percentage: 25,
+        };
+
+        assert_eq!(
+            voting_power(&E8s::from_whole(7), 100, 100, &params),
+            E8s::from_whole(7)
+        );
+        assert_eq!(ONE_YEAR_SECONDS, 31_557_600);
+    }
+}
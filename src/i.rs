@@ -0,0 +1,388 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::Display,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+};
+
+#[cfg(feature = "candid")]
+use candid::{CandidType, Int};
+use num_bigint::{BigInt, BigUint, Sign};
+#[cfg(feature = "candid")]
+use serde::Deserialize;
+
+use crate::{c::ECs, fmt_fixed, parse_decimal_str};
+
+/// Signed fixed-point decimals, for values that can go below zero (cashflows, deltas, PnL).
+/// Multiplication and division truncate towards zero.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct ICs<const DECIMALS: usize> {
+    pub val: BigInt,
+}
+
+impl<const D: usize> ICs<D> {
+    pub fn new(val: BigInt) -> Self {
+        Self { val }
+    }
+
+    pub fn base() -> BigInt {
+        BigInt::from(ECs::<D>::base().clone())
+    }
+
+    pub fn zero() -> Self {
+        Self::new(BigInt::ZERO)
+    }
+
+    pub fn one() -> Self {
+        Self::new(Self::base())
+    }
+
+    /// Builds a value from a magnitude and a sign (`true` for negative)
+    pub fn from_parts(negative: bool, magnitude: ECs<D>) -> Self {
+        let sign = if negative { Sign::Minus } else { Sign::Plus };
+
+        Self::new(BigInt::from_biguint(sign, magnitude.val.into_big()))
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.val.sign() == Sign::Minus
+    }
+
+    pub fn abs(&self) -> ECs<D> {
+        ECs::new(self.val.magnitude().clone())
+    }
+
+    /// The value as `ECs`, if it's not negative
+    pub fn to_unsigned(&self) -> Option<ECs<D>> {
+        if self.is_negative() {
+            None
+        } else {
+            Some(self.abs())
+        }
+    }
+
+    /// Raw units as `i64`. Fails, if the value doesn't fit into it.
+    pub fn to_i64(&self) -> Result<i64, String> {
+        i64::try_from(&self.val).map_err(|_| format!("{} doesn't fit into i64", self))
+    }
+
+    /// Raw units as `i128`, e.g. for an EVM `int256` field. Fails, if the value doesn't fit
+    /// into it.
+    pub fn to_i128(&self) -> Result<i128, String> {
+        i128::try_from(&self.val).map_err(|_| format!("{} doesn't fit into i128", self))
+    }
+}
+
+impl<const D: usize> ECs<D> {
+    /// Relative change from `self` to `new` as a signed fraction (`-0.05` is -5%):
+    /// `(new - self) / self`, truncated towards zero. Traps, if `self` is zero.
+    pub fn pct_change(&self, new: &Self) -> ICs<D> {
+        let old = BigInt::from(self.val.clone());
+        let delta = BigInt::from(new.val.clone()) - &old;
+
+        ICs::new(delta * ICs::<D>::base() / old)
+    }
+}
+
+impl<const D: usize> Display for ICs<D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_negative() {
+            f.write_str("-")?;
+        }
+
+        fmt_fixed(&self.val.magnitude().into(), D, f)
+    }
+}
+
+impl<const D: usize> FromStr for ICs<D> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+
+        let (val, _) = parse_decimal_str(s, Some(D))?;
+
+        Ok(Self::from_parts(negative, ECs::new(val)))
+    }
+}
+
+impl<const D: usize> Neg for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn neg(self) -> Self::Output {
+        ICs::new(-&self.val)
+    }
+}
+
+impl<const D: usize> Neg for ICs<D> {
+    type Output = ICs<D>;
+
+    fn neg(self) -> Self::Output {
+        ICs::new(-self.val)
+    }
+}
+
+impl<const D: usize> Add for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        ICs::<D>::new(&self.val + &rhs.val)
+    }
+}
+
+impl<const D: usize> Add for ICs<D> {
+    type Output = ICs<D>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        (&self).add(&rhs)
+    }
+}
+
+impl<const D: usize> Add<&ICs<D>> for ICs<D> {
+    type Output = ICs<D>;
+
+    fn add(self, rhs: &ICs<D>) -> Self::Output {
+        (&self).add(rhs)
+    }
+}
+
+impl<const D: usize> Add<ICs<D>> for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn add(self, rhs: ICs<D>) -> Self::Output {
+        self.add(&rhs)
+    }
+}
+
+impl<const D: usize> AddAssign<&ICs<D>> for ICs<D> {
+    fn add_assign(&mut self, rhs: &ICs<D>) {
+        self.val += &rhs.val
+    }
+}
+
+impl<const D: usize> AddAssign for ICs<D> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.add_assign(&rhs)
+    }
+}
+
+impl<const D: usize> Sub for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        ICs::<D>::new(&self.val - &rhs.val)
+    }
+}
+
+impl<const D: usize> Sub for ICs<D> {
+    type Output = ICs<D>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        (&self).sub(&rhs)
+    }
+}
+
+impl<const D: usize> Sub<&ICs<D>> for ICs<D> {
+    type Output = ICs<D>;
+
+    fn sub(self, rhs: &ICs<D>) -> Self::Output {
+        (&self).sub(rhs)
+    }
+}
+
+impl<const D: usize> Sub<ICs<D>> for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn sub(self, rhs: ICs<D>) -> Self::Output {
+        self.sub(&rhs)
+    }
+}
+
+impl<const D: usize> SubAssign<&ICs<D>> for ICs<D> {
+    fn sub_assign(&mut self, rhs: &ICs<D>) {
+        self.val -= &rhs.val
+    }
+}
+
+impl<const D: usize> SubAssign for ICs<D> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.sub_assign(&rhs)
+    }
+}
+
+impl<const D: usize> Mul for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        ICs::<D>::new(&self.val * &rhs.val / ICs::<D>::base())
+    }
+}
+
+impl<const D: usize> Mul for ICs<D> {
+    type Output = ICs<D>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        (&self).mul(&rhs)
+    }
+}
+
+impl<const D: usize> Mul<&ICs<D>> for ICs<D> {
+    type Output = ICs<D>;
+
+    fn mul(self, rhs: &ICs<D>) -> Self::Output {
+        (&self).mul(rhs)
+    }
+}
+
+impl<const D: usize> Mul<ICs<D>> for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn mul(self, rhs: ICs<D>) -> Self::Output {
+        self.mul(&rhs)
+    }
+}
+
+impl<const D: usize> MulAssign<&ICs<D>> for ICs<D> {
+    fn mul_assign(&mut self, rhs: &ICs<D>) {
+        self.val = &self.val * &rhs.val / ICs::<D>::base()
+    }
+}
+
+impl<const D: usize> MulAssign for ICs<D> {
+    fn mul_assign(&mut self, rhs: Self) {
+        self.mul_assign(&rhs)
+    }
+}
+
+impl<const D: usize> Div for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        ICs::<D>::new(&self.val * ICs::<D>::base() / &rhs.val)
+    }
+}
+
+impl<const D: usize> Div for ICs<D> {
+    type Output = ICs<D>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        (&self).div(&rhs)
+    }
+}
+
+impl<const D: usize> Div<&ICs<D>> for ICs<D> {
+    type Output = ICs<D>;
+
+    fn div(self, rhs: &ICs<D>) -> Self::Output {
+        (&self).div(rhs)
+    }
+}
+
+impl<const D: usize> Div<ICs<D>> for &ICs<D> {
+    type Output = ICs<D>;
+
+    fn div(self, rhs: ICs<D>) -> Self::Output {
+        self.div(&rhs)
+    }
+}
+
+impl<const D: usize> DivAssign<&ICs<D>> for ICs<D> {
+    fn div_assign(&mut self, rhs: &ICs<D>) {
+        self.val = &self.val * ICs::<D>::base() / &rhs.val
+    }
+}
+
+impl<const D: usize> DivAssign for ICs<D> {
+    fn div_assign(&mut self, rhs: Self) {
+        self.div_assign(&rhs)
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<const D: usize> CandidType for ICs<D> {
+    fn _ty() -> candid::types::Type {
+        Int::_ty()
+    }
+
+    fn idl_serialize<S>(&self, serializer: S) -> Result<(), S::Error>
+    where
+        S: candid::types::Serializer,
+    {
+        Int::idl_serialize(&Int(self.val.clone()), serializer)
+    }
+}
+
+#[cfg(feature = "candid")]
+impl<'de, const C: usize> Deserialize<'de> for ICs<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Ok(ICs::new(Int::deserialize(deserializer)?.0))
+    }
+}
+
+/// Without Candid, serialized as a string of signed raw units, e.g. `"-150000000"`
+#[cfg(not(feature = "candid"))]
+impl<const D: usize> serde::Serialize for ICs<D> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serde::Serialize::serialize(&crate::serde_raw::RawSigned(&self.val), serializer)
+    }
+}
+
+/// Without Candid, accepts a string of signed raw units or an integer
+#[cfg(not(feature = "candid"))]
+impl<'de, const C: usize> serde::Deserialize<'de> for ICs<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::serde_raw::deserialize_signed(deserializer).map(ICs::new)
+    }
+}
+
+/// Raw units, e.g. `ICs::<8>::from(-150_000_000)` is `-1.5`. Every `i64` and `i128` fits, so
+/// `TryFrom` for them comes with these impls and never fails.
+impl<const D: usize> From<i64> for ICs<D> {
+    fn from(value: i64) -> Self {
+        Self::new(BigInt::from(value))
+    }
+}
+
+/// Raw units, see `From<i64>`
+impl<const D: usize> From<i128> for ICs<D> {
+    fn from(value: i128) -> Self {
+        Self::new(BigInt::from(value))
+    }
+}
+
+impl<const D: usize> From<ECs<D>> for ICs<D> {
+    fn from(value: ECs<D>) -> Self {
+        Self::new(BigInt::from(value.val))
+    }
+}
+
+impl<const D: usize> From<&ECs<D>> for ICs<D> {
+    fn from(value: &ECs<D>) -> Self {
+        Self::new(BigInt::from(value.val.clone()))
+    }
+}
+
+impl<const D: usize> TryFrom<ICs<D>> for ECs<D> {
+    type Error = String;
+
+    fn try_from(value: ICs<D>) -> Result<Self, Self::Error> {
+        BigUint::try_from(value.val)
+            .map(ECs::new)
+            .map_err(|_| "Negative values can't be converted into ECs".to_string())
+    }
+}
@@ -0,0 +1,158 @@
+use alloc::{format, string::String};
+
+use ic_ledger_types::{Block, Operation, Tokens};
+
+use crate::c::E8s;
+
+impl From<Tokens> for E8s {
+    fn from(value: Tokens) -> Self {
+        Self::from(value.e8s())
+    }
+}
+
+impl From<&Tokens> for E8s {
+    fn from(value: &Tokens) -> Self {
+        Self::from(value.e8s())
+    }
+}
+
+/// Fails, if the value doesn't fit into `u64` e8s
+impl TryFrom<&E8s> for Tokens {
+    type Error = String;
+
+    fn try_from(value: &E8s) -> Result<Self, Self::Error> {
+        let e8s = u64::try_from(&value.val)
+            .map_err(|_| format!("{} ICP doesn't fit into Tokens", value))?;
+
+        Ok(Tokens::from_e8s(e8s))
+    }
+}
+
+impl TryFrom<E8s> for Tokens {
+    type Error = String;
+
+    fn try_from(value: E8s) -> Result<Self, Self::Error> {
+        Tokens::try_from(&value)
+    }
+}
+
+/// Amounts of a single ICP ledger operation
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct OperationAmounts {
+    /// Moved (transferred, minted or burned) amount, zero for approvals
+    pub amount: E8s,
+    /// Paid fee, zero for mints and burns
+    pub fee: E8s,
+}
+
+impl From<&Operation> for OperationAmounts {
+    fn from(value: &Operation) -> Self {
+        match value {
+            Operation::Mint { amount, .. } | Operation::Burn { amount, .. } => Self {
+                amount: E8s::from(amount),
+                fee: E8s::zero(),
+            },
+            Operation::Transfer { amount, fee, .. }
+            | Operation::TransferFrom { amount, fee, .. } => Self {
+                amount: E8s::from(amount),
+                fee: E8s::from(fee),
+            },
+            Operation::Approve { fee, .. } => Self {
+                amount: E8s::zero(),
+                fee: E8s::from(fee),
+            },
+        }
+    }
+}
+
+impl OperationAmounts {
+    /// `None`, if the block has no operation
+    pub fn from_block(block: &Block) -> Option<Self> {
+        block.transaction.operation.as_ref().map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::Principal;
+    use ic_ledger_types::{AccountIdentifier, Memo, Timestamp, Transaction, DEFAULT_SUBACCOUNT};
+
+    use super::*;
+
+    fn account() -> AccountIdentifier {
+        AccountIdentifier::new(&Principal::anonymous(), &DEFAULT_SUBACCOUNT)
+    }
+
+    fn block(operation: Option<Operation>) -> Block {
+        Block {
+            parent_hash: None,
+            transaction: Transaction {
+                memo: Memo(0),
+                operation,
+                created_at_time: Timestamp { timestamp_nanos: 0 },
+                icrc1_memo: None,
+            },
+            timestamp: Timestamp { timestamp_nanos: 0 },
+        }
+    }
+
+    #[test]
+    fn amounts_of_every_operation() {
+        let amount = Tokens::from_e8s(150_000_000);
+        let fee = Tokens::from_e8s(10_000);
+
+        let mint = Operation::Mint {
+            to: account(),
+            amount,
+        };
+        let burn = Operation::Burn {
+            from: account(),
+            amount,
+        };
+        let transfer = Operation::Transfer {
+            from: account(),
+            to: account(),
+            amount,
+            fee,
+        };
+        let transfer_from = Operation::TransferFrom {
+            from: account(),
+            to: account(),
+            spender: account(),
+            amount,
+            fee,
+        };
+        let approve = Operation::Approve {
+            from: account(),
+            spender: account(),
+            expires_at: None,
+            fee,
+        };
+
+        let moved = E8s::new(150_000_000u64);
+        let paid = E8s::new(10_000u64);
+
+        for (op, expected_amount, expected_fee) in [
+            (mint, moved.clone(), E8s::zero()),
+            (burn, moved.clone(), E8s::zero()),
+            (transfer, moved.clone(), paid.clone()),
+            (transfer_from, moved, paid.clone()),
+            (approve, E8s::zero(), paid),
+        ] {
+            let amounts = OperationAmounts::from_block(&block(Some(op))).unwrap();
+
+            assert_eq!(amounts.amount, expected_amount);
+            assert_eq!(amounts.fee, expected_fee);
+        }
+
+        assert_eq!(OperationAmounts::from_block(&block(None)), None);
+    }
+
+    #[test]
+    fn tokens_round_trip() {
+        let e8s = E8s::from(Tokens::from_e8s(u64::MAX));
+
+        assert_eq!(Tokens::try_from(&e8s), Ok(Tokens::from_e8s(u64::MAX)));
+        assert!(Tokens::try_from(e8s + E8s::new(1u64)).is_err());
+    }
+}
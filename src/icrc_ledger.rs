@@ -0,0 +1,84 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use candid::Nat;
+use icrc_ledger_types::{
+    icrc::generic_value::Value,
+    icrc1::transfer::{NumTokens, TransferArg},
+};
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs};
+
+fn check_decimals(decimals: u8, ledger_decimals: u8) -> Result<(), String> {
+    if decimals != ledger_decimals {
+        return Err(format!(
+            "Incompatible decimal points: {} (amount) != {} (ledger)",
+            decimals, ledger_decimals
+        ));
+    }
+
+    Ok(())
+}
+
+impl EDs {
+    /// Interprets raw `NumTokens` of a ledger with `icrc1_decimals` equal to `ledger_decimals`
+    pub fn from_num_tokens(value: NumTokens, ledger_decimals: u8) -> Self {
+        Self::new(value.0, ledger_decimals)
+    }
+
+    /// Fails, if the value has a different amount of decimal points than the ledger
+    pub fn to_num_tokens(&self, ledger_decimals: u8) -> Result<NumTokens, String> {
+        check_decimals(self.decimals, ledger_decimals)?;
+
+        Ok(Nat(self.val.big().into_owned()))
+    }
+}
+
+impl<const D: usize> ECs<D> {
+    /// Fails, if `D` is not equal to the ledger's `icrc1_decimals`
+    pub fn to_num_tokens(&self, ledger_decimals: u8) -> Result<NumTokens, String> {
+        check_decimals(D as u8, ledger_decimals)?;
+
+        Ok(Nat(self.val.big().into_owned()))
+    }
+}
+
+/// Fills `amount` and `fee` fields of the transfer arg, validating the ledger's `icrc1_decimals`
+pub fn fill_transfer_arg(
+    arg: &mut TransferArg,
+    amount: &EDs,
+    fee: Option<&EDs>,
+    ledger_decimals: u8,
+) -> Result<(), String> {
+    arg.amount = amount.to_num_tokens(ledger_decimals)?;
+    arg.fee = fee
+        .map(|it| it.to_num_tokens(ledger_decimals))
+        .transpose()?;
+
+    Ok(())
+}
+
+impl EDs {
+    /// Reads an amount from an ICRC-3 value: raw units (`Nat`, `Nat64`) or a decimal string (`Text`)
+    pub fn from_icrc3_value(value: &Value, decimals: u8) -> Result<Self, String> {
+        match value {
+            Value::Nat(n) => Ok(Self::new(n.0.clone(), decimals)),
+            Value::Nat64(n) => Ok(Self::new(BigUint::from(*n), decimals)),
+            Value::Text(s) => Self::from_str_d(s, decimals),
+            _ => Err("ICRC-3 value is not an amount".to_string()),
+        }
+    }
+
+    /// Raw units as an ICRC-3 `Nat` value
+    pub fn to_icrc3_nat(&self) -> Value {
+        Value::Nat(Nat(self.val.big().into_owned()))
+    }
+
+    /// A decimal string as an ICRC-3 `Text` value
+    pub fn to_icrc3_text(&self) -> Value {
+        Value::Text(self.to_string())
+    }
+}
@@ -0,0 +1,69 @@
+use alloc::{format, string::String};
+
+use crate::{c::ECs, rounding::RoundingMode};
+
+/// A monotonically growing index of an interest-bearing (rebasing) token: balances are
+/// stored as shares, the underlying amount is `shares * index`. Both conversions round down,
+/// so converting an amount into shares and back never yields more than the original amount,
+/// i.e. rounding can't inflate balances.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Index<const D: usize> {
+    value: ECs<D>,
+}
+
+impl<const D: usize> Default for Index<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const D: usize> Index<D> {
+    /// Starts at 1.0
+    pub fn new() -> Self {
+        Self { value: ECs::one() }
+    }
+
+    /// Traps, if the value is zero
+    pub fn from_value(value: ECs<D>) -> Self {
+        if value == ECs::zero() {
+            unreachable!("Index can't be zero");
+        }
+
+        Self { value }
+    }
+
+    pub fn value(&self) -> &ECs<D> {
+        &self.value
+    }
+
+    /// Moves the index to a new value. Fails, if it is lower than the current one.
+    pub fn update(&mut self, value: ECs<D>) -> Result<(), String> {
+        if value < self.value {
+            return Err(format!(
+                "Index can't decrease from {} to {}",
+                self.value, value
+            ));
+        }
+
+        self.value = value;
+
+        Ok(())
+    }
+
+    /// Grows the index by `rate` (`index * (1 + rate)`), rounding down
+    pub fn accrue(&mut self, rate: &ECs<D>) {
+        let factor = ECs::<D>::one() + rate;
+
+        self.value = self.value.muldiv(&factor, &ECs::one(), RoundingMode::Down);
+    }
+
+    /// Shares worth the amount at the current index: `amount / index`, rounded down
+    pub fn to_shares(&self, amount: &ECs<D>) -> ECs<D> {
+        amount.muldiv(&ECs::one(), &self.value, RoundingMode::Down)
+    }
+
+    /// Underlying amount of the shares at the current index: `shares * index`, rounded down
+    pub fn to_underlying(&self, shares: &ECs<D>) -> ECs<D> {
+        shares.muldiv(&self.value, &ECs::one(), RoundingMode::Down)
+    }
+}
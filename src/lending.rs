@@ -0,0 +1,159 @@
+//! Money-market math. Values are in the same quote currency (e.g. USD in e8s).
+
+use num_bigint::BigUint;
+
+use crate::{
+    bps::{Bps, BPS_DENOMINATOR},
+    c::ECs,
+};
+
+/// `collateral_value * liquidation_threshold / debt_value`, rounded down, so positions never
+/// look healthier than they are. A position with health factor below 1 can be liquidated.
+/// `None` for zero debt (infinite health factor).
+pub fn health_factor<const D: usize>(
+    collateral_value: &ECs<D>,
+    debt_value: &ECs<D>,
+    liquidation_threshold: Bps,
+) -> Option<ECs<D>> {
+    if debt_value.val.is_zero() {
+        return None;
+    }
+
+    let n = &collateral_value.val * BigUint::from(liquidation_threshold.0) * ECs::<D>::base();
+    let d = &debt_value.val * BigUint::from(BPS_DENOMINATOR);
+
+    Some(ECs::new(n / d))
+}
+
+/// Whether a position with such health factor can be liquidated
+pub fn is_liquidatable<const D: usize>(health_factor: Option<&ECs<D>>) -> bool {
+    health_factor.is_some_and(|hf| hf < &ECs::one())
+}
+
+/// The biggest part of the debt a liquidator can repay at once: `debt * close_factor`,
+/// rounded down, so the close factor is never exceeded
+pub fn max_liquidatable<const D: usize>(debt: &ECs<D>, close_factor: Bps) -> ECs<D> {
+    close_factor.apply(debt)
+}
+
+/// Utilization of a pool: `borrowed / supplied`, rounded down. Zero for an empty pool.
+pub fn utilization<const D: usize>(borrowed: &ECs<D>, supplied: &ECs<D>) -> ECs<D> {
+    if supplied.val.is_zero() {
+        return ECs::zero();
+    }
+
+    ECs::new(&borrowed.val * ECs::<D>::base() / &supplied.val)
+}
+
+/// Utilization capped at 1
+fn clamp_utilization<const D: usize>(utilization: &ECs<D>) -> ECs<D> {
+    if utilization.val > *ECs::<D>::base() {
+        ECs::one()
+    } else {
+        utilization.clone()
+    }
+}
+
+/// Kinked utilization-based interest rate model: the borrow rate grows by `slope1` until the
+/// optimal utilization and by `slope2` after it. Rates are per period (e.g. per year)
+/// and rounded down.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateCurve<const D: usize> {
+    pub base_rate: ECs<D>,
+    pub slope1: ECs<D>,
+    pub slope2: ECs<D>,
+    pub optimal_utilization: ECs<D>,
+}
+
+impl<const D: usize> RateCurve<D> {
+    /// Traps, if the optimal utilization is not in (0, 1)
+    pub fn new(
+        base_rate: ECs<D>,
+        slope1: ECs<D>,
+        slope2: ECs<D>,
+        optimal_utilization: ECs<D>,
+    ) -> Self {
+        if optimal_utilization.val.is_zero() || optimal_utilization >= ECs::one() {
+            unreachable!("Optimal utilization should be in (0, 1)");
+        }
+
+        Self {
+            base_rate,
+            slope1,
+            slope2,
+            optimal_utilization,
+        }
+    }
+
+    /// Utilization above 1 is treated as 1
+    pub fn borrow_rate(&self, utilization: &ECs<D>) -> ECs<D> {
+        let (n, d) = self.borrow_rate_exact(utilization);
+
+        ECs::new(n / d)
+    }
+
+    /// What suppliers earn: `borrow_rate * utilization * (1 - reserve_factor)`, rounded down once.
+    /// Utilization above 1 (borrows exceeding supply) counts as 1, as it does for the borrow rate.
+    pub fn supply_rate(&self, utilization: &ECs<D>, reserve_factor: Bps) -> ECs<D> {
+        let u = clamp_utilization(utilization);
+        let (n, d) = self.borrow_rate_exact(&u);
+
+        let n = n * &u.val * BigUint::from(BPS_DENOMINATOR.saturating_sub(reserve_factor.0));
+        let d = d * ECs::<D>::base() * BigUint::from(BPS_DENOMINATOR);
+
+        ECs::new(n / d)
+    }
+
+    /// Borrow rate as an exact fraction of raw values
+    fn borrow_rate_exact(&self, utilization: &ECs<D>) -> (BigUint, BigUint) {
+        let u = clamp_utilization(utilization);
+        let opt = &self.optimal_utilization.val;
+
+        if &u.val <= opt {
+            // base + slope1 * u / opt
+            let n = &self.base_rate.val * opt + &self.slope1.val * &u.val;
+
+            return (n.into_big(), opt.big().into_owned());
+        }
+
+        // base + slope1 + slope2 * (u - opt) / (1 - opt)
+        let d = ECs::<D>::base() - opt;
+        let n = (&self.base_rate.val + &self.slope1.val) * &d + &self.slope2.val * (&u.val - opt);
+
+        (n, d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn pct(p: u64) -> E8s {
+        E8s::from(p * 1_000_000)
+    }
+
+    fn curve() -> RateCurve<8> {
+        // 2% + 4% up to 80% utilization, +75% more up to 100%
+        RateCurve::new(pct(2), pct(4), pct(75), pct(80))
+    }
+
+    #[test]
+    fn supply_rate_is_capped_at_full_utilization() {
+        let full = curve().supply_rate(&pct(100), Bps(1_000));
+
+        assert_eq!(curve().supply_rate(&pct(150), Bps(1_000)), full);
+        assert!(full <= curve().borrow_rate(&pct(150)));
+        // 81% * 100% * 90%
+        assert_eq!(full, E8s::from(72_900_000u64));
+    }
+
+    #[test]
+    fn supply_rate_is_rounded_once() {
+        // borrow rate 2% + 4% * 66.666667% / 80% = 5.33333335%, supply rate is that
+        // * 66.666667% * 90% = 3.2000000...%, rounding the borrow rate first gives 3.199999%
+        let u = E8s::from(66_666_667u64);
+
+        assert_eq!(curve().supply_rate(&u, Bps(1_000)), E8s::from(3_200_000u64));
+    }
+}
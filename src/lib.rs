@@ -1,46 +1,410 @@
-use lazy_static::lazy_static;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{format, string::String};
+#[cfg(all(feature = "std", feature = "candid"))]
+use core::cell::RefCell;
+use core::ops::{Deref, Sub};
+
+#[cfg(feature = "candid")]
+use candid::Nat;
 use num_bigint::BigUint;
+use spin::Once;
 
+use crate::{math::ten_pow, uint::Uint};
+
+pub mod accumulator;
+pub mod allocation;
+pub mod amm;
+pub mod amount;
+pub mod auction;
+pub mod batch;
+pub mod bonding;
+pub mod bps;
 pub mod c;
+pub mod consts;
+pub mod context;
+pub mod cycles;
 pub mod d;
+pub mod emission;
+#[cfg(feature = "candid")]
+pub mod encoding;
+pub mod eth;
+pub mod fees;
+pub mod finance;
+pub mod fixed;
+pub mod governance;
+pub mod i;
+#[cfg(feature = "ic-ledger-types")]
+pub mod icp_ledger;
+#[cfg(feature = "icrc-ledger-types")]
+pub mod icrc_ledger;
+pub mod index;
+pub mod lending;
+pub mod lottery;
+pub mod matching;
+pub mod math;
+pub mod merkle;
+pub mod metrics;
+pub mod mixed;
+pub mod newtype;
+pub mod oracle;
+pub mod quadratic;
+#[cfg(feature = "rand")]
+pub mod random;
+pub mod rate;
+pub mod rounding;
+#[cfg(feature = "candid")]
+pub mod serde_nat;
+#[cfg(not(feature = "candid"))]
+mod serde_raw;
+pub mod serde_string;
+pub mod serde_u64;
+pub mod stablecoins;
+pub mod stats;
+#[cfg(feature = "stable-structures")]
+pub mod storable;
+pub mod tagged;
+#[cfg(any(feature = "proptest", feature = "arbitrary"))]
+pub mod testing;
+#[cfg(feature = "candid")]
+pub mod tokens;
+pub mod transfer;
+#[cfg(feature = "trig")]
+pub mod trig;
+pub mod uint;
+pub mod vault;
+pub mod vesting;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
+pub mod xrc;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{borrow::Cow, string::String};
+    #[cfg(feature = "candid")]
+    pub use candid;
+    #[cfg(feature = "stable-structures")]
+    pub use ic_stable_structures;
+    pub use serde;
+}
+
+/// The biggest supported number of decimal points: 10^76 still fits into 256 bits
+pub const MAX_DECIMALS: usize = 76;
+
+/// 10^0..10^38, all powers of ten fitting into `u128`
+pub const ES_BASES_U128: [u128; 39] = {
+    let mut bases = [1u128; 39];
+    let mut i = 1;
+
+    while i < 39 {
+        bases[i] = bases[i - 1] * 10;
+        i += 1;
+    }
+
+    bases
+};
+
+/// 10^0..10^MAX_DECIMALS as `BigUint`s, built on first use, see `ES_BASES`
+pub struct Bases(Once<[BigUint; MAX_DECIMALS + 1]>);
+
+impl Deref for Bases {
+    type Target = [BigUint; MAX_DECIMALS + 1];
+
+    fn deref(&self) -> &Self::Target {
+        self.0.call_once(|| {
+            core::array::from_fn(|i| match ES_BASES_U128.get(i) {
+                Some(base) => BigUint::from(*base),
+                None => BigUint::from(ES_BASES_U128[38]) * ES_BASES_U128[i - 38],
+            })
+        })
+    }
+}
+
+/// 10^0..10^MAX_DECIMALS as `BigUint`s. Works without `std`: the table is built exactly once,
+/// concurrent first uses wait for it (which never happens on single-threaded wasm).
+pub static ES_BASES: Bases = Bases(Once::new());
+
+#[cfg(all(feature = "std", feature = "candid"))]
+thread_local! {
+    static NAT_SCRATCH: RefCell<Nat> = const { RefCell::new(Nat(BigUint::ZERO)) };
+}
 
-lazy_static! {
-    pub static ref ES_BASES: [BigUint; 32] = {
-        let ten = BigUint::from(10u64);
-
-        [
-            ten.pow(0),
-            ten.pow(1),
-            ten.pow(2),
-            ten.pow(3),
-            ten.pow(4),
-            ten.pow(5),
-            ten.pow(6),
-            ten.pow(7),
-            ten.pow(8),
-            ten.pow(9),
-            ten.pow(10),
-            ten.pow(11),
-            ten.pow(12),
-            ten.pow(13),
-            ten.pow(14),
-            ten.pow(15),
-            ten.pow(16),
-            ten.pow(17),
-            ten.pow(18),
-            ten.pow(19),
-            ten.pow(20),
-            ten.pow(21),
-            ten.pow(22),
-            ten.pow(23),
-            ten.pow(24),
-            ten.pow(25),
-            ten.pow(26),
-            ten.pow(27),
-            ten.pow(28),
-            ten.pow(29),
-            ten.pow(30),
-            ten.pow(31),
-        ]
+/// Passes the value as a `Nat` without allocating one per call: it is copied into a
+/// thread-local scratch `Nat`, whose buffer is reused. Falls back to a fresh clone if the
+/// scratch is already in use.
+#[cfg(all(feature = "std", feature = "candid"))]
+pub(crate) fn with_nat<R>(val: &Uint, f: impl FnOnce(&Nat) -> R) -> R {
+    NAT_SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut nat) => {
+            val.clone_into_big(&mut nat.0);
+            f(&nat)
+        }
+        Err(_) => f(&Nat(val.into())),
+    })
+}
+
+#[cfg(all(not(feature = "std"), feature = "candid"))]
+pub(crate) fn with_nat<R>(val: &Uint, f: impl FnOnce(&Nat) -> R) -> R {
+    f(&Nat(val.into()))
+}
+
+/// Parses a "123.456" string into a raw value, returning it together with its decimal points.
+/// If `decimals` is provided, the value is scaled to exactly that many decimal points.
+pub(crate) fn parse_decimal_str(
+    s: &str,
+    decimals: Option<usize>,
+) -> Result<(BigUint, usize), String> {
+    let (whole, mut fract) = match s.split_once('.') {
+        Some((whole, fract)) if !fract.is_empty() => (whole, fract),
+        Some(_) => return Err(format!("Invalid decimal string: '{}'", s)),
+        None => (s, ""),
+    };
+
+    if whole.is_empty()
+        || !whole
+            .bytes()
+            .chain(fract.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(format!("Invalid decimal string: '{}'", s));
+    }
+
+    let decimals = match decimals {
+        Some(d) => {
+            if fract.len() > d {
+                if fract[d..].bytes().any(|b| b != b'0') {
+                    return Err(format!(
+                        "Decimal string '{}' has more than {} significant decimal points",
+                        s, d
+                    ));
+                }
+
+                fract = &fract[..d];
+            }
+
+            d
+        }
+        None => fract.len(),
     };
+
+    if decimals > MAX_DECIMALS {
+        return Err(format!(
+            "Decimal points after {} are not supported",
+            MAX_DECIMALS
+        ));
+    }
+
+    let mut digits = String::with_capacity(whole.len() + decimals);
+    digits.push_str(whole);
+    digits.push_str(fract);
+    for _ in fract.len()..decimals {
+        digits.push('0');
+    }
+
+    // can't fail - only ascii digits are left at this point
+    let val = BigUint::parse_bytes(digits.as_bytes(), 10).unwrap();
+
+    Ok((val, decimals))
+}
+
+/// Rescales a raw value from one amount of decimal points to another, rounding down
+pub(crate) fn rescale(val: BigUint, from_decimals: usize, to_decimals: usize) -> BigUint {
+    if to_decimals >= from_decimals {
+        val * ten_pow(to_decimals - from_decimals)
+    } else {
+        val / ten_pow(from_decimals - to_decimals)
+    }
+}
+
+/// 5^13, the biggest power of 5 fitting into a single 32-bit digit
+const POW5_13: u32 = 1_220_703_125;
+
+/// `val / 10^decimals` as a shift by `decimals` bits followed by single-digit divisions by
+/// powers of 5 (10^d = 2^d * 5^d), instead of a generic long division by a multi-digit base
+pub(crate) fn div_base_assign(val: &mut BigUint, decimals: usize) {
+    *val >>= decimals;
+
+    let mut rest = decimals;
+    while rest >= 13 {
+        *val /= POW5_13;
+        rest -= 13;
+    }
+
+    if rest > 0 {
+        *val /= 5u32.pow(rest as u32);
+    }
+}
+
+/// `a * b / 10^decimals`, the fixed-point product. Computed on `u128` without allocating while
+/// the operands and the product fit, which is the case for the vast majority of token amounts.
+#[inline]
+pub(crate) fn mul_base_raw(a: &Uint, b: &Uint, decimals: usize) -> Uint {
+    if let (Some(a), Some(b), Some(&base)) = (a.to_u128(), b.to_u128(), ES_BASES_U128.get(decimals))
+    {
+        if let Some(p) = a.checked_mul(b) {
+            return Uint::from_u128(p / base);
+        }
+    }
+
+    let mut p = &*a.big() * &*b.big();
+    div_base_assign(&mut p, decimals);
+
+    Uint::from_big(p)
+}
+
+/// `a * 10^decimals / b`, the fixed-point quotient, see `mul_base_raw`
+#[inline]
+pub(crate) fn quot_base_raw(a: &Uint, b: &Uint, decimals: usize) -> Uint {
+    if let (Some(a), Some(b), Some(&base)) = (a.to_u128(), b.to_u128(), ES_BASES_U128.get(decimals))
+    {
+        if let Some(n) = a.checked_mul(base) {
+            return Uint::from_u128(n / b);
+        }
+    }
+
+    Uint::from_big(&*a.big() * &ES_BASES[decimals] / &*b.big())
+}
+
+/// Writes a raw value as "123.456" with exactly `decimals` digits after the point, straight
+/// into the formatter. Values fitting into `u128` are written without allocations.
+///
+/// The fractional part is zero-padded, so `1.05` with 8 decimal points is "1.05000000" (earlier
+/// versions wrote it unpadded, as "1.5000000", which reads as 1.5). Without decimal points
+/// there's no point at all ("5", not "5." or "5.0"). The output is always accepted by `FromStr`.
+pub(crate) fn fmt_fixed(
+    val: &Uint,
+    decimals: usize,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result {
+    if decimals > MAX_DECIMALS {
+        unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
+    }
+
+    if decimals == 0 {
+        return write!(f, "{}", val);
+    }
+
+    match (val.to_u128(), ES_BASES_U128.get(decimals)) {
+        (Some(val), Some(&base)) => {
+            write!(
+                f,
+                "{}.{:0>width$}",
+                val / base,
+                val % base,
+                width = decimals
+            )
+        }
+        _ => {
+            let base = &ES_BASES[decimals];
+            let val = val.big();
+
+            write!(
+                f,
+                "{}.{:0>width$}",
+                &*val / base,
+                &*val % base,
+                width = decimals
+            )
+        }
+    }
+}
+
+/// Compares a raw value against a raw `u64` without allocating
+pub(crate) fn cmp_raw_u64(val: &Uint, other: u64) -> core::cmp::Ordering {
+    match val.to_u64() {
+        Some(v) => v.cmp(&other),
+        None => core::cmp::Ordering::Greater,
+    }
+}
+
+/// `|a - b|` of raw values
+pub(crate) fn abs_diff_raw<T: Ord>(a: &T, b: &T) -> T
+where
+    for<'a> &'a T: Sub<&'a T, Output = T>,
+{
+    if a >= b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+/// `|a - b| <= bps / 10'000 * max(a, b)`, exact (no rounding involved)
+pub(crate) fn within_bps_raw(a: &Uint, b: &Uint, bps: u32) -> bool {
+    let max = if a >= b { a } else { b };
+
+    abs_diff_raw(a, b) * bps::BPS_DENOMINATOR <= max * bps
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::{c::ECs, d::EDs, i::ICs};
+
+    #[test]
+    fn display_pads_the_fraction() {
+        assert_eq!(ECs::<8>::zero().to_string(), "0.00000000");
+        assert_eq!(ECs::<8>::from(1u64).to_string(), "0.00000001");
+        assert_eq!(ECs::<8>::from(105_000_000u64).to_string(), "1.05000000");
+        assert_eq!(ECs::<8>::from(50_000_000u64).to_string(), "0.50000000");
+        assert_eq!(ICs::<8>::from(-5_000_000i64).to_string(), "-0.05000000");
+        assert_eq!(EDs::new(5u64, 3).to_string(), "0.005");
+        assert_eq!(EDs::zero(18).to_string(), "0.000000000000000000");
+    }
+
+    #[test]
+    fn display_without_decimals() {
+        assert_eq!(ECs::<0>::zero().to_string(), "0");
+        assert_eq!(ECs::<0>::from(5u64).to_string(), "5");
+        assert_eq!(EDs::new(5u64, 0).to_string(), "5");
+        assert_eq!(ICs::<0>::from(-5i64).to_string(), "-5");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for raw in [
+            0u64,
+            1,
+            9,
+            10,
+            99_999_999,
+            100_000_000,
+            105_000_000,
+            u64::MAX,
+        ] {
+            let a = ECs::<8>::from(raw);
+            assert_eq!(a.to_string().parse::<ECs<8>>().unwrap(), a);
+
+            let b = ECs::<0>::from(raw);
+            assert_eq!(b.to_string().parse::<ECs<0>>().unwrap(), b);
+        }
+    }
+
+    #[test]
+    fn display_above_u128() {
+        let a = ECs::<8>::new(num_bigint::BigUint::from(u128::MAX) * 100_000_000u32 + 7u32);
+
+        assert_eq!(
+            a.to_string(),
+            "340282366920938463463374607431768211455.00000007"
+        );
+    }
+
+    #[test]
+    fn bases_are_powers_of_ten() {
+        let ten = num_bigint::BigUint::from(10u32);
+
+        for (i, base) in crate::ES_BASES.iter().enumerate() {
+            assert_eq!(*base, ten.pow(i as u32));
+        }
+
+        assert_eq!(crate::math::ten_pow(80), ten.pow(80));
+        assert_eq!(
+            crate::rescale(ten.pow(3), 2, crate::MAX_DECIMALS + 1),
+            ten.pow(78)
+        );
+    }
 }
@@ -0,0 +1,195 @@
+//! Stake-weighted random selection from raw randomness (e.g. the 32 bytes returned by the
+//! management canister's `raw_rand`), without modulo bias: random numbers are read bit by bit
+//! and rejected when they are not below the total weight, instead of being reduced `% total`.
+//! Each draw needs a bit more than `log2(total weight)` random bits on average, so 32 bytes are
+//! enough for several draws, but the randomness can run out, in which case `None` is returned.
+
+use alloc::{vec, vec::Vec};
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, uint::Uint};
+
+/// Reads random bits in order, least significant first
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// `bits` random bits as a number, `None` if there are not enough of them left
+    fn read(&mut self, bits: u64) -> Option<BigUint> {
+        let bits = usize::try_from(bits).ok()?;
+        if self.bytes.len() * 8 - self.pos < bits {
+            return None;
+        }
+
+        let mut val = BigUint::ZERO;
+        for i in 0..bits {
+            let pos = self.pos + i;
+            if self.bytes[pos / 8] >> (pos % 8) & 1 == 1 {
+                val.set_bit(i as u64, true);
+            }
+        }
+        self.pos += bits;
+
+        Some(val)
+    }
+
+    /// Uniform in `0..n` by rejection sampling, `n` should be positive
+    fn below(&mut self, n: &Uint) -> Option<Uint> {
+        let bits = (n - 1u32).bits();
+
+        loop {
+            let val = self.read(bits)?;
+            if val < *n {
+                return Some(val.into());
+            }
+        }
+    }
+}
+
+/// Index of the weight `r` falls into, if the weights are laid out one after another
+fn find(weights: &[&Uint], r: &Uint) -> usize {
+    let mut acc = Uint::ZERO;
+
+    for (i, weight) in weights.iter().enumerate() {
+        acc += *weight;
+        if r < &acc {
+            return i;
+        }
+    }
+
+    unreachable!("Random value is out of the total weight");
+}
+
+/// Picks an index with the probability proportional to its weight. Zero weights are never
+/// picked. `None`, if all the weights are zero or there isn't enough randomness.
+pub fn weighted_pick<const D: usize>(weights: &[ECs<D>], random: &[u8]) -> Option<usize> {
+    let weights: Vec<&Uint> = weights.iter().map(|it| &it.val).collect();
+    let total: Uint = weights.iter().copied().sum();
+    if total.is_zero() {
+        return None;
+    }
+
+    let r = BitReader::new(random).below(&total)?;
+
+    Some(find(&weights, &r))
+}
+
+/// Picks `n` distinct indices one by one, each draw being a `weighted_pick` among the weights
+/// not picked yet. `None`, if there are fewer than `n` non-zero weights or there isn't enough
+/// randomness.
+pub fn weighted_pick_many<const D: usize>(
+    weights: &[ECs<D>],
+    n: usize,
+    random: &[u8],
+) -> Option<Vec<usize>> {
+    let zero = Uint::ZERO;
+    let mut weights: Vec<&Uint> = weights.iter().map(|it| &it.val).collect();
+    let mut total: Uint = weights.iter().copied().sum();
+    let mut reader = BitReader::new(random);
+    let mut picked = vec![];
+
+    for _ in 0..n {
+        if total.is_zero() {
+            return None;
+        }
+
+        let r = reader.below(&total)?;
+        let i = find(&weights, &r);
+
+        total -= weights[i];
+        weights[i] = &zero;
+        picked.push(i);
+    }
+
+    Some(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn weights(raw: &[u64]) -> Vec<E8s> {
+        raw.iter().map(|it| E8s::new(*it)).collect()
+    }
+
+    #[test]
+    fn maps_random_bits_onto_weights() {
+        let w = weights(&[1, 1, 2]);
+
+        // 2 bits per draw, least significant first
+        assert_eq!(weighted_pick(&w, &[0b00]), Some(0));
+        assert_eq!(weighted_pick(&w, &[0b01]), Some(1));
+        assert_eq!(weighted_pick(&w, &[0b10]), Some(2));
+        assert_eq!(weighted_pick(&w, &[0b11]), Some(2));
+    }
+
+    #[test]
+    fn rejects_instead_of_reducing() {
+        let w = weights(&[1, 2]);
+
+        // 3 is rejected, the next 2 bits are 1
+        assert_eq!(weighted_pick(&w, &[0b0111]), Some(1));
+        // every draw is rejected
+        assert_eq!(weighted_pick(&w, &[0xff]), None);
+
+        // exactly proportional over all the possible inputs
+        let w = weights(&[1, 3]);
+        let mut counts = [0; 2];
+        for b in 0..=255u8 {
+            counts[weighted_pick(&w, &[b]).unwrap()] += 1;
+        }
+        assert_eq!(counts, [64, 192]);
+    }
+
+    #[test]
+    fn never_picks_zero_weights() {
+        let w = weights(&[0, 5, 0, 3, 0]);
+
+        for b in 0..=255u8 {
+            if let Some(i) = weighted_pick(&w, &[b, b.rotate_left(3)]) {
+                assert!(i == 1 || i == 3);
+            }
+        }
+
+        assert_eq!(weighted_pick(&weights(&[0, 0]), &[0xaa; 32]), None);
+        assert_eq!(weighted_pick(&weights(&[]), &[0xaa; 32]), None);
+        // a total weight of 1 needs no randomness
+        assert_eq!(weighted_pick(&weights(&[0, 1, 0]), &[]), Some(1));
+    }
+
+    #[test]
+    fn none_when_randomness_runs_out() {
+        let w = weights(&[1, 1, 1, 1]);
+
+        assert_eq!(weighted_pick(&w, &[]), None);
+        assert_eq!(weighted_pick(&w, &[0xff]), Some(3));
+        // the second draw among 3 weights rejects every `0b11` left
+        assert_eq!(weighted_pick_many(&w, 2, &[0xff]), None);
+        assert!(weighted_pick_many(&w, 3, &[0; 32]).is_some());
+    }
+
+    #[test]
+    fn picks_many_distinct() {
+        let w = weights(&[10, 0, 20, 30, 40]);
+        let random = [0x5a; 32];
+
+        let picked = weighted_pick_many(&w, 4, &random).unwrap();
+        let mut sorted = picked.clone();
+        sorted.sort();
+        assert_eq!(sorted, [0, 2, 3, 4]);
+
+        // the first draw is the same as a single pick
+        assert_eq!(Some(picked[0]), weighted_pick(&w, &random));
+
+        assert_eq!(weighted_pick_many(&w, 5, &random), None);
+        assert_eq!(weighted_pick_many(&w, 0, &[]), Some(vec![]));
+    }
+}
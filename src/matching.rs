@@ -0,0 +1,70 @@
+//! Order-book fills. What the maker receives is computed from cumulative totals rather than
+//! per fill, so rounding never accumulates: any sequence of partial fills gives and receives
+//! exactly the order's totals once it's completely filled, and never more before that.
+
+use alloc::{format, string::String};
+
+use crate::{c::ECs, uint::Uint};
+
+/// An order giving `give_total` of one token (`DG` decimals) for `receive_total` of another
+/// (`DR` decimals), tracking what was already filled
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderFill<const DG: usize, const DR: usize> {
+    pub give_total: ECs<DG>,
+    pub receive_total: ECs<DR>,
+    pub given: ECs<DG>,
+    pub received: ECs<DR>,
+}
+
+impl<const DG: usize, const DR: usize> OrderFill<DG, DR> {
+    pub fn new(give_total: ECs<DG>, receive_total: ECs<DR>) -> Self {
+        Self {
+            give_total,
+            receive_total,
+            given: ECs::zero(),
+            received: ECs::zero(),
+        }
+    }
+
+    /// An order of `size` at `price` (received per one given), the total to receive is
+    /// rounded down
+    pub fn from_price(size: ECs<DG>, price: &ECs<DR>) -> Self {
+        let receive_total = ECs::new(&size.val * &price.val / ECs::<DG>::base());
+
+        Self::new(size, receive_total)
+    }
+
+    pub fn remaining(&self) -> ECs<DG> {
+        &self.give_total - &self.given
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.given == self.give_total
+    }
+
+    /// Fills `amount` of the order, returning the `(give, receive)` pair of this fill.
+    /// Fails, if the amount is bigger than what remains.
+    pub fn fill(&mut self, amount: &ECs<DG>) -> Result<(ECs<DG>, ECs<DR>), String> {
+        if &self.given.val + &amount.val > self.give_total.val {
+            return Err(format!(
+                "Can't fill {}, only {} remains",
+                amount,
+                self.remaining()
+            ));
+        }
+
+        let given = &self.given.val + &amount.val;
+        let received = if self.give_total.val.is_zero() {
+            Uint::ZERO
+        } else {
+            &self.receive_total.val * &given / &self.give_total.val
+        };
+
+        let receive = ECs::new(&received - &self.received.val);
+
+        self.given = ECs::new(given);
+        self.received = ECs::new(received);
+
+        Ok((amount.clone(), receive))
+    }
+}
@@ -0,0 +1,588 @@
+//! Transcendental functions in deterministic integer math. Every function is evaluated at
+//! the raw scale plus guard digits, then truncated, so results are accurate to within one unit
+//! of the last decimal point (rounded down) unless stated otherwise.
+
+use num_bigint::BigUint;
+
+use crate::{
+    c::ECs,
+    d::EDs,
+    rounding::{div_rounded, RoundingMode},
+    uint::Uint,
+    ES_BASES,
+};
+
+/// Extra decimal points every evaluation is carried out with
+pub(crate) const GUARD_DIGITS: usize = 10;
+
+/// 10^exp, taken from `ES_BASES` up to `MAX_DECIMALS`
+pub(crate) fn ten_pow(exp: usize) -> BigUint {
+    match ES_BASES.get(exp) {
+        Some(base) => base.clone(),
+        None => BigUint::from(10u64).pow(exp as u32),
+    }
+}
+
+/// The biggest argument `exp` accepts. The cost of `exp` grows quadratically with the argument
+/// (`e^5000` already has 2'172 integer digits, all of them evaluated exactly), and this one still
+/// takes a small fraction of a canister's instruction limit.
+pub const MAX_EXP_ARG: u64 = 5_000;
+
+/// `e^x` for a raw `x` with `decimals` decimal points
+pub(crate) fn exp_raw(x: &BigUint, decimals: usize) -> BigUint {
+    let base = ten_pow(decimals);
+    if *x > &base * MAX_EXP_ARG {
+        unreachable!("exp() argument is above {}", MAX_EXP_ARG);
+    }
+
+    // can't fail - the argument is below the cap
+    let whole = u64::try_from(x / base).unwrap();
+
+    // x is halved m times to get below 1, the Taylor sum is then squared m times,
+    // which amplifies the error by 2^m; the result itself has log10(e^x) integer digits
+    let m = (whole + 1).ilog2() as usize + 1;
+    let guard = GUARD_DIGITS + (whole as usize) * 44 / 100 + m * 31 / 100 + 1;
+    let scale = ten_pow(decimals + guard);
+
+    let r = (x * ten_pow(guard)) >> m;
+
+    // Taylor series at r in [0, 1)
+    let mut sum = scale.clone();
+    let mut term = scale.clone();
+    let mut k = 1u64;
+
+    loop {
+        term = term * &r / (&scale * BigUint::from(k));
+        if term == BigUint::ZERO {
+            break;
+        }
+
+        sum += &term;
+        k += 1;
+    }
+
+    for _ in 0..m {
+        sum = &sum * &sum / &scale;
+    }
+
+    sum / ten_pow(guard)
+}
+
+/// `powf` results have to stay below `2^MAX_POWF_BITS` (judging by the whole parts of the
+/// arguments), so that `y * ln(x)` stays within `MAX_EXP_ARG`
+pub const MAX_POWF_BITS: u64 = 7_200;
+
+/// `x^y` for raw `x` and `y` with `decimals` decimal points, as `exp(y * ln(x))`
+pub(crate) fn powf_raw(x: &BigUint, y: &BigUint, decimals: usize) -> BigUint {
+    let base = ten_pow(decimals);
+
+    if *y == BigUint::ZERO {
+        return base;
+    }
+
+    if *x == BigUint::ZERO {
+        return BigUint::ZERO;
+    }
+
+    // checked before any evaluation, since the guard digits (and so the cost) grow with both
+    let y_whole = u64::try_from(y / &base).unwrap_or(u64::MAX);
+    if y_whole > MAX_EXP_ARG {
+        unreachable!("powf() exponent is above {}", MAX_EXP_ARG);
+    }
+
+    // x < 2^x_whole_bits, so the result is below 2^((y_whole + 1) * x_whole_bits)
+    let x_whole_bits = (x / &base).bits();
+    if (y_whole + 1) * x_whole_bits > MAX_POWF_BITS {
+        unreachable!("powf() result is above 2^{}", MAX_POWF_BITS);
+    }
+
+    // the error of ln(x) is amplified by y and then by the magnitude of the result,
+    // which has about y * log10(x) integer digits
+    let (y_whole, x_whole_bits) = (y_whole as usize, x_whole_bits as usize);
+    let guard = GUARD_DIGITS + (y_whole + 1) * (x_whole_bits + 1) * 31 / 100 + 1;
+
+    let (neg, ln_x) = ln_scaled(x, decimals, guard);
+    let t = ln_x * y / &base;
+    let scale = ten_pow(decimals + guard);
+
+    let result = if neg {
+        // e^-MAX_EXP_ARG is zero at any supported precision
+        if t > &scale * MAX_EXP_ARG {
+            return BigUint::ZERO;
+        }
+
+        &scale * &scale / exp_raw(&t, decimals + guard)
+    } else {
+        exp_raw(&t, decimals + guard)
+    };
+
+    // rounded to the nearest, so exact results (e.g. 4^0.5) aren't off by one unit
+    div_rounded(&result, &ten_pow(guard), RoundingMode::HalfUp)
+}
+
+/// Weighted geometric mean of raw values with the same decimal points, rounded down.
+/// The product is kept at full precision, so the root is the only rounding step.
+pub(crate) fn geometric_mean_raw<'a>(
+    items: impl Iterator<Item = (&'a Uint, u32)>,
+) -> Option<BigUint> {
+    let mut product = BigUint::from(1u64);
+    let mut total_weight = 0u32;
+
+    for (val, weight) in items {
+        product *= val.big().pow(weight);
+        total_weight = total_weight
+            .checked_add(weight)
+            .unwrap_or_else(|| unreachable!("Total weight overflow"));
+    }
+
+    if total_weight == 0 {
+        return None;
+    }
+
+    Some(product.nth_root(total_weight))
+}
+
+/// Widest raw value `checked_pow` accepts, the width of ICRC-1 and EVM amounts
+const MAX_CHECKED_BITS: u64 = 256;
+
+/// The widest raw result `pow` (and the formulas built on it) evaluates, wider ones trap.
+/// The guard digits grow with the result, so this bounds the cost the same way `MAX_POWF_BITS` does.
+pub const MAX_POW_BITS: u64 = 7_200;
+
+/// Upper bound on the integer bits of `x^n` (up to `max_bits`): the smaller one of
+/// `n * bits(whole part of x)` and `n * (x - 1) / ln(2)`, since `ln(x) <= x - 1`
+fn pow_whole_bits(x: &BigUint, decimals: usize, n: u32, max_bits: u64) -> u64 {
+    let base = ten_pow(decimals);
+    if *x <= base {
+        return 0;
+    }
+
+    let by_bits = (x / &base).bits().saturating_mul(n as u64);
+    let by_ln = (x - &base) * n * 1_443u32 / (base * 1_000u32) + 1u32;
+    let by_ln = u64::try_from(by_ln).unwrap_or(u64::MAX);
+
+    by_bits.min(by_ln).min(max_bits)
+}
+
+/// `x^n` for a raw `x` with `decimals` decimal points via square-and-multiply, evaluated with
+/// guard digits. Returns `None` as soon as the raw result is known to exceed `max_bits`.
+pub(crate) fn pow_raw(x: &BigUint, decimals: usize, n: u32, max_bits: u64) -> Option<BigUint> {
+    // every truncation is amplified by the following multiplications, so the error grows with
+    // the number of steps and with the magnitude of the result
+    let whole_bits = pow_whole_bits(x, decimals, n, max_bits) as usize;
+    let guard = GUARD_DIGITS + n.checked_ilog10().unwrap_or(0) as usize + 1 + whole_bits * 31 / 100;
+
+    let guard_scale = ten_pow(guard);
+    let scale = ten_pow(decimals + guard);
+    let exceeds = |v: &BigUint| v.bits() > max_bits + guard_scale.bits() + 1;
+
+    let mut result = scale.clone();
+    let mut base = x * &guard_scale;
+    let mut n = n;
+
+    while n > 0 {
+        if n & 1 == 1 {
+            result = result * &base / &scale;
+            if exceeds(&result) {
+                return None;
+            }
+        }
+
+        n >>= 1;
+        if n > 0 {
+            base = &base * &base / &scale;
+            if exceeds(&base) && *x >= ten_pow(decimals) {
+                return None;
+            }
+        }
+    }
+
+    let result = result / guard_scale;
+
+    if result.bits() > max_bits {
+        return None;
+    }
+
+    Some(result)
+}
+
+pub(crate) fn pow_overflow() -> BigUint {
+    unreachable!("pow() result is above 2^{}", MAX_POW_BITS)
+}
+
+/// `2 * atanh(num / den)` at `scale`, which is `ln((den + num) / (den - num))`
+fn atanh2(num: &BigUint, den: &BigUint, scale: &BigUint) -> BigUint {
+    let t = scale * num / den;
+    let t2 = &t * &t / scale;
+
+    let mut power = t;
+    let mut sum = BigUint::ZERO;
+    let mut n = 1u64;
+
+    loop {
+        let term = &power / BigUint::from(n);
+        if term == BigUint::ZERO {
+            break;
+        }
+
+        sum += term;
+        power = power * &t2 / scale;
+        n += 2;
+    }
+
+    sum * 2u32
+}
+
+/// `ln(2)` at `scale`
+fn ln2_scaled(scale: &BigUint) -> BigUint {
+    atanh2(&BigUint::from(1u64), &BigUint::from(3u64), scale)
+}
+
+/// `ln(x)` for a raw `x` with `decimals` decimal points, evaluated at `decimals + guard`
+/// decimal points. Returns the sign (`true` for negative) and the magnitude.
+pub(crate) fn ln_scaled(x: &BigUint, decimals: usize, guard: usize) -> (bool, BigUint) {
+    if *x == BigUint::ZERO {
+        unreachable!("Logarithm of zero is undefined");
+    }
+
+    let scale = ten_pow(decimals + guard);
+    let xs = x * ten_pow(guard);
+
+    // xs = z * 2^k, where z is in [1, 2)
+    let mut k = xs.bits() as i64 - scale.bits() as i64;
+    let mut z = if k >= 0 {
+        &xs >> (k as u64)
+    } else {
+        &xs << (-k as u64)
+    };
+
+    if z < scale {
+        z <<= 1;
+        k -= 1;
+    } else if z >= &scale * 2u32 {
+        z >>= 1;
+        k += 1;
+    }
+
+    let ln_z = atanh2(&(&z - &scale), &(&z + &scale), &scale);
+    let k_ln2 = ln2_scaled(&scale) * BigUint::from(k.unsigned_abs());
+
+    if k >= 0 {
+        (false, k_ln2 + ln_z)
+    } else if k_ln2 >= ln_z {
+        (true, k_ln2 - ln_z)
+    } else {
+        (false, ln_z - k_ln2)
+    }
+}
+
+/// Guard digits for logarithms, `k * ln(2)` amplifies the error of `ln(2)` by `k`
+fn ln_guard(x: &BigUint) -> usize {
+    GUARD_DIGITS + (x.bits() as usize) * 31 / 100 + 1
+}
+
+/// `log_base(x)` for a raw `x`, the sign (`true` for negative) and the magnitude
+pub(crate) fn log_raw(x: &BigUint, decimals: usize, base: u64) -> (bool, BigUint) {
+    let guard = ln_guard(x);
+    let (neg, ln_x) = ln_scaled(x, decimals, guard);
+
+    let val = match base {
+        0 | 1 => unreachable!("Invalid logarithm base"),
+        2 => {
+            let scale = ten_pow(decimals + guard);
+            ln_x * &scale / ln2_scaled(&scale) / ten_pow(guard)
+        }
+        _ => {
+            let scale = ten_pow(decimals + guard);
+            let (_, ln_base) = ln_scaled(&BigUint::from(base), 0, decimals + guard);
+
+            ln_x * &scale / ln_base / ten_pow(guard)
+        }
+    };
+
+    (neg, val)
+}
+
+/// `ln(x)` for a raw `x`, the sign (`true` for negative) and the magnitude
+pub(crate) fn ln_raw(x: &BigUint, decimals: usize) -> (bool, BigUint) {
+    let guard = ln_guard(x);
+    let (neg, val) = ln_scaled(x, decimals, guard);
+
+    (neg, val / ten_pow(guard))
+}
+
+fn expect_non_negative((neg, val): (bool, BigUint)) -> BigUint {
+    if neg {
+        unreachable!("Logarithm of a value below 1 is negative, use the *_signed version");
+    }
+
+    val
+}
+
+impl<const D: usize> ECs<D> {
+    /// Geometric mean of the items, rounded down. Returns `None` for an empty slice.
+    pub fn geometric_mean(items: &[Self]) -> Option<Self> {
+        geometric_mean_raw(items.iter().map(|it| (&it.val, 1))).map(Self::new)
+    }
+
+    /// Geometric mean of the items weighted by integer weights (`prod(x^w)^(1/sum(w))`),
+    /// rounded down. Returns `None` if the total weight is zero.
+    pub fn weighted_geometric_mean(items: &[(Self, u32)]) -> Option<Self> {
+        geometric_mean_raw(items.iter().map(|(it, w)| (&it.val, *w))).map(Self::new)
+    }
+
+    /// `e^self`. Traps, if `self` is above `MAX_EXP_ARG` (5'000), which bounds the cost.
+    pub fn exp(&self) -> Self {
+        Self::new(exp_raw(&self.val.big(), D))
+    }
+
+    /// `self^n`, evaluated with guard digits to avoid truncating at every step.
+    /// Traps, if the raw result is above `2^MAX_POW_BITS`, which bounds the cost.
+    pub fn pow(&self, n: u32) -> Self {
+        Self::new(pow_raw(&self.val.big(), D, n, MAX_POW_BITS).unwrap_or_else(pow_overflow))
+    }
+
+    /// Same as `pow`, but returns `None` if the raw result doesn't fit into 256 bits
+    pub fn checked_pow(&self, n: u32) -> Option<Self> {
+        pow_raw(&self.val.big(), D, n, MAX_CHECKED_BITS).map(Self::new)
+    }
+
+    /// `self^exponent` for a fractional exponent, as `exp(exponent * ln(self))`.
+    /// Guard digits account for the magnitude of the result, which is rounded to the nearest,
+    /// so the error stays within one unit of the last decimal point. To bound the cost, traps
+    /// right away if the exponent is above `MAX_EXP_ARG` (5'000) or the result can reach
+    /// `2^MAX_POWF_BITS`.
+    pub fn powf(&self, exponent: &Self) -> Self {
+        Self::new(powf_raw(&self.val.big(), &exponent.val.big(), D))
+    }
+
+    /// Natural logarithm. Traps, if `self` is below 1, see `ln_signed`.
+    pub fn ln(&self) -> Self {
+        Self::new(expect_non_negative(ln_raw(&self.val.big(), D)))
+    }
+
+    /// Natural logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn ln_signed(&self) -> (bool, Self) {
+        let (neg, val) = ln_raw(&self.val.big(), D);
+
+        (neg, Self::new(val))
+    }
+
+    /// Base-2 logarithm. Traps, if `self` is below 1, see `log2_signed`.
+    pub fn log2(&self) -> Self {
+        Self::new(expect_non_negative(log_raw(&self.val.big(), D, 2)))
+    }
+
+    /// Base-2 logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn log2_signed(&self) -> (bool, Self) {
+        let (neg, val) = log_raw(&self.val.big(), D, 2);
+
+        (neg, Self::new(val))
+    }
+
+    /// Base-10 logarithm. Traps, if `self` is below 1, see `log10_signed`.
+    pub fn log10(&self) -> Self {
+        Self::new(expect_non_negative(log_raw(&self.val.big(), D, 10)))
+    }
+
+    /// Base-10 logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn log10_signed(&self) -> (bool, Self) {
+        let (neg, val) = log_raw(&self.val.big(), D, 10);
+
+        (neg, Self::new(val))
+    }
+}
+
+fn expect_same_decimals<'a>(items: impl Iterator<Item = &'a EDs>) -> Option<u8> {
+    let mut decimals = None;
+
+    for it in items {
+        match decimals {
+            None => decimals = Some(it.decimals),
+            Some(d) if d != it.decimals => unreachable!("Incompatible decimal points"),
+            _ => {}
+        }
+    }
+
+    decimals
+}
+
+impl EDs {
+    /// Geometric mean of the items, rounded down. Returns `None` for an empty slice.
+    pub fn geometric_mean(items: &[Self]) -> Option<Self> {
+        let decimals = expect_same_decimals(items.iter())?;
+
+        geometric_mean_raw(items.iter().map(|it| (&it.val, 1))).map(|val| Self::new(val, decimals))
+    }
+
+    /// Geometric mean of the items weighted by integer weights (`prod(x^w)^(1/sum(w))`),
+    /// rounded down. Returns `None` if the total weight is zero.
+    pub fn weighted_geometric_mean(items: &[(Self, u32)]) -> Option<Self> {
+        let decimals = expect_same_decimals(items.iter().map(|(it, _)| it))?;
+
+        geometric_mean_raw(items.iter().map(|(it, w)| (&it.val, *w)))
+            .map(|val| Self::new(val, decimals))
+    }
+
+    /// `e^self`. Traps, if `self` is above `MAX_EXP_ARG` (5'000), which bounds the cost.
+    pub fn exp(&self) -> Self {
+        Self::new(
+            exp_raw(&self.val.big(), self.decimals as usize),
+            self.decimals,
+        )
+    }
+
+    /// `self^n`, evaluated with guard digits to avoid truncating at every step.
+    /// Traps, if the raw result is above `2^MAX_POW_BITS`, which bounds the cost.
+    pub fn pow(&self, n: u32) -> Self {
+        let val = pow_raw(&self.val.big(), self.decimals as usize, n, MAX_POW_BITS)
+            .unwrap_or_else(pow_overflow);
+
+        Self::new(val, self.decimals)
+    }
+
+    /// Same as `pow`, but returns `None` if the raw result doesn't fit into 256 bits
+    pub fn checked_pow(&self, n: u32) -> Option<Self> {
+        pow_raw(&self.val.big(), self.decimals as usize, n, MAX_CHECKED_BITS)
+            .map(|val| Self::new(val, self.decimals))
+    }
+
+    /// `self^exponent` for a fractional exponent, as `exp(exponent * ln(self))`.
+    /// Guard digits account for the magnitude of the result, which is rounded to the nearest,
+    /// so the error stays within one unit of the last decimal point. To bound the cost, traps
+    /// right away if the exponent is above `MAX_EXP_ARG` (5'000) or the result can reach
+    /// `2^MAX_POWF_BITS`.
+    pub fn powf(&self, exponent: &Self) -> Self {
+        if self.decimals != exponent.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        let val = powf_raw(&self.val.big(), &exponent.val.big(), self.decimals as usize);
+
+        Self::new(val, self.decimals)
+    }
+
+    /// Natural logarithm. Traps, if `self` is below 1, see `ln_signed`.
+    pub fn ln(&self) -> Self {
+        let val = expect_non_negative(ln_raw(&self.val.big(), self.decimals as usize));
+
+        Self::new(val, self.decimals)
+    }
+
+    /// Natural logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn ln_signed(&self) -> (bool, Self) {
+        let (neg, val) = ln_raw(&self.val.big(), self.decimals as usize);
+
+        (neg, Self::new(val, self.decimals))
+    }
+
+    /// Base-2 logarithm. Traps, if `self` is below 1, see `log2_signed`.
+    pub fn log2(&self) -> Self {
+        let val = expect_non_negative(log_raw(&self.val.big(), self.decimals as usize, 2));
+
+        Self::new(val, self.decimals)
+    }
+
+    /// Base-2 logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn log2_signed(&self) -> (bool, Self) {
+        let (neg, val) = log_raw(&self.val.big(), self.decimals as usize, 2);
+
+        (neg, Self::new(val, self.decimals))
+    }
+
+    /// Base-10 logarithm. Traps, if `self` is below 1, see `log10_signed`.
+    pub fn log10(&self) -> Self {
+        let val = expect_non_negative(log_raw(&self.val.big(), self.decimals as usize, 10));
+
+        Self::new(val, self.decimals)
+    }
+
+    /// Base-10 logarithm as a sign (`true` for negative) and a magnitude. Traps on zero.
+    pub fn log10_signed(&self) -> (bool, Self) {
+        let (neg, val) = log_raw(&self.val.big(), self.decimals as usize, 10);
+
+        (neg, Self::new(val, self.decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exp_at_the_cap() {
+        let x = ECs::<8>::from_whole(MAX_EXP_ARG);
+
+        // e^5000 = 2.9676283840236670...e2171
+        assert_eq!(x.exp().val.to_string().len(), 2172 + 8);
+        assert!(x.exp().to_string().starts_with("29676283840236670"));
+    }
+
+    #[test]
+    #[should_panic(expected = "exp() argument is above 5000")]
+    fn exp_above_the_cap_traps() {
+        (ECs::<8>::from_whole(MAX_EXP_ARG) + ECs::<8>::from(1u64)).exp();
+    }
+
+    #[test]
+    fn powf_within_the_caps() {
+        let x = ECs::<8>::from_whole(3) / ECs::<8>::from_whole(2);
+
+        // 1.5^5000 ~ 2^2925, 0.5^5000 is zero
+        assert!(x.powf(&ECs::from_whole(MAX_EXP_ARG)).val.bits() > 2900);
+        assert!(ECs::<8>::from(50_000_000u64)
+            .powf(&ECs::from_whole(MAX_EXP_ARG))
+            .is_zero());
+    }
+
+    #[test]
+    fn powf_just_past_the_exp_cap_is_zero() {
+        // ln(0.00000001) = -18.42068..., so y * ln(x) = -5000.47, above the cap by a fraction
+        let y = ECs::<8>::from(27_146_000_000u64);
+
+        assert!(ECs::<8>::from(1u64).powf(&y).is_zero());
+        // and just below the cap, e^-4999.4 is zero at 8 decimal points as well
+        assert!(ECs::<8>::from(1u64)
+            .powf(&ECs::from(27_140_000_000u64))
+            .is_zero());
+    }
+
+    #[test]
+    fn pow_is_accurate_for_big_results_and_exponents() {
+        let e8s = |raw: u64| ECs::<8>::from(raw);
+
+        // 1.0001^100000 = 22015.45604855...
+        assert_eq!(e8s(100_010_000).pow(100_000), e8s(2_201_545_604_855));
+        // 0.99999999^100000000 = 0.36787943...
+        assert_eq!(e8s(99_999_999).pow(100_000_000), e8s(36_787_943));
+        // 1.00000001^(2^32 - 1) = 4495788462262349260.92775138...
+        assert_eq!(
+            e8s(100_000_001).pow(u32::MAX).val,
+            BigUint::from(449_578_846_263_492_609_277_513_814u128)
+        );
+
+        let expected =
+            "12338405969061734792274390994867800574218690051484280854252297518432942262365\
+            2841193932245134021231941529455308114511924428000273557831297406949841351167568024\
+            30116410527190306498735542";
+        assert_eq!(e8s(150_000_000).pow(1_000).val.to_string(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "pow() result is above 2^7200")]
+    fn pow_above_the_cap_traps() {
+        ECs::<8>::from_whole(2).pow(10_000);
+    }
+
+    #[test]
+    #[should_panic(expected = "powf() exponent is above 5000")]
+    fn powf_huge_exponent_traps() {
+        ECs::<8>::from(50_000_000u64).powf(&ECs::from_whole(1_000_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "powf() result is above 2^7200")]
+    fn powf_huge_result_traps() {
+        ECs::<18>::from_whole(1_000_000_000).powf(&ECs::from_whole(1_000));
+    }
+}
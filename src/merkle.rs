@@ -0,0 +1,249 @@
+//! Merkle-sum tree for proofs of reserves/liabilities: every node commits both to the hashes
+//! and to the sums of its children, so a user holding an inclusion proof of their balance can
+//! check it's counted in the published total, which is computed with this crate's arithmetic.
+//!
+//! The hash function is provided by the caller (e.g. SHA-256), the crate doesn't depend on any.
+//! A node hash is `H(left_hash || left_sum || right_hash || right_sum)`, where sums are encoded
+//! as a 4-byte big-endian length followed by the big-endian raw value. Odd levels are padded
+//! with an empty node: a zero hash with a zero sum.
+
+use alloc::{vec, vec::Vec};
+
+use crate::c::ECs;
+
+pub type Hash = [u8; 32];
+
+/// Hash function over the node preimage, e.g. `|b| sha2::Sha256::digest(b).into()`
+pub type Hasher = fn(&[u8]) -> Hash;
+
+/// Padding node hash
+pub const EMPTY_HASH: Hash = [0; 32];
+
+fn encode_sum<const D: usize>(sum: &ECs<D>, buf: &mut Vec<u8>) {
+    let bytes = sum.val.to_bytes_be();
+
+    buf.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    buf.extend_from_slice(&bytes);
+}
+
+fn node<const D: usize>(
+    left: &(Hash, ECs<D>),
+    right: &(Hash, ECs<D>),
+    hasher: Hasher,
+) -> (Hash, ECs<D>) {
+    let mut buf = Vec::with_capacity(2 * (32 + 4 + 32));
+    buf.extend_from_slice(&left.0);
+    encode_sum(&left.1, &mut buf);
+    buf.extend_from_slice(&right.0);
+    encode_sum(&right.1, &mut buf);
+
+    (hasher(&buf), &left.1 + &right.1)
+}
+
+fn empty<const D: usize>() -> (Hash, ECs<D>) {
+    (EMPTY_HASH, ECs::zero())
+}
+
+/// Inclusion proof of a leaf: its siblings from the bottom up
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleSumProof<const D: usize> {
+    pub index: usize,
+    pub leaf: (Hash, ECs<D>),
+    pub siblings: Vec<(Hash, ECs<D>)>,
+}
+
+impl<const D: usize> MerkleSumProof<D> {
+    /// Recomputes the root from the leaf and the siblings
+    pub fn root(&self, hasher: Hasher) -> (Hash, ECs<D>) {
+        let mut acc = self.leaf.clone();
+        let mut index = self.index;
+
+        for sibling in &self.siblings {
+            acc = if index & 1 == 0 {
+                node(&acc, sibling, hasher)
+            } else {
+                node(sibling, &acc, hasher)
+            };
+            index /= 2;
+        }
+
+        acc
+    }
+
+    /// Whether the leaf is included in the tree with this root hash and total
+    pub fn verify(&self, root_hash: &Hash, total: &ECs<D>, hasher: Hasher) -> bool {
+        let (hash, sum) = self.root(hasher);
+
+        &hash == root_hash && &sum == total
+    }
+}
+
+/// Merkle-sum tree over `(hash, amount)` leaves, e.g. hashes of account ids with their balances
+#[derive(Clone, Debug)]
+pub struct MerkleSumTree<const D: usize> {
+    /// From the leaves (the first level) up to the root (a single node), without padding
+    levels: Vec<Vec<(Hash, ECs<D>)>>,
+    hasher: Hasher,
+}
+
+impl<const D: usize> MerkleSumTree<D> {
+    /// `None`, if there are no leaves
+    pub fn new(leaves: Vec<(Hash, ECs<D>)>, hasher: Hasher) -> Option<Self> {
+        if leaves.is_empty() {
+            return None;
+        }
+
+        let mut levels = vec![leaves];
+
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node(left, right, hasher),
+                    [left] => node(left, &empty(), hasher),
+                    _ => unreachable!(),
+                })
+                .collect();
+
+            levels.push(next);
+        }
+
+        Some(Self { levels, hasher })
+    }
+
+    pub fn root_hash(&self) -> &Hash {
+        &self.levels.last().unwrap()[0].0
+    }
+
+    /// Sum of all the leaves
+    pub fn total(&self) -> &ECs<D> {
+        &self.levels.last().unwrap()[0].1
+    }
+
+    pub fn leaves(&self) -> &[(Hash, ECs<D>)] {
+        &self.levels[0]
+    }
+
+    /// `None`, if there is no such leaf
+    pub fn proof(&self, index: usize) -> Option<MerkleSumProof<D>> {
+        let leaf = self.levels[0].get(index)?.clone();
+
+        let mut siblings = Vec::with_capacity(self.levels.len() - 1);
+        let mut i = index;
+
+        for level in &self.levels[..self.levels.len() - 1] {
+            siblings.push(level.get(i ^ 1).cloned().unwrap_or_else(empty));
+            i /= 2;
+        }
+
+        Some(MerkleSumProof {
+            index,
+            leaf,
+            siblings,
+        })
+    }
+
+    /// Whether the proof is valid against this tree's root
+    pub fn verify(&self, proof: &MerkleSumProof<D>) -> bool {
+        proof.verify(self.root_hash(), self.total(), self.hasher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    /// FNV-1a stretched over 32 bytes, good enough to tell preimages apart in tests
+    fn fnv(bytes: &[u8]) -> Hash {
+        let mut hash = [0u8; 32];
+        let mut acc = 0xcbf2_9ce4_8422_2325u64;
+
+        for chunk in hash.chunks_mut(8) {
+            for b in bytes {
+                acc = (acc ^ *b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+            }
+            chunk.copy_from_slice(&acc.to_be_bytes());
+        }
+
+        hash
+    }
+
+    fn leaves(n: u64) -> Vec<(Hash, E8s)> {
+        (1..=n)
+            .map(|i| (fnv(&i.to_be_bytes()), E8s::new(i * 100_000_000)))
+            .collect()
+    }
+
+    #[test]
+    fn proofs_of_an_odd_sized_tree() {
+        let tree = MerkleSumTree::new(leaves(5), fnv).unwrap();
+
+        assert_eq!(tree.total(), &E8s::new(1_500_000_000u64));
+
+        for i in 0..5 {
+            let proof = tree.proof(i).unwrap();
+
+            assert_eq!(proof.siblings.len(), 3);
+            assert!(tree.verify(&proof));
+            assert_eq!(proof.root(fnv), (*tree.root_hash(), tree.total().clone()));
+        }
+
+        // the last leaf is paired with an empty node on every level
+        let last = tree.proof(4).unwrap();
+        assert_eq!(last.siblings[0], (EMPTY_HASH, E8s::zero()));
+        assert_eq!(last.siblings[1], (EMPTY_HASH, E8s::zero()));
+        assert_eq!(last.siblings[2].1, E8s::new(1_000_000_000u64));
+
+        assert!(tree.proof(5).is_none());
+    }
+
+    #[test]
+    fn root_commits_to_hashes_and_sums() {
+        let l = leaves(3);
+        let tree = MerkleSumTree::new(l.clone(), fnv).unwrap();
+
+        let left = node(&l[0], &l[1], fnv);
+        let right = node(&l[2], &empty(), fnv);
+        let root = node(&left, &right, fnv);
+
+        assert_eq!(tree.root_hash(), &root.0);
+        assert_eq!(tree.total(), &root.1);
+    }
+
+    #[test]
+    fn tampered_proofs_fail() {
+        let tree = MerkleSumTree::new(leaves(6), fnv).unwrap();
+        let proof = tree.proof(2).unwrap();
+
+        let mut more = proof.clone();
+        more.leaf.1 += E8s::new(1u64);
+        assert!(!tree.verify(&more));
+
+        let mut moved = proof.clone();
+        moved.index = 3;
+        assert!(!tree.verify(&moved));
+
+        // a sibling hiding some liabilities
+        let mut hidden = proof.clone();
+        hidden.siblings[1].1 = E8s::zero();
+        assert!(!tree.verify(&hidden));
+
+        assert!(!proof.verify(tree.root_hash(), &E8s::new(1u64), fnv));
+        assert!(proof.verify(tree.root_hash(), tree.total(), fnv));
+    }
+
+    #[test]
+    fn single_and_no_leaves() {
+        assert!(MerkleSumTree::<8>::new(Vec::new(), fnv).is_none());
+
+        let tree = MerkleSumTree::new(leaves(1), fnv).unwrap();
+        let proof = tree.proof(0).unwrap();
+
+        assert_eq!(tree.root_hash(), &leaves(1)[0].0);
+        assert!(proof.siblings.is_empty());
+        assert!(tree.verify(&proof));
+    }
+}
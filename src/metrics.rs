@@ -0,0 +1,69 @@
+//! Values for Prometheus-style `/metrics` endpoints. Both representations are in whole tokens
+//! (`1.5`, not `150000000`):
+//!
+//! - `to_metric_f64` is the nearest `f64`, so it is exact up to 2^53 and otherwise keeps
+//!   15-17 significant digits (relative error below 2^-53)
+//! - `to_metric_string` is the exact decimal value with trailing zeros trimmed, e.g. `"1.5"`,
+//!   which scrapers parse into the same `f64`
+//!
+//! Values above `f64::MAX` (only reachable with very large raw values) saturate to `f64::MAX`
+//! instead of becoming `+Inf`, which would break rate and sum queries.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::{c::ECs, d::EDs};
+
+/// `Display` output ("1.50000000") with trailing zeros (and a trailing point) trimmed
+fn trim_zeros(mut s: String) -> String {
+    if s.contains('.') {
+        let len = s.trim_end_matches('0').trim_end_matches('.').len();
+        s.truncate(len);
+    }
+
+    s
+}
+
+/// Correctly rounded by the `f64` parser, `+Inf` for values above `f64::MAX`
+fn nearest_f64(exact: &str) -> f64 {
+    // can't fail - always a valid decimal number
+    exact.parse().unwrap()
+}
+
+fn metric_f64(exact: &str) -> f64 {
+    nearest_f64(exact).min(f64::MAX)
+}
+
+fn metric_string(exact: String) -> String {
+    if nearest_f64(&exact).is_infinite() {
+        format!("{:e}", f64::MAX)
+    } else {
+        exact
+    }
+}
+
+impl<const D: usize> ECs<D> {
+    /// Whole tokens as the nearest `f64`, saturating, see the module docs
+    pub fn to_metric_f64(&self) -> f64 {
+        metric_f64(&trim_zeros(self.to_string()))
+    }
+
+    /// Whole tokens as an exact decimal string, saturating, see the module docs
+    pub fn to_metric_string(&self) -> String {
+        metric_string(trim_zeros(self.to_string()))
+    }
+}
+
+impl EDs {
+    /// Whole tokens as the nearest `f64`, saturating, see the module docs
+    pub fn to_metric_f64(&self) -> f64 {
+        metric_f64(&trim_zeros(self.to_string()))
+    }
+
+    /// Whole tokens as an exact decimal string, saturating, see the module docs
+    pub fn to_metric_string(&self) -> String {
+        metric_string(trim_zeros(self.to_string()))
+    }
+}
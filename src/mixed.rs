@@ -0,0 +1,154 @@
+//! Operators between `ECs<D>` and `EDs`. Decimal points are checked at runtime, just like
+//! between two `EDs`. The result takes the type of the left operand.
+
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign};
+
+use crate::{c::ECs, d::EDs, mul_base_raw, quot_base_raw, uint::Uint};
+
+fn check_decimals<const D: usize>(rhs_decimals: u8) {
+    if D != rhs_decimals as usize {
+        unreachable!("Incompatible decimal points");
+    }
+}
+
+fn add_vals(a: &Uint, b: &Uint, _decimals: u8) -> Uint {
+    a + b
+}
+
+fn sub_vals(a: &Uint, b: &Uint, _decimals: u8) -> Uint {
+    a - b
+}
+
+fn mul_vals(a: &Uint, b: &Uint, decimals: u8) -> Uint {
+    mul_base_raw(a, b, decimals as usize)
+}
+
+fn div_vals(a: &Uint, b: &Uint, decimals: u8) -> Uint {
+    quot_base_raw(a, b, decimals as usize)
+}
+
+macro_rules! mixed_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident, $f:ident) => {
+        impl<const D: usize> $op<&EDs> for &ECs<D> {
+            type Output = ECs<D>;
+
+            fn $method(self, rhs: &EDs) -> Self::Output {
+                check_decimals::<D>(rhs.decimals);
+
+                ECs::new($f(&self.val, &rhs.val, rhs.decimals))
+            }
+        }
+
+        impl<const D: usize> $op<EDs> for ECs<D> {
+            type Output = ECs<D>;
+
+            fn $method(self, rhs: EDs) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<const D: usize> $op_assign<&EDs> for ECs<D> {
+            fn $method_assign(&mut self, rhs: &EDs) {
+                check_decimals::<D>(rhs.decimals);
+
+                self.val = $f(&self.val, &rhs.val, rhs.decimals);
+            }
+        }
+
+        impl<const D: usize> $op_assign<EDs> for ECs<D> {
+            fn $method_assign(&mut self, rhs: EDs) {
+                self.$method_assign(&rhs)
+            }
+        }
+
+        impl<const D: usize> $op<&ECs<D>> for &EDs {
+            type Output = EDs;
+
+            fn $method(self, rhs: &ECs<D>) -> Self::Output {
+                check_decimals::<D>(self.decimals);
+
+                EDs::new($f(&self.val, &rhs.val, self.decimals), self.decimals)
+            }
+        }
+
+        impl<const D: usize> $op<ECs<D>> for EDs {
+            type Output = EDs;
+
+            fn $method(self, rhs: ECs<D>) -> Self::Output {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl<const D: usize> $op_assign<&ECs<D>> for EDs {
+            fn $method_assign(&mut self, rhs: &ECs<D>) {
+                check_decimals::<D>(self.decimals);
+
+                self.val = $f(&self.val, &rhs.val, self.decimals);
+            }
+        }
+
+        impl<const D: usize> $op_assign<ECs<D>> for EDs {
+            fn $method_assign(&mut self, rhs: ECs<D>) {
+                self.$method_assign(&rhs)
+            }
+        }
+    };
+}
+
+mixed_op!(Add, add, AddAssign, add_assign, add_vals);
+mixed_op!(Sub, sub, SubAssign, sub_assign, sub_vals);
+mixed_op!(Mul, mul, MulAssign, mul_assign, mul_vals);
+mixed_op!(Div, div, DivAssign, div_assign, div_vals);
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::*;
+    use crate::c::E8s;
+
+    fn e8s(raw: u64) -> E8s {
+        E8s::new(raw)
+    }
+
+    fn eds(raw: u64) -> EDs {
+        EDs::new(raw, 8)
+    }
+
+    #[test]
+    fn matches_same_type_operators() {
+        let (a, b) = (e8s(150_000_000), e8s(30_000_000));
+
+        assert_eq!(&a + &eds(30_000_000), &a + &b);
+        assert_eq!(&a - &eds(30_000_000), &a - &b);
+        assert_eq!(&a * &eds(30_000_000), &a * &b);
+        assert_eq!(&a / &eds(30_000_000), &a / &b);
+
+        assert_eq!(&eds(150_000_000) * &b, eds(45_000_000));
+        assert_eq!(&eds(100_000_000) / &e8s(30_000_000), eds(333_333_333));
+
+        let mut c = e8s(100_000_000);
+        c *= eds(250_000_000);
+        c /= &eds(50_000_000);
+        assert_eq!(c, e8s(500_000_000));
+    }
+
+    #[test]
+    fn rounds_like_ecs_above_u128() {
+        let a = E8s::new(BigUint::from(u128::MAX) * 3u32 + 7u32);
+        let b = e8s(123_456_789);
+
+        assert_eq!(&a * &b.as_dynamic().to_eds(), &a * &b);
+        assert_eq!(&a / &b.as_dynamic().to_eds(), &a / &b);
+        assert_eq!(
+            &a.as_dynamic().to_eds() * &b,
+            (&a * &b).as_dynamic().to_eds()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible decimal points")]
+    fn traps_on_other_decimals() {
+        let _ = e8s(1) + EDs::new(1u64, 6);
+    }
+}
@@ -0,0 +1,275 @@
+/// Declares a token newtype around `ECs<D>` with arithmetic, Candid, serde, `Storable`, `Display`,
+/// `FromStr` and conversion impls, all delegating to the wrapped value (Candid and `Storable` ones
+/// only with the respective features on):
+///
+/// ```ignore
+/// ic_e8s::token_newtype! {
+///     /// ICP amount
+///     pub struct Icp(E8s);
+/// }
+/// ```
+#[macro_export]
+macro_rules! token_newtype {
+    ($(#[$meta:meta])* $vis:vis struct $name:ident($inner:ty);) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+        $vis struct $name(pub $inner);
+
+        impl $name {
+            pub fn new(value: $inner) -> Self {
+                Self(value)
+            }
+
+            pub fn zero() -> Self {
+                Self(<$inner>::zero())
+            }
+
+            pub fn one() -> Self {
+                Self(<$inner>::one())
+            }
+
+            pub fn into_inner(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::str::FromStr for $name {
+            type Err = $crate::__private::String;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                <$inner as ::core::str::FromStr>::from_str(s).map(Self)
+            }
+        }
+
+        impl From<$inner> for $name {
+            fn from(value: $inner) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for $inner {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self(<$inner>::from(value))
+            }
+        }
+
+        impl From<u128> for $name {
+            fn from(value: u128) -> Self {
+                Self(<$inner>::from(value))
+            }
+        }
+
+        $crate::token_newtype!(@op $name, Add, add, AddAssign, add_assign);
+        $crate::token_newtype!(@op $name, Sub, sub, SubAssign, sub_assign);
+        $crate::token_newtype!(@op $name, Mul, mul, MulAssign, mul_assign);
+        $crate::token_newtype!(@op $name, Div, div, DivAssign, div_assign);
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                <$inner as $crate::__private::serde::Deserialize<'de>>::deserialize(deserializer)
+                    .map(Self)
+            }
+        }
+
+        $crate::__token_newtype_candid!($name, $inner);
+        $crate::__token_newtype_storable!($name, $inner);
+    };
+
+    (@op $name:ident, $tr:ident, $f:ident, $tr_assign:ident, $f_assign:ident) => {
+        impl ::core::ops::$tr for &$name {
+            type Output = $name;
+
+            fn $f(self, rhs: Self) -> Self::Output {
+                $name(::core::ops::$tr::$f(&self.0, &rhs.0))
+            }
+        }
+
+        impl ::core::ops::$tr for $name {
+            type Output = $name;
+
+            fn $f(self, rhs: Self) -> Self::Output {
+                ::core::ops::$tr::$f(&self, &rhs)
+            }
+        }
+
+        impl ::core::ops::$tr<&$name> for $name {
+            type Output = $name;
+
+            fn $f(self, rhs: &$name) -> Self::Output {
+                ::core::ops::$tr::$f(&self, rhs)
+            }
+        }
+
+        impl ::core::ops::$tr<$name> for &$name {
+            type Output = $name;
+
+            fn $f(self, rhs: $name) -> Self::Output {
+                ::core::ops::$tr::$f(self, &rhs)
+            }
+        }
+
+        impl ::core::ops::$tr_assign<&$name> for $name {
+            fn $f_assign(&mut self, rhs: &$name) {
+                ::core::ops::$tr_assign::$f_assign(&mut self.0, &rhs.0)
+            }
+        }
+
+        impl ::core::ops::$tr_assign for $name {
+            fn $f_assign(&mut self, rhs: Self) {
+                ::core::ops::$tr_assign::$f_assign(self, &rhs)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "candid")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __token_newtype_candid {
+    ($name:ident, $inner:ty) => {
+        impl $crate::__private::candid::CandidType for $name {
+            fn _ty() -> $crate::__private::candid::types::Type {
+                <$inner as $crate::__private::candid::CandidType>::_ty()
+            }
+
+            fn idl_serialize<S>(&self, serializer: S) -> ::core::result::Result<(), S::Error>
+            where
+                S: $crate::__private::candid::types::Serializer,
+            {
+                $crate::__private::candid::CandidType::idl_serialize(&self.0, serializer)
+            }
+        }
+    };
+}
+
+/// Without Candid the newtype is serialized by serde exactly as the wrapped value
+#[cfg(not(feature = "candid"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __token_newtype_candid {
+    ($name:ident, $inner:ty) => {
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                $crate::__private::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "stable-structures")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __token_newtype_storable {
+    ($name:ident, $inner:ty) => {
+        impl $crate::__private::ic_stable_structures::Storable for $name {
+            fn to_bytes(&self) -> $crate::__private::Cow<'_, [u8]> {
+                $crate::__private::ic_stable_structures::Storable::to_bytes(&self.0)
+            }
+
+            fn from_bytes(bytes: $crate::__private::Cow<[u8]>) -> Self {
+                Self(
+                    <$inner as $crate::__private::ic_stable_structures::Storable>::from_bytes(
+                        bytes,
+                    ),
+                )
+            }
+
+            const BOUND: $crate::__private::ic_stable_structures::storable::Bound =
+                <$inner as $crate::__private::ic_stable_structures::Storable>::BOUND;
+        }
+    };
+}
+
+#[cfg(not(feature = "stable-structures"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __token_newtype_storable {
+    ($name:ident, $inner:ty) => {};
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use core::str::FromStr;
+
+    use crate::c::E8s;
+
+    crate::token_newtype! {
+        /// ICP amount
+        pub struct Icp(E8s);
+    }
+
+    #[test]
+    fn delegates_to_the_wrapped_value() {
+        let a = Icp::from(150_000_000u64);
+        let b = Icp::new(E8s::new(50_000_000u64));
+
+        assert_eq!(&a + &b, Icp::from(200_000_000u64));
+        assert_eq!(a.clone() - &b, Icp::from(100_000_000u64));
+        assert_eq!(&a * b.clone(), Icp::from(75_000_000u64));
+        assert_eq!(a.clone() / b.clone(), Icp::from(300_000_000u128));
+
+        let mut c = Icp::zero();
+        c += &a;
+        c *= Icp::one();
+        c -= b;
+        assert_eq!(c.into_inner(), E8s::new(100_000_000u64));
+
+        assert!(Icp::one() > Icp::zero());
+        assert_eq!(a.to_string(), "1.50000000");
+        assert_eq!(Icp::from_str("1.5"), Ok(a.clone()));
+        assert!(Icp::from_str("1.5.0").is_err());
+        assert_eq!(E8s::from(a), E8s::new(150_000_000u64));
+    }
+
+    #[cfg(feature = "candid")]
+    #[test]
+    fn encodes_as_the_wrapped_value() {
+        use candid::{decode_one, encode_one};
+
+        let a = Icp::from(150_000_000u64);
+        let bytes = encode_one(&a).unwrap();
+
+        assert_eq!(bytes, encode_one(&a.0).unwrap());
+        assert_eq!(decode_one::<Icp>(&bytes).unwrap(), a);
+    }
+
+    #[cfg(not(feature = "candid"))]
+    #[test]
+    fn serializes_as_the_wrapped_value() {
+        let a = Icp::from(150_000_000u64);
+        let json = serde_json::to_string(&a).unwrap();
+
+        assert_eq!(json, serde_json::to_string(&a.0).unwrap());
+        assert_eq!(serde_json::from_str::<Icp>(&json).unwrap(), a);
+    }
+
+    #[cfg(feature = "stable-structures")]
+    #[test]
+    fn stores_as_the_wrapped_value() {
+        use ic_stable_structures::Storable;
+
+        let a = Icp::from(u128::MAX);
+
+        assert_eq!(a.to_bytes(), a.0.to_bytes());
+        assert_eq!(Icp::from_bytes(a.to_bytes()), a);
+    }
+}
@@ -0,0 +1,248 @@
+//! Time-weighted price smoothing. Timestamps are in arbitrary, but consistent units
+//! (e.g. seconds or nanos) and can't go back.
+
+use alloc::{format, string::String};
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, uint::Uint};
+
+/// Cumulative `price * time` accumulator (Uniswap V2 style). Every price holds
+/// until the next observation.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct TwapAccumulator<const D: usize> {
+    cumulative: Uint,
+    last: Option<(ECs<D>, u64)>,
+    first_timestamp: u64,
+}
+
+impl<const D: usize> TwapAccumulator<D> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails, if the timestamp is before the previous one
+    pub fn observe(&mut self, price: ECs<D>, timestamp: u64) -> Result<(), String> {
+        match &self.last {
+            None => self.first_timestamp = timestamp,
+            Some((last_price, last_timestamp)) => {
+                if timestamp < *last_timestamp {
+                    return Err(format!(
+                        "Observation at {} is before the previous one at {}",
+                        timestamp, last_timestamp
+                    ));
+                }
+
+                self.cumulative += &last_price.val * (timestamp - last_timestamp);
+            }
+        }
+
+        self.last = Some((price, timestamp));
+
+        Ok(())
+    }
+
+    /// Sum of `price * duration` of every observation so far, in raw units
+    pub fn cumulative(&self) -> &Uint {
+        &self.cumulative
+    }
+
+    /// Time-weighted average price between the first and the last observations, rounded down.
+    /// `None`, if no time has passed yet.
+    pub fn current(&self) -> Option<ECs<D>> {
+        let (_, last_timestamp) = self.last.as_ref()?;
+        let elapsed = last_timestamp - self.first_timestamp;
+
+        if elapsed == 0 {
+            return None;
+        }
+
+        Some(ECs::new(&self.cumulative / elapsed))
+    }
+}
+
+/// Time-weighted exponential moving average: a new observation moves the average towards
+/// the price by `1 - e^(-dt / time_constant)`, so irregular observation intervals are
+/// handled correctly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ema<const D: usize> {
+    time_constant: u64,
+    last: Option<(ECs<D>, u64)>,
+}
+
+impl<const D: usize> Ema<D> {
+    /// Traps, if the time constant is zero
+    pub fn new(time_constant: u64) -> Self {
+        if time_constant == 0 {
+            unreachable!("EMA time constant can't be zero");
+        }
+
+        Self {
+            time_constant,
+            last: None,
+        }
+    }
+
+    /// Fails, if the timestamp is before the previous one
+    pub fn observe(&mut self, price: ECs<D>, timestamp: u64) -> Result<(), String> {
+        let (ema, last_timestamp) = match &self.last {
+            None => {
+                self.last = Some((price, timestamp));
+                return Ok(());
+            }
+            Some(last) => last,
+        };
+
+        if timestamp < *last_timestamp {
+            return Err(format!(
+                "Observation at {} is before the previous one at {}",
+                timestamp, last_timestamp
+            ));
+        }
+
+        let elapsed = timestamp - last_timestamp;
+
+        // past D * ln(10) time constants e^(-dt / tau) is below a unit of the last decimal
+        // point, so the weight is 1 - the average jumps to the price, without evaluating exp,
+        // which would only get more expensive (and trap past MAX_EXP_ARG)
+        let cutoff = (D as u64 * 2_303).div_ceil(1_000) + 1;
+        if elapsed / self.time_constant >= cutoff {
+            self.last = Some((price, timestamp));
+            return Ok(());
+        }
+
+        // weight = 1 - e^(-dt / tau)
+        let dt = ECs::<D>::new(
+            BigUint::from(elapsed) * ECs::<D>::base() / BigUint::from(self.time_constant),
+        );
+        let exp = dt.exp();
+        let weight = (&exp - ECs::<D>::one()) / exp;
+
+        let new_ema = if price >= *ema {
+            ema + (&price - ema) * weight
+        } else {
+            ema - (ema - &price) * weight
+        };
+
+        self.last = Some((new_ema, timestamp));
+
+        Ok(())
+    }
+
+    /// `None` before the first observation
+    pub fn current(&self) -> Option<&ECs<D>> {
+        self.last.as_ref().map(|(ema, _)| ema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    #[test]
+    fn twap_weights_prices_by_time() {
+        let mut twap = TwapAccumulator::<8>::new();
+        assert_eq!(twap.current(), None);
+
+        twap.observe(E8s::from_whole(100), 1_000).unwrap();
+        assert_eq!(twap.current(), None);
+
+        // 100 holds for 30, 200 for 10, the last price doesn't count until the next observation
+        twap.observe(E8s::from_whole(200), 1_030).unwrap();
+        twap.observe(E8s::from_whole(1_000), 1_040).unwrap();
+
+        assert_eq!(twap.current(), Some(E8s::from_whole(125)));
+        assert_eq!(twap.cumulative(), &(E8s::from_whole(5_000).val));
+
+        // a repeated timestamp only replaces the price
+        twap.observe(E8s::from_whole(0), 1_040).unwrap();
+        twap.observe(E8s::from_whole(0), 1_060).unwrap();
+        assert_eq!(
+            twap.current(),
+            Some(E8s::from_whole(5_000) / E8s::from_whole(60))
+        );
+    }
+
+    #[test]
+    fn twap_rounds_down() {
+        let mut twap = TwapAccumulator::<0>::new();
+        twap.observe(ECs::from(1u64), 0).unwrap();
+        twap.observe(ECs::from(2u64), 2).unwrap();
+        twap.observe(ECs::from(0u64), 3).unwrap();
+
+        // (1 * 2 + 2 * 1) / 3
+        assert_eq!(twap.current(), Some(ECs::from(1u64)));
+    }
+
+    #[test]
+    fn observations_cant_go_back() {
+        let mut twap = TwapAccumulator::<8>::new();
+        twap.observe(E8s::from_whole(1), 10).unwrap();
+        assert!(twap.observe(E8s::from_whole(2), 9).is_err());
+        // the failed observation is ignored
+        twap.observe(E8s::from_whole(3), 20).unwrap();
+        assert_eq!(twap.current(), Some(E8s::from_whole(1)));
+
+        let mut ema = Ema::<8>::new(10);
+        ema.observe(E8s::from_whole(1), 10).unwrap();
+        assert!(ema.observe(E8s::from_whole(2), 9).is_err());
+        assert_eq!(ema.current(), Some(&E8s::from_whole(1)));
+    }
+
+    #[test]
+    fn ema_moves_down_and_ignores_zero_gaps() {
+        let mut ema = Ema::<8>::new(10);
+        assert_eq!(ema.current(), None);
+
+        ema.observe(E8s::from_whole(200), 0).unwrap();
+        ema.observe(E8s::from_whole(100), 0).unwrap();
+        assert_eq!(ema.current(), Some(&E8s::from_whole(200)));
+
+        // 200 - 100 * 0.63212055
+        ema.observe(E8s::from_whole(100), 10).unwrap();
+        assert_eq!(ema.current().unwrap().to_string(), "136.78794500");
+    }
+
+    #[test]
+    #[should_panic(expected = "EMA time constant can't be zero")]
+    fn ema_zero_time_constant_traps() {
+        Ema::<8>::new(0);
+    }
+
+    #[test]
+    fn ema_moves_towards_the_price() {
+        let mut ema = Ema::<8>::new(10);
+        ema.observe(E8s::from_whole(100), 0).unwrap();
+        ema.observe(E8s::from_whole(200), 10).unwrap();
+
+        // 100 + 100 * (1 - e^-1), with the weight rounded down to 0.63212055
+        assert_eq!(ema.current().unwrap().to_string(), "163.21205500");
+    }
+
+    #[test]
+    fn ema_after_a_long_gap_is_the_price() {
+        // tau = 1s and a 12-day gap, way above the exp() cap
+        let mut ema = Ema::<8>::new(1);
+        ema.observe(E8s::from_whole(100), 0).unwrap();
+        ema.observe(E8s::from_whole(50), 12 * 24 * 60 * 60).unwrap();
+
+        assert_eq!(ema.current(), Some(&E8s::from_whole(50)));
+
+        ema.observe(E8s::from_whole(70), u64::MAX).unwrap();
+
+        assert_eq!(ema.current(), Some(&E8s::from_whole(70)));
+    }
+
+    #[test]
+    fn ema_just_below_the_cutoff() {
+        // 18 time constants at 8 decimal points: e^-18 is still above 10^-8
+        let mut ema = Ema::<8>::new(1_000);
+        ema.observe(E8s::from_whole(100), 0).unwrap();
+        ema.observe(E8s::from_whole(200), 18_000).unwrap();
+
+        let current = ema.current().unwrap();
+        assert!(current < &E8s::from_whole(200));
+        assert!(current > &E8s::from_whole(199));
+    }
+}
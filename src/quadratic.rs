@@ -0,0 +1,54 @@
+//! Quadratic voting and quadratic funding (CLR) math. Square roots are taken at full scale
+//! (see `ECs::sqrt_precise`) and rounded down, so nobody gets more votes or matching than the
+//! exact math would give.
+
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+
+use crate::{allocation::allocate_weighted, c::ECs};
+
+/// Votes bought with the credits: `sqrt(credits)`
+pub fn votes<const D: usize>(credits: &ECs<D>) -> ECs<D> {
+    credits.sqrt_precise()
+}
+
+/// Total votes of all the voters, each voter getting `sqrt(credits)`
+pub fn tally<'a, const D: usize>(credits: impl IntoIterator<Item = &'a ECs<D>>) -> ECs<D> {
+    credits.into_iter().map(votes).sum()
+}
+
+/// Ideal CLR match of a single project: `(Σ sqrt(c))² - Σ c`, i.e. what the project would get
+/// with an unlimited matching pool
+pub fn ideal_match<const D: usize>(contributions: &[ECs<D>]) -> ECs<D> {
+    let sum_sqrt = tally(contributions);
+    let sum: BigUint = contributions.iter().map(|it| &it.val).sum();
+
+    let squared = &sum_sqrt * &sum_sqrt;
+
+    // the rounded down square may be below the sum, e.g. with a single contributor
+    if squared.val > sum {
+        ECs::new(squared.val - sum)
+    } else {
+        ECs::zero()
+    }
+}
+
+/// CLR matching of every project (a slice of its contributions) from the pool. Projects get
+/// their ideal matches, if the pool covers them all. Otherwise the whole pool is split
+/// proportionally to the ideal matches (see `allocate_weighted`), so matches sum up exactly
+/// to the pool.
+pub fn quadratic_match<const D: usize, C: AsRef<[ECs<D>]>>(
+    pool: &ECs<D>,
+    projects: &[C],
+) -> Vec<ECs<D>> {
+    let ideal: Vec<ECs<D>> = projects.iter().map(|it| ideal_match(it.as_ref())).collect();
+    let total: ECs<D> = ideal.iter().sum();
+
+    if &total <= pool {
+        return ideal;
+    }
+
+    // can't be `None` - the total ideal match is above the pool, so it's not zero
+    allocate_weighted(pool, &ideal).unwrap()
+}
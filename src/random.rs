@@ -0,0 +1,153 @@
+//! Uniformly distributed random amounts: `ECs<D>` and `EDs` implement `rand`'s `SampleUniform`,
+//! so `rng.gen_range(lo..hi)` and `rng.gen_range(lo..=hi)` work with them directly. Any `Rng`
+//! works, e.g. a `ChaCha20Rng` seeded with the output of the management canister's `raw_rand`.
+
+use alloc::{vec, vec::Vec};
+
+use num_bigint::BigUint;
+use rand::{
+    distributions::uniform::{SampleBorrow, SampleUniform, UniformSampler},
+    Rng,
+};
+
+use crate::{c::ECs, d::EDs};
+
+/// Uniform in `0..n` by rejection sampling: random `n.bits()`-bit values are drawn until one is
+/// below `n`, which takes less than 2 draws on average and, unlike `% n`, has no bias
+fn gen_below<R: Rng + ?Sized>(rng: &mut R, n: &BigUint) -> BigUint {
+    let bits = n.bits();
+    let mut digits: Vec<u32> = vec![0; bits.div_ceil(32) as usize];
+    let extra = digits.len() as u64 * 32 - bits;
+
+    loop {
+        for digit in digits.iter_mut() {
+            *digit = rng.next_u32();
+        }
+        if let Some(last) = digits.last_mut() {
+            *last >>= extra;
+        }
+
+        let val = BigUint::from_slice(&digits);
+        if &val < n {
+            return val;
+        }
+    }
+}
+
+/// `low + uniform(0..span)` over raw values
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct UniformRaw {
+    low: BigUint,
+    span: BigUint,
+}
+
+impl UniformRaw {
+    fn new(low: &BigUint, high: &BigUint, inclusive: bool) -> Self {
+        let empty = if inclusive { low > high } else { low >= high };
+        if empty {
+            unreachable!("Can't sample from an empty range");
+        }
+
+        let mut span = high - low;
+        if inclusive {
+            span += 1u32;
+        }
+
+        Self {
+            low: low.clone(),
+            span,
+        }
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigUint {
+        &self.low + gen_below(rng, &self.span)
+    }
+}
+
+/// `UniformSampler` of `ECs<D>`, see `SampleUniform`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformECs<const D: usize>(UniformRaw);
+
+impl<const D: usize> UniformSampler for UniformECs<D> {
+    type X = ECs<D>;
+
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Self(UniformRaw::new(
+            &low.borrow().val.big(),
+            &high.borrow().val.big(),
+            false,
+        ))
+    }
+
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Self(UniformRaw::new(
+            &low.borrow().val.big(),
+            &high.borrow().val.big(),
+            true,
+        ))
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::X {
+        ECs::new(self.0.sample(rng))
+    }
+}
+
+impl<const D: usize> SampleUniform for ECs<D> {
+    type Sampler = UniformECs<D>;
+}
+
+/// `UniformSampler` of `EDs`, see `SampleUniform`. Traps, if the bounds have different decimal points.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UniformEDs {
+    raw: UniformRaw,
+    decimals: u8,
+}
+
+impl UniformEDs {
+    fn new_eds(low: &EDs, high: &EDs, inclusive: bool) -> Self {
+        if low.decimals != high.decimals {
+            unreachable!("Incompatible decimal points");
+        }
+
+        Self {
+            raw: UniformRaw::new(&low.val.big(), &high.val.big(), inclusive),
+            decimals: low.decimals,
+        }
+    }
+}
+
+impl UniformSampler for UniformEDs {
+    type X = EDs;
+
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Self::new_eds(low.borrow(), high.borrow(), false)
+    }
+
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        Self::new_eds(low.borrow(), high.borrow(), true)
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::X {
+        EDs::new(self.raw.sample(rng), self.decimals)
+    }
+}
+
+impl SampleUniform for EDs {
+    type Sampler = UniformEDs;
+}
@@ -0,0 +1,138 @@
+use core::{fmt::Display, marker::PhantomData};
+
+use crate::{
+    amount::{Amount, TokenTag},
+    c::ECs,
+    rescale,
+};
+
+/// Price of one `FROM` token in `TO` tokens, with `D` decimal points of precision
+pub struct Rate<FROM: TokenTag, TO: TokenTag, const D: usize>(
+    pub ECs<D>,
+    PhantomData<fn() -> (FROM, TO)>,
+);
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> Rate<FROM, TO, D> {
+    pub fn new(value: ECs<D>) -> Self {
+        Self(value, PhantomData)
+    }
+
+    /// Converts an amount of `FROM` with any decimals into an amount of `TO` with any decimals,
+    /// rounding down only once
+    pub fn convert<const DF: usize, const DT: usize>(
+        &self,
+        amount: &Amount<FROM, DF>,
+    ) -> Amount<TO, DT> {
+        Amount::new(ECs::new(rescale(
+            (&amount.0.val * &self.0.val).into_big(),
+            DF + D,
+            DT,
+        )))
+    }
+
+    /// Price of one `TO` token in `FROM` tokens
+    pub fn invert(&self) -> Rate<TO, FROM, D> {
+        Rate::new(&ECs::one() / &self.0)
+    }
+
+    /// Chains `FROM -> TO` and `TO -> X` into `FROM -> X`
+    pub fn compose<X: TokenTag>(&self, other: &Rate<TO, X, D>) -> Rate<FROM, X, D> {
+        Rate::new(&self.0 * &other.0)
+    }
+}
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> Clone for Rate<FROM, TO, D> {
+    fn clone(&self) -> Self {
+        Self::new(self.0.clone())
+    }
+}
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> core::fmt::Debug for Rate<FROM, TO, D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Rate")
+            .field(&FROM::SYMBOL)
+            .field(&TO::SYMBOL)
+            .field(&self.0)
+            .finish()
+    }
+}
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> Display for Rate<FROM, TO, D> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} {}/{}", self.0, TO::SYMBOL, FROM::SYMBOL)
+    }
+}
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> PartialEq for Rate<FROM, TO, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<FROM: TokenTag, TO: TokenTag, const D: usize> Eq for Rate<FROM, TO, D> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::c::{E6s, E8s};
+
+    struct Icp;
+    struct Usdc;
+    struct Btc;
+
+    impl TokenTag for Icp {
+        const SYMBOL: &'static str = "ICP";
+    }
+
+    impl TokenTag for Usdc {
+        const SYMBOL: &'static str = "USDC";
+    }
+
+    impl TokenTag for Btc {
+        const SYMBOL: &'static str = "BTC";
+    }
+
+    fn icp_usdc() -> Rate<Icp, Usdc, 8> {
+        Rate::new(E8s::new(1_234_000_000u64))
+    }
+
+    #[test]
+    fn converts_between_decimals() {
+        let rate = icp_usdc();
+        let icp = Amount::<Icp, 8>::new(E8s::new(150_000_000u64));
+
+        let usdc: Amount<Usdc, 6> = rate.convert(&icp);
+        assert_eq!(usdc.0, E6s::new(18_510_000u64));
+
+        // more decimals on the other side lose nothing
+        let usdc: Amount<Usdc, 18> = icp.convert(&rate);
+        assert_eq!(usdc.0, ECs::<18>::new(18_510_000_000_000_000_000u128));
+
+        // a single e8s is worth less than a unit of USDC
+        let dust: Amount<Usdc, 6> = rate.convert(&Amount::<Icp, 8>::new(E8s::new(1u64)));
+        assert_eq!(dust.0, E6s::zero());
+    }
+
+    #[test]
+    fn invert_and_compose() {
+        let rate = icp_usdc();
+
+        // 1 / 12.34, rounded down
+        assert_eq!(rate.invert().0, E8s::new(8_103_727u64));
+
+        let usdc_btc = Rate::<Usdc, Btc, 8>::new(E8s::new(1_000u64));
+        let icp_btc = rate.compose(&usdc_btc);
+        assert_eq!(icp_btc.0, E8s::new(12_340u64));
+
+        let btc: Amount<Btc, 8> = icp_btc.convert(&Amount::<Icp, 8>::new(E8s::from_whole(100)));
+        assert_eq!(btc.0, E8s::new(1_234_000u64));
+    }
+
+    #[test]
+    fn formats_as_a_price() {
+        assert_eq!(icp_usdc().to_string(), "12.34000000 USDC/ICP");
+        assert_eq!(icp_usdc(), icp_usdc().clone());
+    }
+}
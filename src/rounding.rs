@@ -0,0 +1,41 @@
+use num_bigint::BigUint;
+use num_integer::Integer;
+
+/// How to round results of operations that drop precision
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum RoundingMode {
+    /// Towards zero (truncation), what the arithmetic operators do
+    #[default]
+    Down,
+    /// Away from zero
+    Up,
+    /// To the nearest, ties away from zero
+    HalfUp,
+    /// To the nearest, ties to the even neighbour (banker's rounding)
+    HalfEven,
+}
+
+/// Divides `n` by `d`, rounding the quotient according to the mode
+pub fn div_rounded(n: &BigUint, d: &BigUint, mode: RoundingMode) -> BigUint {
+    let (q, r) = n.div_rem(d);
+
+    if r == BigUint::ZERO {
+        return q;
+    }
+
+    let round_up = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::HalfUp => &r * 2u32 >= *d,
+        RoundingMode::HalfEven => {
+            let r2 = &r * 2u32;
+            r2 > *d || (r2 == *d && q.bit(0))
+        }
+    };
+
+    if round_up {
+        q + 1u32
+    } else {
+        q
+    }
+}
@@ -0,0 +1,56 @@
+//! Serializes a value as a raw-units `candid::Nat`, e.g. `150000000` for `1.5` E8s.
+//! Only for `ECs<D>`: raw units don't carry decimal points, so an `EDs` couldn't be read back.
+//! Use `serde_string` for `EDs`.
+//!
+//! ```ignore
+//! #[derive(CandidType, Serialize, Deserialize)]
+//! struct Transfer {
+//!     #[serde(with = "ic_e8s::serde_nat")]
+//!     amount: E8s,
+//! }
+//! ```
+
+use candid::Nat;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{c::ECs, with_nat};
+
+pub fn serialize<const D: usize, S>(value: &ECs<D>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    with_nat(&value.val, |nat| nat.serialize(serializer))
+}
+
+pub fn deserialize<'de, const D: usize, De>(deserializer: De) -> Result<ECs<D>, De::Error>
+where
+    De: Deserializer<'de>,
+{
+    Ok(ECs::new(Nat::deserialize(deserializer)?.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::c::E8s;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Transfer {
+        #[serde(with = "super")]
+        amount: E8s,
+    }
+
+    #[test]
+    fn round_trips_raw_units() {
+        for amount in [
+            E8s::new(150_000_000u64),
+            E8s::new(num_bigint::BigUint::from(u128::MAX) * 1000u32),
+        ] {
+            let transfer = Transfer { amount };
+
+            let json = serde_json::to_string(&transfer).unwrap();
+            assert_eq!(serde_json::from_str::<Transfer>(&json).unwrap(), transfer);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Serde representation of raw units without `candid::Nat`/`candid::Int`: a decimal string,
+//! e.g. `"150000000"` for `1.5` E8s, so big values survive formats like JSON. Integers are
+//! accepted too, when deserializing.
+
+use core::fmt;
+
+use num_bigint::{BigInt, BigUint};
+use serde::{
+    de::{Error, Visitor},
+    Deserializer, Serialize, Serializer,
+};
+
+use crate::uint::Uint;
+
+/// Serializes the wrapped raw value as a decimal string
+pub(crate) struct Raw<'a>(pub &'a Uint);
+
+impl Serialize for Raw<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+/// Serializes the wrapped signed raw value as a decimal string
+pub(crate) struct RawSigned<'a>(pub &'a BigInt);
+
+impl Serialize for RawSigned<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self.0)
+    }
+}
+
+struct RawVisitor;
+
+impl Visitor<'_> for RawVisitor {
+    type Value = BigUint;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a non-negative integer or a string of digits")
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(BigUint::from(v))
+    }
+
+    fn visit_u128<E: Error>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(BigUint::from(v))
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        u64::try_from(v)
+            .map(BigUint::from)
+            .map_err(|_| E::custom("negative raw value"))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        if v.is_empty() || !v.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(E::invalid_value(serde::de::Unexpected::Str(v), &self));
+        }
+
+        // can't fail - only ascii digits
+        Ok(BigUint::parse_bytes(v.as_bytes(), 10).unwrap())
+    }
+}
+
+struct RawSignedVisitor;
+
+impl Visitor<'_> for RawSignedVisitor {
+    type Value = BigInt;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an integer or a string of digits with an optional '-'")
+    }
+
+    fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(BigInt::from(v))
+    }
+
+    fn visit_u128<E: Error>(self, v: u128) -> Result<Self::Value, E> {
+        Ok(BigInt::from(v))
+    }
+
+    fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(BigInt::from(v))
+    }
+
+    fn visit_i128<E: Error>(self, v: i128) -> Result<Self::Value, E> {
+        Ok(BigInt::from(v))
+    }
+
+    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+        let digits = v.strip_prefix('-').unwrap_or(v);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(E::invalid_value(serde::de::Unexpected::Str(v), &self));
+        }
+
+        // can't fail - only ascii digits and a sign
+        Ok(BigInt::parse_bytes(v.as_bytes(), 10).unwrap())
+    }
+}
+
+pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<BigUint, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(RawVisitor)
+}
+
+pub(crate) fn deserialize_signed<'de, D>(deserializer: D) -> Result<BigInt, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(RawSignedVisitor)
+}
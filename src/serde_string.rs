@@ -0,0 +1,94 @@
+//! Serializes a value as a decimal string with all of its decimal points, e.g. `"1.50000000"`
+//! for `1.5` E8s, which is parsed back exactly. Works with both `ECs<D>` and `EDs`, since the
+//! string carries the decimal points.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Order {
+//!     #[serde(with = "ic_e8s::serde_string")]
+//!     price: E8s,
+//! }
+//! ```
+
+use alloc::string::String;
+use core::str::FromStr;
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+use crate::fixed::FixedDecimal;
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: FixedDecimal,
+    S: Serializer,
+{
+    serializer.collect_str(value)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr<Err = String>,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+
+    T::from_str(&s).map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        c::{E8s, ECs},
+        d::EDs,
+    };
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Order {
+        #[serde(with = "super")]
+        price: E8s,
+        #[serde(with = "super")]
+        amount: EDs,
+        #[serde(with = "super")]
+        count: ECs<0>,
+    }
+
+    #[test]
+    fn round_trips_with_all_decimal_points() {
+        let order = Order {
+            price: E8s::new(105_000_000u64),
+            amount: EDs::new(5u64, 3),
+            count: ECs::new(5u64),
+        };
+
+        let json = serde_json::to_string(&order).unwrap();
+        assert_eq!(
+            json,
+            r#"{"price":"1.05000000","amount":"0.005","count":"5"}"#
+        );
+        assert_eq!(serde_json::from_str::<Order>(&json).unwrap(), order);
+    }
+
+    #[test]
+    fn round_trips_above_u128() {
+        let order = Order {
+            price: E8s::new(num_bigint::BigUint::from(u128::MAX) * 1000u32 + 7u32),
+            amount: EDs::new(num_bigint::BigUint::from(u128::MAX) * 3u32, 40),
+            count: ECs::new(u128::MAX),
+        };
+
+        let json = serde_json::to_string(&order).unwrap();
+        let back = serde_json::from_str::<Order>(&json).unwrap();
+
+        assert_eq!(back, order);
+        assert_eq!(back.amount.decimals, 40);
+    }
+
+    #[test]
+    fn rejects_malformed_strings() {
+        let json = r#"{"price":"1.0.5","amount":"0.005","count":"5"}"#;
+
+        assert!(serde_json::from_str::<Order>(json).is_err());
+    }
+}
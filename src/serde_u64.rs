@@ -0,0 +1,75 @@
+//! Serializes a value as raw units packed into `u64`, e.g. `150000000` for `1.5` E8s.
+//! Serialization fails for values that don't fit into `u64`.
+//! Only for `ECs<D>`: raw units don't carry decimal points, so an `EDs` couldn't be read back.
+//! Use `serde_string` for `EDs`.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Transfer {
+//!     #[serde(with = "ic_e8s::serde_u64")]
+//!     amount: E8s,
+//! }
+//! ```
+
+use alloc::format;
+
+use serde::{ser::Error, Deserialize, Deserializer, Serializer};
+
+use crate::c::ECs;
+
+pub fn serialize<const D: usize, S>(value: &ECs<D>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let raw = u64::try_from(&value.val)
+        .map_err(|_| S::Error::custom(format!("{} doesn't fit into u64", value)))?;
+
+    serializer.serialize_u64(raw)
+}
+
+pub fn deserialize<'de, const D: usize, De>(deserializer: De) -> Result<ECs<D>, De::Error>
+where
+    De: Deserializer<'de>,
+{
+    Ok(ECs::from(u64::deserialize(deserializer)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::c::E8s;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Transfer {
+        #[serde(with = "super")]
+        amount: E8s,
+    }
+
+    #[test]
+    fn round_trips_raw_units() {
+        let transfer = Transfer {
+            amount: E8s::new(150_000_000u64),
+        };
+
+        let json = serde_json::to_string(&transfer).unwrap();
+        assert_eq!(json, r#"{"amount":150000000}"#);
+        assert_eq!(serde_json::from_str::<Transfer>(&json).unwrap(), transfer);
+
+        let max = Transfer {
+            amount: E8s::new(u64::MAX),
+        };
+        let json = serde_json::to_string(&max).unwrap();
+        assert_eq!(serde_json::from_str::<Transfer>(&json).unwrap(), max);
+    }
+
+    #[test]
+    fn fails_above_u64() {
+        let transfer = Transfer {
+            amount: E8s::new(u64::MAX as u128 + 1),
+        };
+
+        assert!(serde_json::to_string(&transfer).is_err());
+        assert!(serde_json::from_str::<Transfer>(r#"{"amount":18446744073709551616}"#).is_err());
+    }
+}
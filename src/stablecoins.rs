@@ -0,0 +1,22 @@
+use crate::{
+    c::{E6s, E8s},
+    rounding::RoundingMode,
+};
+
+/// ckUSDC/ckUSDT-style assets use 6 decimal points
+impl E6s {
+    /// Exact, since no precision is dropped
+    pub fn to_e8s(&self) -> E8s {
+        self.clone().to_decimals::<8>()
+    }
+
+    pub fn from_e8s(value: &E8s, mode: RoundingMode) -> Self {
+        value.to_e6s(mode)
+    }
+}
+
+impl E8s {
+    pub fn to_e6s(&self, mode: RoundingMode) -> E6s {
+        self.clone().to_decimals_rounded::<6>(mode)
+    }
+}
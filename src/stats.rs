@@ -0,0 +1,412 @@
+//! Aggregates over slices of amounts in fixed point. Results that don't fit the scale are
+//! rounded down. Functions return `None` for empty inputs.
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num_bigint::BigUint;
+
+use crate::{
+    bps::{Bps, BPS_DENOMINATOR},
+    c::ECs,
+    d::EDs,
+    uint::Uint,
+};
+
+fn sorted<const D: usize>(values: &[ECs<D>]) -> Vec<&ECs<D>> {
+    let mut refs: Vec<&ECs<D>> = values.iter().collect();
+    refs.sort_unstable();
+
+    refs
+}
+
+fn median_raw(mut values: Vec<&Uint>) -> Option<Uint> {
+    if values.is_empty() {
+        return None;
+    }
+
+    let len = values.len();
+    let (lower, upper, _) = values.select_nth_unstable(len / 2);
+    let upper = (*upper).clone();
+
+    if len % 2 == 1 {
+        return Some(upper);
+    }
+
+    let lower = lower.iter().max().unwrap();
+
+    Some((*lower + upper) / 2u32)
+}
+
+/// Count, sum and sum of squares
+fn moments_raw<'a>(values: impl Iterator<Item = &'a Uint>) -> (Uint, Uint, Uint) {
+    let mut n = Uint::ZERO;
+    let mut sum = Uint::ZERO;
+    let mut sum_sq = Uint::ZERO;
+
+    for it in values {
+        n += 1u32;
+        sum += it;
+        sum_sq += it * it;
+    }
+
+    (n, sum, sum_sq)
+}
+
+fn mean_raw<'a>(values: impl Iterator<Item = &'a Uint>) -> Option<Uint> {
+    let (n, sum, _) = moments_raw(values);
+    if n.is_zero() {
+        return None;
+    }
+
+    Some(sum / n)
+}
+
+/// n² times the variance of raw values: nΣx² - (Σx)²
+fn scaled_variance_raw<'a>(values: impl Iterator<Item = &'a Uint>) -> Option<(Uint, Uint)> {
+    let (n, sum, sum_sq) = moments_raw(values);
+    if n.is_zero() {
+        return None;
+    }
+
+    Some((&n * sum_sq - &sum * &sum, n))
+}
+
+fn variance_raw<'a>(values: impl Iterator<Item = &'a Uint>, base: &BigUint) -> Option<Uint> {
+    let (var, n) = scaled_variance_raw(values)?;
+
+    Some((var / (&n * &n * base)).into())
+}
+
+fn stddev_raw<'a>(values: impl Iterator<Item = &'a Uint>) -> Option<Uint> {
+    let (var, n) = scaled_variance_raw(values)?;
+
+    // floor(floor(sqrt(v)) / n) == floor(sqrt(v) / n)
+    Some(var.sqrt() / n)
+}
+
+/// Converts all values to the highest of their decimal points
+fn aligned(values: &[EDs]) -> (Vec<Uint>, u8) {
+    let decimals = values
+        .iter()
+        .map(|it| it.decimals)
+        .max()
+        .unwrap_or_default();
+    let vals = values
+        .iter()
+        .map(|it| it.clone().to_decimals(decimals).val)
+        .collect();
+
+    (vals, decimals)
+}
+
+pub fn mean<const D: usize>(values: &[ECs<D>]) -> Option<ECs<D>> {
+    mean_raw(values.iter().map(|it| &it.val)).map(ECs::new)
+}
+
+/// Middle value, or the mean of the two middle values for even lengths
+pub fn median<const D: usize>(values: &[ECs<D>]) -> Option<ECs<D>> {
+    median_raw(values.iter().map(|it| &it.val).collect()).map(ECs::new)
+}
+
+/// Population variance (divided by the number of values)
+pub fn variance<const D: usize>(values: &[ECs<D>]) -> Option<ECs<D>> {
+    variance_raw(values.iter().map(|it| &it.val), ECs::<D>::base()).map(ECs::new)
+}
+
+/// Population standard deviation, the square root of [`variance`] computed without
+/// truncating the variance first
+pub fn stddev<const D: usize>(values: &[ECs<D>]) -> Option<ECs<D>> {
+    stddev_raw(values.iter().map(|it| &it.val)).map(ECs::new)
+}
+
+/// Like [`mean`], but aligns decimal points. The result has the highest of them.
+pub fn mean_aligned(values: &[EDs]) -> Option<EDs> {
+    let (vals, decimals) = aligned(values);
+
+    mean_raw(vals.iter()).map(|it| EDs::new(it, decimals))
+}
+
+/// Like [`median`], but aligns decimal points. The result has the highest of them.
+pub fn median_aligned(values: &[EDs]) -> Option<EDs> {
+    let (vals, decimals) = aligned(values);
+
+    median_raw(vals.iter().collect()).map(|it| EDs::new(it, decimals))
+}
+
+/// Like [`variance`], but aligns decimal points. The result has the highest of them.
+pub fn variance_aligned(values: &[EDs]) -> Option<EDs> {
+    let (vals, decimals) = aligned(values);
+
+    variance_raw(vals.iter(), EDs::base(decimals)).map(|it| EDs::new(it, decimals))
+}
+
+/// Like [`stddev`], but aligns decimal points. The result has the highest of them.
+pub fn stddev_aligned(values: &[EDs]) -> Option<EDs> {
+    let (vals, decimals) = aligned(values);
+
+    stddev_raw(vals.iter()).map(|it| EDs::new(it, decimals))
+}
+
+/// Mean of the values, after dropping `trim` of the smallest and of the biggest ones
+/// (e.g. 1000 bps drops the bottom and the top 10%). `None`, if nothing is left.
+pub fn trimmed_mean<const D: usize>(values: &[ECs<D>], trim: Bps) -> Option<ECs<D>> {
+    let cut = values.len() * trim.0.min(BPS_DENOMINATOR) as usize / BPS_DENOMINATOR as usize;
+    if values.len() <= cut * 2 {
+        return None;
+    }
+
+    let refs = sorted(values);
+    let kept = &refs[cut..refs.len() - cut];
+    let sum = kept.iter().fold(Uint::ZERO, |acc, it| acc + &it.val);
+
+    Some(ECs::new(sum / kept.len() as u64))
+}
+
+/// The value at which the cumulative weight of sorted values reaches half of the total weight.
+/// `None`, if the total weight is zero.
+pub fn weighted_median<const D: usize, const W: usize>(
+    values: &[(ECs<D>, ECs<W>)],
+) -> Option<ECs<D>> {
+    let total = values.iter().fold(Uint::ZERO, |acc, (_, w)| acc + &w.val);
+    if total.is_zero() {
+        return None;
+    }
+
+    let mut refs: Vec<&(ECs<D>, ECs<W>)> = values.iter().collect();
+    refs.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut cumulative = Uint::ZERO;
+    for (value, weight) in refs {
+        cumulative += &weight.val;
+
+        if &cumulative * 2u32 >= total {
+            return Some(value.clone());
+        }
+    }
+
+    None
+}
+
+/// Σ(weight·value) / Σweight, computed on raw values without intermediate truncation.
+/// `None`, if the total weight is zero.
+pub fn weighted_avg<'a, const D: usize>(
+    pairs: impl Iterator<Item = (&'a ECs<D>, &'a ECs<D>)>,
+) -> Option<ECs<D>> {
+    let mut weighted = Uint::ZERO;
+    let mut total = Uint::ZERO;
+
+    for (value, weight) in pairs {
+        weighted += &value.val * &weight.val;
+        total += &weight.val;
+    }
+
+    if total.is_zero() {
+        return None;
+    }
+
+    Some(ECs::new(weighted / total))
+}
+
+/// Iterator of running totals, see [`CumSum::cumsum`]
+#[derive(Clone, Debug)]
+pub struct CumSumIter<I, const D: usize> {
+    iter: I,
+    total: ECs<D>,
+}
+
+impl<I, T, const D: usize> Iterator for CumSumIter<I, D>
+where
+    I: Iterator<Item = T>,
+    T: Borrow<ECs<D>>,
+{
+    type Item = ECs<D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let it = self.iter.next()?;
+        self.total += it.borrow();
+
+        Some(self.total.clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+pub trait CumSum<const D: usize>: Iterator + Sized {
+    /// Yields the running totals of amounts (owned or borrowed): `a, a + b, a + b + c, ...`
+    fn cumsum(self) -> CumSumIter<Self, D>;
+}
+
+impl<I, T, const D: usize> CumSum<D> for I
+where
+    I: Iterator<Item = T>,
+    T: Borrow<ECs<D>>,
+{
+    fn cumsum(self) -> CumSumIter<Self, D> {
+        CumSumIter {
+            iter: self,
+            total: ECs::zero(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn whole(values: &[u64]) -> Vec<E8s> {
+        values.iter().map(|it| E8s::from_whole(*it)).collect()
+    }
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(median(&whole(&[3, 1, 2])), Some(E8s::from_whole(2)));
+        assert_eq!(
+            median(&whole(&[4, 1, 3, 2])),
+            Some(E8s::new(250_000_000u64))
+        );
+        // the mean of the middle values is rounded down
+        assert_eq!(
+            median(&[E8s::new(1u64), E8s::new(2u64)]),
+            Some(E8s::new(1u64))
+        );
+        assert_eq!(median::<8>(&[]), None);
+    }
+
+    #[test]
+    fn trimmed_mean_drops_outliers() {
+        let prices = whole(&[100, 101, 99, 100, 10_000]);
+
+        // 20% of 5 values is one from each side
+        assert_eq!(
+            trimmed_mean(&prices, Bps(2_000)),
+            Some(E8s::new(10_033_333_333u64))
+        );
+        assert_eq!(trimmed_mean(&prices, Bps(0)), Some(E8s::from_whole(2_080)));
+        // 10% of 5 values rounds down to nothing
+        assert_eq!(
+            trimmed_mean(&prices, Bps(1_000)),
+            trimmed_mean(&prices, Bps(0))
+        );
+
+        let values = whole(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(
+            trimmed_mean(&values, Bps(1_000)),
+            Some(E8s::new(550_000_000u64))
+        );
+        assert_eq!(
+            trimmed_mean(&values, Bps(4_999)),
+            Some(E8s::new(550_000_000u64))
+        );
+        // nothing is left
+        assert_eq!(trimmed_mean(&values, Bps(5_000)), None);
+        assert_eq!(trimmed_mean(&values, Bps(20_000)), None);
+        assert_eq!(trimmed_mean::<8>(&[], Bps(0)), None);
+    }
+
+    #[test]
+    fn weighted_median_reaches_half_of_the_weight() {
+        let w = |v: u64, w: u64| (E8s::from_whole(v), E8s::from_whole(w));
+
+        assert_eq!(
+            weighted_median(&[w(3, 5), w(1, 1), w(2, 1)]),
+            Some(E8s::from_whole(3))
+        );
+        // exactly half is reached at the lower value
+        assert_eq!(
+            weighted_median(&[w(2, 1), w(1, 1)]),
+            Some(E8s::from_whole(1))
+        );
+        // zero weights don't count
+        assert_eq!(
+            weighted_median(&[w(1, 0), w(5, 2), w(9, 1)]),
+            Some(E8s::from_whole(5))
+        );
+        assert_eq!(weighted_median(&[w(1, 0)]), None);
+        assert_eq!(weighted_median::<8, 8>(&[]), None);
+    }
+
+    #[test]
+    fn moments_round_down() {
+        let values = whole(&[2, 4, 4, 4, 5, 5, 7, 9]);
+
+        assert_eq!(mean(&values), Some(E8s::from_whole(5)));
+        assert_eq!(variance(&values), Some(E8s::from_whole(4)));
+        assert_eq!(stddev(&values), Some(E8s::from_whole(2)));
+
+        let values = whole(&[1, 2, 2]);
+        // 5/3 and 2/9
+        assert_eq!(mean(&values), Some(E8s::new(166_666_666u64)));
+        assert_eq!(variance(&values), Some(E8s::new(22_222_222u64)));
+        assert_eq!(stddev(&values), Some(E8s::new(47_140_452u64)));
+
+        assert_eq!(variance(&whole(&[7])), Some(E8s::zero()));
+        assert_eq!(mean::<8>(&[]), None);
+        assert_eq!(variance::<8>(&[]), None);
+        assert_eq!(stddev::<8>(&[]), None);
+    }
+
+    #[test]
+    fn stddev_doesnt_truncate_the_variance() {
+        // 0.0 and 0.3: the variance 0.0225 truncates to 0.0, but the stddev is 0.15
+        let values = [ECs::<1>::new(0u64), ECs::<1>::new(3u64)];
+
+        assert_eq!(variance(&values), Some(ECs::zero()));
+        assert_eq!(stddev(&values), Some(ECs::new(1u64)));
+    }
+
+    #[test]
+    fn aligned_moments_take_the_highest_decimals() {
+        let values = [EDs::new(15u64, 1), EDs::new(225u64, 2), EDs::new(3u64, 0)];
+
+        // 1.5, 2.25 and 3: 6.75 / 3
+        assert_eq!(mean_aligned(&values), Some(EDs::new(225u64, 2)));
+        assert_eq!(median_aligned(&values), Some(EDs::new(225u64, 2)));
+        // 0.375 truncated to 2 decimals, and the square root of 0.375 (0.612...)
+        assert_eq!(variance_aligned(&values), Some(EDs::new(37u64, 2)));
+        assert_eq!(stddev_aligned(&values), Some(EDs::new(61u64, 2)));
+
+        assert_eq!(mean_aligned(&[]), None);
+        assert_eq!(stddev_aligned(&[]), None);
+    }
+
+    #[test]
+    fn weighted_avg_doesnt_truncate_products() {
+        let values = [E8s::new(1u64), E8s::new(2u64)];
+        let weights = [E8s::new(1u64), E8s::new(2u64)];
+
+        // per-pair products would truncate to zero at 8 decimal points: 5 / 3
+        assert_eq!(
+            weighted_avg(values.iter().zip(weights.iter())),
+            Some(E8s::new(1u64))
+        );
+
+        let values = whole(&[100, 200]);
+        let weights = whole(&[3, 1]);
+        assert_eq!(
+            weighted_avg(values.iter().zip(weights.iter())),
+            Some(E8s::from_whole(125))
+        );
+
+        let zero = [E8s::zero(), E8s::zero()];
+        assert_eq!(weighted_avg(values.iter().zip(zero.iter())), None);
+        assert_eq!(weighted_avg::<8>(core::iter::empty()), None);
+    }
+
+    #[test]
+    fn cumsum_of_owned_and_borrowed() {
+        let values = whole(&[1, 2, 3]);
+
+        let borrowed: Vec<E8s> = values.iter().cumsum().collect();
+        assert_eq!(borrowed, whole(&[1, 3, 6]));
+
+        let owned: Vec<E8s> = values.clone().into_iter().cumsum().collect();
+        assert_eq!(owned, borrowed);
+
+        assert_eq!(values.iter().cumsum().size_hint(), (3, Some(3)));
+        assert_eq!(core::iter::empty::<E8s>().cumsum().next(), None);
+    }
+}
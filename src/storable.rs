@@ -0,0 +1,909 @@
+use alloc::{borrow::Cow, vec, vec::Vec};
+use core::cmp::Ordering;
+
+use candid::decode_one;
+use ic_stable_structures::{storable::Bound, BTreeMap, Memory, Storable};
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs, math::ten_pow, uint::Uint, MAX_DECIMALS};
+
+/// Width of the big-endian magnitude used by `OrderedKey`, enough for any 256-bit value
+pub const ORDERED_KEY_WIDTH: usize = 32;
+
+/// Width of the big-endian magnitude used by `OrderedKey<EDs>`, enough for any 256-bit value
+/// scaled to `MAX_DECIMALS`
+pub const ORDERED_EDS_KEY_WIDTH: usize = 64;
+
+/// Current version of the compact binary layout of `EDs`
+pub const EDS_COMPACT_VERSION: u8 = 1;
+
+/// Current version of the binary layout of `ECs`
+pub const ECS_LAYOUT_VERSION: u8 = 1;
+
+// Versioned layouts start with a byte with the highest bit set. The very first (unversioned) layout
+// starts with decimals instead, which never exceeded 31 back then, and Candid starts with "DIDL",
+// so all of them can be told apart.
+const VERSION_FLAG: u8 = 0x80;
+const CANDID_MAGIC: &[u8] = b"DIDL";
+
+/// `EDs` wrapper with a compact `Storable` layout: a version byte, 1 byte of decimals and LE magnitude bytes.
+/// `EDs` itself is stored the same way now, this wrapper is kept for compatibility.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct EDsCompact(pub EDs);
+
+/// Decodes `EDs` stored in any layout ever produced by this crate:
+/// the Candid one `EDs` used to have or any version of the compact one
+pub fn decode_any(bytes: &[u8]) -> EDs {
+    if bytes.starts_with(CANDID_MAGIC) {
+        return decode_one(bytes).expect("Unable to decode");
+    }
+
+    let (&header, payload) = bytes.split_first().expect("Empty EDs bytes");
+
+    if header & VERSION_FLAG == 0 {
+        // unversioned layout: decimals + LE magnitude
+        return decode_compact_v1(bytes);
+    }
+
+    match header & !VERSION_FLAG {
+        1 => decode_compact_v1(payload),
+        v => unreachable!("Unsupported EDs layout version {}", v),
+    }
+}
+
+/// Re-encodes up to `batch` entries of the map with the current layouts of `K` and `V`, starting
+/// from `from` (or the first entry), and returns the key to resume from, or `None` once the whole
+/// map is done. `K` and `V` should be able to decode all their previous layouts (as `EDs` and
+/// `EDsCompact` do). Meant to be called in `post_upgrade` for small maps, or across several
+/// messages (e.g. from a timer) with the returned cursor for the big ones, so neither the
+/// instruction limit nor the heap is exceeded.
+///
+/// Keys are removed and inserted back, since inserting over an existing key keeps its old bytes.
+/// This relies on `Ord` of `K` being the same for both layouts, which holds for any `K` ordered
+/// by its decoded value.
+pub fn reencode_map<K, V, M>(
+    map: &mut BTreeMap<K, V, M>,
+    from: Option<K>,
+    batch: usize,
+) -> Option<K>
+where
+    K: Storable + Ord + Clone,
+    V: Storable,
+    M: Memory,
+{
+    let mut keys: Vec<K> = match from {
+        Some(from) => map.range(from..).take(batch + 1).map(|(k, _)| k).collect(),
+        None => map.iter().take(batch + 1).map(|(k, _)| k).collect(),
+    };
+
+    let next = if keys.len() > batch { keys.pop() } else { None };
+
+    for key in keys {
+        let value = map.remove(&key).unwrap();
+        map.insert(key, value);
+    }
+
+    next
+}
+
+pub(crate) fn encode_compact(value: &EDs) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(2 + (value.val.bits() as usize).div_ceil(8).max(1));
+    buf.push(VERSION_FLAG | EDS_COMPACT_VERSION);
+    buf.push(value.decimals);
+    write_le(&mut buf, &value.val);
+
+    buf
+}
+
+// `ECs` used to be stored as bare LE magnitude bytes, which never end with a zero byte (except
+// zero itself, stored as `[0]`), so the version goes into a trailer instead: the magnitude without
+// zero high bytes (nothing for zero), the version byte and a terminating zero byte.
+pub(crate) fn encode_ecs<const D: usize>(value: &ECs<D>) -> Vec<u8> {
+    let mut buf = Vec::with_capacity((value.val.bits() as usize).div_ceil(8) + 2);
+    if !value.is_zero() {
+        write_le(&mut buf, &value.val);
+    }
+
+    buf.push(ECS_LAYOUT_VERSION);
+    buf.push(0);
+
+    buf
+}
+
+/// Decodes `ECs` stored in the current layout or as the bare LE bytes of previous versions
+pub(crate) fn decode_ecs<const D: usize>(bytes: &[u8]) -> ECs<D> {
+    match bytes {
+        [payload @ .., version, 0] => match version {
+            1 => ECs::new(BigUint::from_bytes_le(payload)),
+            v => unreachable!("Unsupported ECs layout version {}", v),
+        },
+        _ => ECs::new(BigUint::from_bytes_le(bytes)),
+    }
+}
+
+fn decode_compact_v1(bytes: &[u8]) -> EDs {
+    let (decimals, val) = bytes.split_first().expect("Empty EDs bytes");
+
+    EDs::new(BigUint::from_bytes_le(val), *decimals)
+}
+
+// Same bytes as `BigUint::to_bytes_le()`, but written straight into the buffer
+fn write_le(buf: &mut Vec<u8>, val: &Uint) {
+    let start = buf.len();
+    match val.to_u128() {
+        Some(val) => buf.extend_from_slice(&val.to_le_bytes()),
+        None => {
+            for digit in val.big().iter_u64_digits() {
+                buf.extend_from_slice(&digit.to_le_bytes());
+            }
+        }
+    }
+
+    // drop the zero high bytes of the last digit, keeping at least one byte like `to_bytes_le()` does
+    while buf.len() > start + 1 && buf[buf.len() - 1] == 0 {
+        buf.pop();
+    }
+
+    if buf.len() == start {
+        buf.push(0);
+    }
+}
+
+impl From<EDs> for EDsCompact {
+    fn from(value: EDs) -> Self {
+        Self(value)
+    }
+}
+
+impl From<EDsCompact> for EDs {
+    fn from(value: EDsCompact) -> Self {
+        value.0
+    }
+}
+
+impl Storable for EDsCompact {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(encode_compact(&self.0))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(decode_any(&bytes))
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Key wrapper for `StableBTreeMap` with a fixed-width big-endian encoding,
+/// so the byte order of encoded keys matches their numeric order.
+///
+/// The map itself orders entries by `Ord` of the decoded keys, which is numeric as well:
+/// `OrderedKey<EDs>` compares values across decimal points and breaks ties by decimals.
+#[derive(Clone, Debug, Default, Hash)]
+pub struct OrderedKey<T>(pub T);
+
+impl<const D: usize> PartialEq for OrderedKey<ECs<D>> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<const D: usize> Eq for OrderedKey<ECs<D>> {}
+
+impl<const D: usize> PartialOrd for OrderedKey<ECs<D>> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const D: usize> Ord for OrderedKey<ECs<D>> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+// equal keys have the same value and the same decimals, so this agrees with `Hash`
+impl PartialEq for OrderedKey<EDs> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedKey<EDs> {}
+
+impl PartialOrd for OrderedKey<EDs> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedKey<EDs> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .cmp_value(&other.0)
+            .then(self.0.decimals.cmp(&other.0.decimals))
+    }
+}
+
+impl<T> From<T> for OrderedKey<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+fn check_key_width(val: &Uint) {
+    if val.bits() > ORDERED_KEY_WIDTH as u64 * 8 {
+        unreachable!(
+            "Values wider than {} bytes can't be used as keys",
+            ORDERED_KEY_WIDTH
+        );
+    }
+}
+
+/// Writes the value into the whole buffer, big-endian and padded with leading zeros
+fn to_be_fixed(val: &Uint, buf: &mut [u8]) {
+    let bytes = val.to_bytes_be();
+    let width = buf.len();
+
+    buf[width - bytes.len()..].copy_from_slice(&bytes);
+}
+
+impl<const D: usize> Storable for OrderedKey<ECs<D>> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        check_key_width(&self.0.val);
+
+        let mut buf = vec![0u8; ORDERED_KEY_WIDTH];
+        to_be_fixed(&self.0.val, &mut buf);
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(ECs::new(BigUint::from_bytes_be(&bytes)))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: ORDERED_KEY_WIDTH as u32,
+        is_fixed_size: true,
+    };
+}
+
+/// Ordered numerically, whatever the decimal points: the value is scaled to `MAX_DECIMALS`
+/// before encoding, so `1.0` with 2 decimals (100) goes after `0.5` with 8 decimals
+/// (50'000'000). Equal values with different decimal points are ordered by decimals.
+/// This matches `Ord` of `OrderedKey<EDs>`, unlike `Ord` of `EDs`, which compares raw values first.
+impl Storable for OrderedKey<EDs> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        check_key_width(&self.0.val);
+
+        let scaled = Uint::from(&self.0.val * ten_pow(MAX_DECIMALS - self.0.decimals as usize));
+
+        let mut buf = vec![0u8; ORDERED_EDS_KEY_WIDTH + 1];
+        to_be_fixed(&scaled, &mut buf[..ORDERED_EDS_KEY_WIDTH]);
+        buf[ORDERED_EDS_KEY_WIDTH] = self.0.decimals;
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let decimals = bytes[ORDERED_EDS_KEY_WIDTH];
+        let scaled = BigUint::from_bytes_be(&bytes[..ORDERED_EDS_KEY_WIDTH]);
+
+        Self(EDs::new(
+            scaled / ten_pow(MAX_DECIMALS - decimals as usize),
+            decimals,
+        ))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: ORDERED_EDS_KEY_WIDTH as u32 + 1,
+        is_fixed_size: true,
+    };
+}
+
+/// Current version of the binary layout of `ECsVec` and `EDsVec`
+pub const VEC_LAYOUT_VERSION: u8 = 1;
+
+fn write_varint(buf: &mut Vec<u8>, mut n: u64) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+
+        if n == 0 {
+            buf.push(byte);
+            break;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> u64 {
+    let mut n = 0u64;
+    let mut shift = 0;
+
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+
+        n |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return n;
+        }
+
+        shift += 7;
+    }
+}
+
+fn write_magnitude(buf: &mut Vec<u8>, val: &Uint) {
+    write_varint(buf, (val.bits() as usize).div_ceil(8).max(1) as u64);
+    write_le(buf, val);
+}
+
+fn read_magnitude(bytes: &[u8], pos: &mut usize) -> BigUint {
+    let len = read_varint(bytes, pos) as usize;
+    let val = BigUint::from_bytes_le(&bytes[*pos..*pos + len]);
+    *pos += len;
+
+    val
+}
+
+fn read_vec_header(bytes: &[u8], pos: &mut usize) -> usize {
+    let header = *bytes.first().expect("Empty vector bytes");
+    if header & VERSION_FLAG == 0 {
+        unreachable!("Unversioned vector layout (header {:#04x})", header);
+    }
+
+    match header & !VERSION_FLAG {
+        1 => {
+            *pos = 1;
+            read_varint(bytes, pos) as usize
+        }
+        v => unreachable!("Unsupported vector layout version {}", v),
+    }
+}
+
+/// Vector of amounts stored as a whole: a shared header (version, varint length)
+/// followed by varint-length-prefixed LE magnitudes
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct ECsVec<const D: usize>(pub Vec<ECs<D>>);
+
+impl<const D: usize> From<Vec<ECs<D>>> for ECsVec<D> {
+    fn from(value: Vec<ECs<D>>) -> Self {
+        Self(value)
+    }
+}
+
+impl<const D: usize> From<ECsVec<D>> for Vec<ECs<D>> {
+    fn from(value: ECsVec<D>) -> Self {
+        value.0
+    }
+}
+
+impl<const D: usize> Storable for ECsVec<D> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = Vec::with_capacity(1 + 9 + self.0.len() * 9);
+        buf.push(VERSION_FLAG | VEC_LAYOUT_VERSION);
+        write_varint(&mut buf, self.0.len() as u64);
+
+        for it in &self.0 {
+            write_magnitude(&mut buf, &it.val);
+        }
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let mut pos = 0;
+        let len = read_vec_header(&bytes, &mut pos);
+
+        let mut res = Vec::with_capacity(len);
+        for _ in 0..len {
+            res.push(ECs::new(read_magnitude(&bytes, &mut pos)));
+        }
+
+        Self(res)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Vector of amounts stored as a whole: a shared header (version, varint length)
+/// followed by decimals and varint-length-prefixed LE magnitudes of each element
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct EDsVec(pub Vec<EDs>);
+
+impl From<Vec<EDs>> for EDsVec {
+    fn from(value: Vec<EDs>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<EDsVec> for Vec<EDs> {
+    fn from(value: EDsVec) -> Self {
+        value.0
+    }
+}
+
+impl Storable for EDsVec {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = Vec::with_capacity(1 + 9 + self.0.len() * 10);
+        buf.push(VERSION_FLAG | VEC_LAYOUT_VERSION);
+        write_varint(&mut buf, self.0.len() as u64);
+
+        for it in &self.0 {
+            buf.push(it.decimals);
+            write_magnitude(&mut buf, &it.val);
+        }
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let mut pos = 0;
+        let len = read_vec_header(&bytes, &mut pos);
+
+        let mut res = Vec::with_capacity(len);
+        for _ in 0..len {
+            let decimals = bytes[pos];
+            pos += 1;
+
+            res.push(EDs::new(read_magnitude(&bytes, &mut pos), decimals));
+        }
+
+        Self(res)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// Current version of the binary layout of `ECsBounded`
+pub const BOUNDED_LAYOUT_VERSION: u8 = 1;
+
+/// `ECs` wrapper with a fixed-size `Storable` layout: a version byte followed by LE magnitude bytes
+/// zero-padded to `MAX_BYTES`, so every entry takes `MAX_BYTES + 1` bytes.
+/// Storing a value wider than `MAX_BYTES` traps.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct ECsBounded<const D: usize, const MAX_BYTES: usize>(pub ECs<D>);
+
+impl<const D: usize, const MAX_BYTES: usize> From<ECs<D>> for ECsBounded<D, MAX_BYTES> {
+    fn from(value: ECs<D>) -> Self {
+        Self(value)
+    }
+}
+
+impl<const D: usize, const MAX_BYTES: usize> From<ECsBounded<D, MAX_BYTES>> for ECs<D> {
+    fn from(value: ECsBounded<D, MAX_BYTES>) -> Self {
+        value.0
+    }
+}
+
+impl<const D: usize, const MAX_BYTES: usize> Storable for ECsBounded<D, MAX_BYTES> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = Vec::with_capacity(MAX_BYTES + 1);
+        buf.push(VERSION_FLAG | BOUNDED_LAYOUT_VERSION);
+        write_le(&mut buf, &self.0.val);
+
+        if buf.len() > MAX_BYTES + 1 {
+            unreachable!("{} doesn't fit into {} bytes", self.0, MAX_BYTES);
+        }
+
+        buf.resize(MAX_BYTES + 1, 0);
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        let (&header, payload) = bytes.split_first().expect("Empty ECs bytes");
+
+        match header {
+            h if h == VERSION_FLAG | 1 => Self(ECs::new(BigUint::from_bytes_le(payload))),
+            h => unreachable!("Unsupported bounded ECs layout (header {:#04x})", h),
+        }
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: MAX_BYTES as u32 + 1,
+        is_fixed_size: true,
+    };
+}
+
+/// The layout `ECs` had before it became unbounded: LE magnitude bytes zero-padded to `D` bytes,
+/// with a fixed bound of `D` bytes. The old layout didn't pad, so it could only store values of
+/// exactly `D` bytes, and those are encoded byte-for-byte the same way here.
+///
+/// Maps created with `ECs` keys by previous versions store the keys without a length prefix and
+/// can't be read with the current `ECs` layout: load them with this type as the key type instead,
+/// then move the entries into a map with `ECs` (or `OrderedKey`) keys. `ECs` values are
+/// length-prefixed in any map and still decode as `ECs`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
+pub struct ECsLegacy<const D: usize>(pub ECs<D>);
+
+impl<const D: usize> From<ECs<D>> for ECsLegacy<D> {
+    fn from(value: ECs<D>) -> Self {
+        Self(value)
+    }
+}
+
+impl<const D: usize> From<ECsLegacy<D>> for ECs<D> {
+    fn from(value: ECsLegacy<D>) -> Self {
+        value.0
+    }
+}
+
+impl<const D: usize> Storable for ECsLegacy<D> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        let mut buf = self.0.val.to_bytes_le();
+        if buf.len() > D {
+            unreachable!("{} doesn't fit into {} bytes", self.0, D);
+        }
+
+        buf.resize(D, 0);
+
+        Cow::Owned(buf)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Self(ECs::new(BigUint::from_bytes_le(&bytes)))
+    }
+
+    const BOUND: Bound = Bound::Bounded {
+        max_size: D as u32,
+        is_fixed_size: true,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use candid::{encode_one, Nat};
+    use ic_stable_structures::DefaultMemoryImpl;
+
+    use super::*;
+    use crate::{c::E8s, d::EDsCandid};
+
+    fn round_trip(value: &EDs) -> EDs {
+        let bytes = EDsCompact(value.clone()).to_bytes().into_owned();
+
+        EDsCompact::from_bytes(Cow::Owned(bytes)).0
+    }
+
+    #[test]
+    fn compact_round_trips_every_decimals() {
+        let above_u128 = BigUint::from(u128::MAX) + 1u32;
+        let max_256 = (BigUint::from(1u32) << 256u32) - 1u32;
+
+        for decimals in 0..=MAX_DECIMALS as u8 {
+            for val in [
+                BigUint::ZERO,
+                BigUint::from(1u32),
+                EDs::base(decimals).clone(),
+                BigUint::from(u64::MAX),
+                above_u128.clone(),
+                max_256.clone(),
+            ] {
+                let value = EDs::new(val, decimals);
+
+                assert_eq!(round_trip(&value), value);
+            }
+        }
+    }
+
+    #[test]
+    fn compact_size_of_e8s_amounts() {
+        let size = |raw: u64| EDsCompact(EDs::new(BigUint::from(raw), 8)).to_bytes().len();
+
+        // version + decimals + at least one magnitude byte
+        assert_eq!(size(0), 3);
+        // 0.01 ICP, the transfer fee is 10_000 e8s
+        assert_eq!(size(10_000), 4);
+        // 1 ICP
+        assert_eq!(size(100_000_000), 6);
+        // 1M ICP
+        assert_eq!(size(100_000_000_000_000), 8);
+        assert_eq!(size(u64::MAX), 10);
+    }
+
+    #[test]
+    fn compact_decodes_legacy_candid() {
+        for (raw, decimals) in [(0u64, 8u8), (100_000_000, 8), (u64::MAX, 18), (7, 0)] {
+            let legacy = encode_one(EDsCandid {
+                val: Nat::from(raw),
+                decimals,
+            })
+            .unwrap();
+            assert!(legacy.starts_with(CANDID_MAGIC));
+
+            let decoded = EDsCompact::from_bytes(Cow::Owned(legacy)).0;
+
+            assert_eq!(decoded, EDs::new(BigUint::from(raw), decimals));
+        }
+    }
+
+    #[test]
+    fn compact_decodes_unversioned_layout() {
+        let mut bytes = vec![8u8];
+        bytes.extend_from_slice(&100_000_000u64.to_le_bytes());
+
+        let decoded = EDsCompact::from_bytes(Cow::Owned(bytes)).0;
+
+        assert_eq!(decoded, EDs::new(BigUint::from(100_000_000u64), 8));
+    }
+
+    fn amounts() -> Vec<BigUint> {
+        vec![
+            BigUint::ZERO,
+            BigUint::from(1u32),
+            BigUint::from(255u32),
+            BigUint::from(256u32),
+            BigUint::from(100_000_000u32),
+            BigUint::from(u64::MAX),
+            BigUint::from(u128::MAX) + 1u32,
+        ]
+    }
+
+    #[test]
+    fn ecs_round_trips() {
+        for val in amounts() {
+            let value = E8s::new(val);
+            let bytes = value.to_bytes().into_owned();
+
+            assert_eq!(bytes[bytes.len() - 2..], [ECS_LAYOUT_VERSION, 0]);
+            assert_eq!(E8s::from_bytes(Cow::Owned(bytes)), value);
+        }
+
+        assert_eq!(E8s::zero().to_bytes().len(), 2);
+    }
+
+    #[test]
+    fn ecs_decodes_unversioned_layout() {
+        for val in amounts() {
+            let legacy = val.to_bytes_le();
+
+            assert_eq!(E8s::from_bytes(Cow::Owned(legacy)), E8s::new(val));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported ECs layout version 2")]
+    fn ecs_rejects_unknown_version() {
+        E8s::from_bytes(Cow::Owned(vec![1, 2, 0]));
+    }
+
+    #[test]
+    fn vectors_round_trip() {
+        let ecs = ECsVec::<8>(amounts().into_iter().map(ECs::new).collect());
+        let eds = EDsVec(
+            amounts()
+                .into_iter()
+                .enumerate()
+                .map(|(i, val)| EDs::new(val, i as u8 * 10))
+                .collect(),
+        );
+
+        assert_eq!(ECsVec::<8>::from_bytes(ecs.to_bytes()), ecs);
+        assert_eq!(EDsVec::from_bytes(eds.to_bytes()), eds);
+        assert_eq!(
+            ECsVec::<8>::from_bytes(ECsVec::<8>(vec![]).to_bytes()),
+            ECsVec(vec![])
+        );
+        assert_eq!(
+            EDsVec::from_bytes(EDsVec(vec![]).to_bytes()),
+            EDsVec(vec![])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported vector layout version 2")]
+    fn ecs_vec_rejects_unknown_version() {
+        ECsVec::<8>::from_bytes(Cow::Owned(vec![VERSION_FLAG | 2, 0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "Unversioned vector layout")]
+    fn eds_vec_rejects_unversioned_bytes() {
+        EDsVec::from_bytes(Cow::Owned(vec![1, 8, 1, 1]));
+    }
+
+    #[test]
+    fn bounded_round_trips() {
+        for val in amounts().into_iter().filter(|it| it.bits() <= 64) {
+            let value = ECsBounded::<8, 8>(ECs::new(val));
+            let bytes = value.to_bytes().into_owned();
+
+            assert_eq!(bytes.len(), 9);
+            assert_eq!(bytes[0], VERSION_FLAG | BOUNDED_LAYOUT_VERSION);
+            assert_eq!(ECsBounded::<8, 8>::from_bytes(Cow::Owned(bytes)), value);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit into 8 bytes")]
+    fn bounded_rejects_wide_values() {
+        ECsBounded::<8, 8>(ECs::new(BigUint::from(u64::MAX) + 1u32)).to_bytes();
+    }
+
+    #[test]
+    #[should_panic(expected = "Unsupported bounded ECs layout")]
+    fn bounded_rejects_unknown_version() {
+        ECsBounded::<8, 8>::from_bytes(Cow::Owned(vec![VERSION_FLAG | 2, 1, 0, 0, 0, 0, 0, 0, 0]));
+    }
+
+    /// `Storable` of `ECs` as it was before it became unbounded
+    #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct OldECs(E8s);
+
+    impl Storable for OldECs {
+        fn to_bytes(&self) -> Cow<'_, [u8]> {
+            Cow::Owned(self.0.val.to_bytes_le())
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            Self(ECs::new(BigUint::from_bytes_le(&bytes)))
+        }
+
+        const BOUND: Bound = Bound::Bounded {
+            max_size: 8,
+            is_fixed_size: true,
+        };
+    }
+
+    #[test]
+    fn legacy_reads_maps_written_with_the_old_bound() {
+        let memory = DefaultMemoryImpl::default();
+
+        // the old layout could only store values of exactly 8 bytes
+        let values = [
+            BigUint::from(1u64 << 56),
+            BigUint::from(100_000_000_000_000_000u64),
+            BigUint::from(u64::MAX),
+        ];
+
+        let mut old: BTreeMap<OldECs, E8s, _> = BTreeMap::init(memory.clone());
+        for (i, val) in values.iter().enumerate() {
+            old.insert(OldECs(ECs::new(val.clone())), E8s::from(i as u64));
+        }
+
+        for val in &values {
+            assert_eq!(
+                ECsLegacy(E8s::new(val.clone())).to_bytes(),
+                OldECs(E8s::new(val.clone())).to_bytes()
+            );
+        }
+
+        let legacy: BTreeMap<ECsLegacy<8>, E8s, _> = BTreeMap::load(memory);
+        let entries: Vec<(ECsLegacy<8>, E8s)> = legacy.iter().collect();
+
+        assert_eq!(entries.len(), values.len());
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            assert_eq!(key.0.val, values[i]);
+            assert_eq!(value, E8s::from(i as u64));
+        }
+
+        // then move the entries into a map with current keys
+        let mut current: BTreeMap<E8s, E8s, _> = BTreeMap::init(DefaultMemoryImpl::default());
+        for (key, value) in legacy.iter() {
+            current.insert(key.0, value);
+        }
+
+        assert_eq!(
+            current.get(&E8s::new(values[2].clone())),
+            Some(E8s::from(2u64))
+        );
+    }
+
+    /// `Storable` of `EDs` as it was when it was encoded with Candid
+    #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+    struct CandidEDs(EDs);
+
+    impl Storable for CandidEDs {
+        fn to_bytes(&self) -> Cow<'_, [u8]> {
+            Cow::Owned(
+                encode_one(EDsCandid {
+                    val: Nat(self.0.val.big().into_owned()),
+                    decimals: self.0.decimals,
+                })
+                .unwrap(),
+            )
+        }
+
+        fn from_bytes(bytes: Cow<[u8]>) -> Self {
+            Self(decode_any(&bytes))
+        }
+
+        const BOUND: Bound = Bound::Unbounded;
+    }
+
+    #[test]
+    fn reencode_map_migrates_legacy_keys_in_batches() {
+        let memory = DefaultMemoryImpl::default();
+        let entries: Vec<(EDs, EDs)> = (0..7u64)
+            .map(|i| {
+                (
+                    EDs::new(BigUint::from(i * 1_000), 8),
+                    EDs::new(BigUint::from(i), 2),
+                )
+            })
+            .collect();
+
+        let mut legacy: BTreeMap<CandidEDs, CandidEDs, _> = BTreeMap::init(memory.clone());
+        for (k, v) in &entries {
+            legacy.insert(CandidEDs(k.clone()), CandidEDs(v.clone()));
+        }
+
+        let mut map: BTreeMap<EDs, EDs, _> = BTreeMap::load(memory.clone());
+
+        let mut cursor = None;
+        let mut calls = 0;
+        loop {
+            cursor = reencode_map(&mut map, cursor, 3);
+            calls += 1;
+
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(calls, 3);
+        assert_eq!(map.iter().collect::<Vec<_>>(), entries);
+
+        // every key and value is stored in the compact layout now
+        let raw: BTreeMap<Vec<u8>, Vec<u8>, _> = BTreeMap::load(memory);
+        assert_eq!(raw.len(), entries.len() as u64);
+
+        for (k, v) in raw.iter() {
+            assert_eq!(k[0], VERSION_FLAG | EDS_COMPACT_VERSION);
+            assert_eq!(v[0], VERSION_FLAG | EDS_COMPACT_VERSION);
+        }
+    }
+
+    #[test]
+    fn ordered_eds_keys_sort_numerically() {
+        let keys = [
+            EDs::new(BigUint::ZERO, 8),
+            EDs::new(BigUint::from(50_000_000u32), 8),
+            EDs::new(BigUint::from(100u32), 2),
+            EDs::new(BigUint::from(100_000_000u32), 8),
+            EDs::new(BigUint::from(2u32), 0),
+            EDs::new((BigUint::from(1u32) << 256u32) - 1u32, 76),
+            EDs::new((BigUint::from(1u32) << 256u32) - 1u32, 0),
+        ];
+
+        let encoded: Vec<Vec<u8>> = keys
+            .iter()
+            .map(|it| OrderedKey(it.clone()).to_bytes().into_owned())
+            .collect();
+
+        for pair in encoded.windows(2) {
+            assert!(pair[0] < pair[1]);
+        }
+
+        for (key, bytes) in keys.iter().zip(encoded) {
+            assert_eq!(&OrderedKey::<EDs>::from_bytes(Cow::Owned(bytes)).0, key);
+        }
+    }
+
+    #[test]
+    fn ordered_eds_keys_iterate_numerically_in_a_map() {
+        let key = |raw: u64, decimals: u8| OrderedKey(EDs::new(BigUint::from(raw), decimals));
+
+        let mut map: BTreeMap<OrderedKey<EDs>, u64, _> =
+            BTreeMap::init(DefaultMemoryImpl::default());
+
+        // 1.5, 1.25 and 10, inserted in the order their raw values would sort in
+        map.insert(key(10, 0), 3);
+        map.insert(key(15, 1), 2);
+        map.insert(key(125, 2), 1);
+        // the same value as 1.5 with more decimals goes right after it
+        map.insert(key(150, 2), 4);
+
+        let values: Vec<u64> = map.iter().map(|(_, v)| v).collect();
+        assert_eq!(values, vec![1, 2, 4, 3]);
+
+        // everything from 1.3 (below 1.5, above 1.25) up to 10 exclusive
+        let keys: Vec<OrderedKey<EDs>> =
+            map.range(key(13, 1)..key(10, 0)).map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![key(15, 1), key(150, 2)]);
+
+        assert_eq!(map.get(&key(125, 2)), Some(1));
+        assert_eq!(map.get(&key(1250, 3)), None);
+    }
+}
@@ -0,0 +1,273 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::{
+    fmt::Display,
+    ops::{Add, AddAssign, Div, Mul, Sub, SubAssign},
+};
+
+#[cfg(feature = "candid")]
+use candid::CandidType;
+use serde::Deserialize;
+#[cfg(not(feature = "candid"))]
+use serde::Serialize;
+
+use crate::d::EDs;
+
+/// `EDs` of a specific currency, known only at runtime. Arithmetic between
+/// different currencies traps, `checked_*` methods return an error instead.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Deserialize)]
+#[cfg_attr(feature = "candid", derive(CandidType))]
+#[cfg_attr(not(feature = "candid"), derive(Serialize))]
+pub struct EDsTagged {
+    pub currency: u32,
+    pub value: EDs,
+}
+
+impl EDsTagged {
+    pub fn new(currency: u32, value: EDs) -> Self {
+        Self { currency, value }
+    }
+
+    pub fn zero(currency: u32, decimals: u8) -> Self {
+        Self::new(currency, EDs::zero(decimals))
+    }
+
+    fn check(&self, other: &Self) -> Result<(), String> {
+        if self.currency != other.currency {
+            return Err(format!(
+                "Incompatible currencies: {} and {}",
+                self.currency, other.currency
+            ));
+        }
+
+        if self.value.decimals != other.value.decimals {
+            return Err("Incompatible decimal points".to_string());
+        }
+
+        Ok(())
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Result<Self, String> {
+        self.check(other)?;
+
+        Ok(Self::new(self.currency, &self.value + &other.value))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Result<Self, String> {
+        self.check(other)?;
+
+        if self.value.val < other.value.val {
+            return Err("Subtraction overflow".to_string());
+        }
+
+        Ok(Self::new(self.currency, &self.value - &other.value))
+    }
+}
+
+impl Display for EDsTagged {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} (#{})", self.value, self.currency)
+    }
+}
+
+impl Add for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        if let Err(e) = self.check(rhs) {
+            unreachable!("{}", e);
+        }
+
+        EDsTagged::new(self.currency, &self.value + &rhs.value)
+    }
+}
+
+impl Add for EDsTagged {
+    type Output = EDsTagged;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        (&self).add(&rhs)
+    }
+}
+
+impl Add<&EDsTagged> for EDsTagged {
+    type Output = EDsTagged;
+
+    fn add(self, rhs: &EDsTagged) -> Self::Output {
+        (&self).add(rhs)
+    }
+}
+
+impl Add<EDsTagged> for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn add(self, rhs: EDsTagged) -> Self::Output {
+        self.add(&rhs)
+    }
+}
+
+impl AddAssign<&EDsTagged> for EDsTagged {
+    fn add_assign(&mut self, rhs: &EDsTagged) {
+        if let Err(e) = self.check(rhs) {
+            unreachable!("{}", e);
+        }
+
+        self.value.add_assign(&rhs.value)
+    }
+}
+
+impl AddAssign for EDsTagged {
+    fn add_assign(&mut self, rhs: Self) {
+        self.add_assign(&rhs)
+    }
+}
+
+impl Sub for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        if let Err(e) = self.check(rhs) {
+            unreachable!("{}", e);
+        }
+
+        EDsTagged::new(self.currency, &self.value - &rhs.value)
+    }
+}
+
+impl Sub for EDsTagged {
+    type Output = EDsTagged;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        (&self).sub(&rhs)
+    }
+}
+
+impl Sub<&EDsTagged> for EDsTagged {
+    type Output = EDsTagged;
+
+    fn sub(self, rhs: &EDsTagged) -> Self::Output {
+        (&self).sub(rhs)
+    }
+}
+
+impl Sub<EDsTagged> for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn sub(self, rhs: EDsTagged) -> Self::Output {
+        self.sub(&rhs)
+    }
+}
+
+impl SubAssign<&EDsTagged> for EDsTagged {
+    fn sub_assign(&mut self, rhs: &EDsTagged) {
+        if let Err(e) = self.check(rhs) {
+            unreachable!("{}", e);
+        }
+
+        self.value.sub_assign(&rhs.value)
+    }
+}
+
+impl SubAssign for EDsTagged {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.sub_assign(&rhs)
+    }
+}
+
+/// Scaling by a plain (currency-less) factor
+impl Mul<&EDs> for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn mul(self, rhs: &EDs) -> Self::Output {
+        EDsTagged::new(self.currency, &self.value * rhs)
+    }
+}
+
+impl Mul<EDs> for EDsTagged {
+    type Output = EDsTagged;
+
+    fn mul(self, rhs: EDs) -> Self::Output {
+        (&self).mul(&rhs)
+    }
+}
+
+/// Scaling by a plain (currency-less) factor
+impl Div<&EDs> for &EDsTagged {
+    type Output = EDsTagged;
+
+    fn div(self, rhs: &EDs) -> Self::Output {
+        EDsTagged::new(self.currency, &self.value / rhs)
+    }
+}
+
+impl Div<EDs> for EDsTagged {
+    type Output = EDsTagged;
+
+    fn div(self, rhs: EDs) -> Self::Output {
+        (&self).div(&rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ICP: u32 = 1;
+    const CKBTC: u32 = 2;
+
+    fn icp(raw: u64) -> EDsTagged {
+        EDsTagged::new(ICP, EDs::new(raw, 8))
+    }
+
+    #[test]
+    fn same_currency_math() {
+        assert_eq!(&icp(150) + &icp(50), icp(200));
+        assert_eq!(icp(150) - icp(50), icp(100));
+        assert_eq!(icp(150).checked_add(&icp(50)), Ok(icp(200)));
+        assert_eq!(icp(150).checked_sub(&icp(50)), Ok(icp(100)));
+
+        let mut acc = EDsTagged::zero(ICP, 8);
+        acc += icp(7);
+        acc -= &icp(2);
+        assert_eq!(acc, icp(5));
+
+        // scaling keeps the currency
+        let half = EDs::new(50_000_000u64, 8);
+        assert_eq!(&icp(300) * &half, icp(150));
+        assert_eq!(icp(300) / half, icp(600));
+        assert_eq!(icp(150_000_000).to_string(), "1.50000000 (#1)");
+    }
+
+    #[test]
+    fn checked_ops_reject_mixing() {
+        let btc = EDsTagged::new(CKBTC, EDs::new(1u64, 8));
+
+        assert_eq!(
+            icp(1).checked_add(&btc),
+            Err("Incompatible currencies: 1 and 2".to_string())
+        );
+        assert_eq!(
+            icp(1).checked_sub(&EDsTagged::new(ICP, EDs::new(1u64, 6))),
+            Err("Incompatible decimal points".to_string())
+        );
+        assert_eq!(
+            icp(1).checked_sub(&icp(2)),
+            Err("Subtraction overflow".to_string())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible currencies: 1 and 2")]
+    fn operators_trap_on_mixing() {
+        let _ = icp(1) + EDsTagged::new(CKBTC, EDs::new(1u64, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "Incompatible currencies: 2 and 1")]
+    fn assign_operators_trap_on_mixing() {
+        let mut btc = EDsTagged::zero(CKBTC, 8);
+        btc -= icp(0);
+    }
+}
@@ -0,0 +1,100 @@
+//! Generators for property tests and fuzzing: `proptest::arbitrary::Arbitrary` (feature
+//! `proptest`) and `arbitrary::Arbitrary` (feature `arbitrary`) impls.
+//!
+//! Raw values are built from 0..=32 random bytes, with the length picked first, so tiny amounts,
+//! amounts around one and huge (up to 256 bits) ones are all equally likely.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn mul_div_roundtrip(a in any::<E8s>(), b in any::<E8s>()) { ... }
+//!
+//!     // `EDs` with random decimals by default, or with the given ones
+//!     fn eds_sum(a in any_with::<EDs>(Some(6)), b in any_with::<EDs>(Some(6))) { ... }
+//! }
+//! ```
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs, MAX_DECIMALS};
+
+/// The biggest generated raw value is 32 bytes long
+const MAX_RAW_BYTES: usize = 32;
+
+#[cfg(feature = "proptest")]
+mod proptest_impls {
+    use proptest::{
+        arbitrary::{any, Arbitrary},
+        collection::vec,
+        strategy::{BoxedStrategy, Just, Strategy},
+    };
+
+    use super::*;
+
+    fn raw() -> impl Strategy<Value = BigUint> {
+        (0..=MAX_RAW_BYTES)
+            .prop_flat_map(|len| vec(any::<u8>(), len..=len))
+            .prop_map(|bytes| BigUint::from_bytes_le(&bytes))
+    }
+
+    impl<const D: usize> Arbitrary for ECs<D> {
+        type Parameters = ();
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+            raw().prop_map(ECs::new).boxed()
+        }
+    }
+
+    /// Parameters are the decimal points, random if `None`
+    impl Arbitrary for EDs {
+        type Parameters = Option<u8>;
+        type Strategy = BoxedStrategy<Self>;
+
+        fn arbitrary_with(decimals: Self::Parameters) -> Self::Strategy {
+            let decimals = match decimals {
+                Some(d) => Just(d).boxed(),
+                None => (0..=MAX_DECIMALS as u8).boxed(),
+            };
+
+            (raw(), decimals)
+                .prop_map(|(val, decimals)| EDs::new(val, decimals))
+                .boxed()
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impls {
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::*;
+
+    fn raw(u: &mut Unstructured<'_>) -> Result<BigUint> {
+        let len = u.int_in_range(0..=MAX_RAW_BYTES)?;
+
+        Ok(BigUint::from_bytes_le(u.bytes(len)?))
+    }
+
+    impl<'a, const D: usize> Arbitrary<'a> for ECs<D> {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            raw(u).map(ECs::new)
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            (1, Some(1 + MAX_RAW_BYTES))
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for EDs {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let decimals = u.int_in_range(0..=MAX_DECIMALS as u8)?;
+
+            raw(u).map(|val| EDs::new(val, decimals))
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            (2, Some(2 + MAX_RAW_BYTES))
+        }
+    }
+}
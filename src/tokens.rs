@@ -0,0 +1,135 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+};
+
+use candid::Principal;
+
+use crate::{d::EDs, MAX_DECIMALS};
+
+/// Maps token symbols and ledger canister ids to their decimal points,
+/// so amounts of different tokens can be constructed without passing bare `u8` decimals around
+#[derive(Clone, Debug, Default)]
+pub struct TokenRegistry {
+    by_symbol: BTreeMap<String, u8>,
+    by_canister_id: BTreeMap<Principal, u8>,
+}
+
+impl TokenRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, symbol: &str, canister_id: Option<Principal>, decimals: u8) {
+        if decimals as usize > MAX_DECIMALS {
+            unreachable!("Decimal points after {} are not supported", MAX_DECIMALS);
+        }
+
+        self.by_symbol.insert(symbol.to_string(), decimals);
+
+        if let Some(id) = canister_id {
+            self.by_canister_id.insert(id, decimals);
+        }
+    }
+
+    pub fn decimals(&self, symbol: &str) -> Option<u8> {
+        self.by_symbol.get(symbol).copied()
+    }
+
+    pub fn decimals_by_canister_id(&self, canister_id: &Principal) -> Option<u8> {
+        self.by_canister_id.get(canister_id).copied()
+    }
+
+    /// Parses a "123.456" string into an amount of the token with the given symbol
+    pub fn amount(&self, symbol: &str, value: &str) -> Result<EDs, String> {
+        let decimals = self
+            .decimals(symbol)
+            .ok_or(format!("Unknown token {}", symbol))?;
+
+        EDs::from_str_d(value, decimals)
+    }
+
+    /// Parses a "123.456" string into an amount of the token with the given ledger canister id
+    pub fn amount_by_canister_id(
+        &self,
+        canister_id: &Principal,
+        value: &str,
+    ) -> Result<EDs, String> {
+        let decimals = self
+            .decimals_by_canister_id(canister_id)
+            .ok_or(format!("Unknown token {}", canister_id))?;
+
+        EDs::from_str_d(value, decimals)
+    }
+
+    pub fn zero(&self, symbol: &str) -> Result<EDs, String> {
+        let decimals = self
+            .decimals(symbol)
+            .ok_or(format!("Unknown token {}", symbol))?;
+
+        Ok(EDs::zero(decimals))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> TokenRegistry {
+        let mut registry = TokenRegistry::new();
+        registry.register(
+            "ckBTC",
+            Some(Principal::from_text("mxzaz-hqaaa-aaaar-qaada-cai").unwrap()),
+            8,
+        );
+        registry.register("ckETH", None, 18);
+
+        registry
+    }
+
+    #[test]
+    fn amounts_get_the_token_decimals() {
+        let registry = registry();
+
+        let btc = registry.amount("ckBTC", "0.001").unwrap();
+        assert_eq!(btc, EDs::new(100_000u64, 8));
+        assert_eq!(btc.decimals, 8);
+
+        let eth = registry.amount("ckETH", "1.5").unwrap();
+        assert_eq!(eth, EDs::new(1_500_000_000_000_000_000u128, 18));
+        assert_eq!(registry.zero("ckETH"), Ok(EDs::zero(18)));
+
+        let id = Principal::from_text("mxzaz-hqaaa-aaaar-qaada-cai").unwrap();
+        assert_eq!(registry.decimals_by_canister_id(&id), Some(8));
+        assert_eq!(registry.amount_by_canister_id(&id, "0.001"), Ok(btc));
+    }
+
+    #[test]
+    fn unknown_tokens_and_bad_amounts() {
+        let registry = registry();
+
+        assert_eq!(registry.decimals("ICP"), None);
+        assert_eq!(registry.amount("ICP", "1"), Err("Unknown token ICP".into()));
+        assert!(registry.zero("ICP").is_err());
+        assert!(registry
+            .amount_by_canister_id(&Principal::anonymous(), "1")
+            .is_err());
+        // more digits than the token has
+        assert!(registry.amount("ckBTC", "0.000000001").is_err());
+    }
+
+    #[test]
+    fn re_registering_overrides() {
+        let mut registry = registry();
+        registry.register("ckBTC", None, 6);
+
+        assert_eq!(registry.decimals("ckBTC"), Some(6));
+    }
+
+    #[test]
+    #[should_panic(expected = "Decimal points after 76 are not supported")]
+    fn too_many_decimals_trap() {
+        TokenRegistry::new().register("X", None, 77);
+    }
+}
@@ -0,0 +1,137 @@
+use alloc::{format, string::String};
+
+#[cfg(feature = "candid")]
+use candid::Nat;
+
+use crate::{c::E8s, fees::icp_fee};
+
+/// Builds a transfer amount, which is guaranteed to be payable from the balance:
+/// `amount + fee <= balance`, `amount <= cap` and `amount > dust`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransferAmount {
+    balance: E8s,
+    fee: E8s,
+    cap: Option<E8s>,
+    dust: E8s,
+}
+
+impl TransferAmount {
+    /// Defaults to the ICP ledger fee, no cap and zero dust
+    pub fn new(balance: E8s) -> Self {
+        Self {
+            balance,
+            fee: icp_fee(),
+            cap: None,
+            dust: E8s::zero(),
+        }
+    }
+
+    pub fn fee(mut self, fee: E8s) -> Self {
+        self.fee = fee;
+        self
+    }
+
+    pub fn cap(mut self, cap: E8s) -> Self {
+        self.cap = Some(cap);
+        self
+    }
+
+    pub fn dust(mut self, dust: E8s) -> Self {
+        self.dust = dust;
+        self
+    }
+
+    pub fn build(&self) -> Result<E8s, String> {
+        if self.balance <= self.fee {
+            return Err(format!(
+                "Balance {} doesn't cover the fee {}",
+                self.balance, self.fee
+            ));
+        }
+
+        let mut amount = &self.balance - &self.fee;
+        if let Some(cap) = &self.cap {
+            if &amount > cap {
+                amount = cap.clone();
+            }
+        }
+
+        if amount <= self.dust {
+            return Err(format!(
+                "Amount {} is not bigger than the dust threshold {}",
+                amount, self.dust
+            ));
+        }
+
+        Ok(amount)
+    }
+
+    pub fn build_u64(&self) -> Result<u64, String> {
+        let amount = self.build()?;
+
+        u64::try_from(&amount.val).map_err(|_| format!("Amount {} doesn't fit into u64", amount))
+    }
+
+    #[cfg(feature = "candid")]
+    pub fn build_nat(&self) -> Result<Nat, String> {
+        self.build().map(Nat::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pays_the_fee_from_the_balance() {
+        let balance = E8s::from_whole(1);
+
+        assert_eq!(
+            TransferAmount::new(balance.clone()).build(),
+            Ok(E8s::new(99_990_000u64))
+        );
+        assert_eq!(
+            TransferAmount::new(balance.clone())
+                .fee(E8s::zero())
+                .build_u64(),
+            Ok(100_000_000)
+        );
+        assert_eq!(
+            TransferAmount::new(balance).cap(E8s::new(500u64)).build(),
+            Ok(E8s::new(500u64))
+        );
+    }
+
+    #[test]
+    fn rejects_unpayable_amounts() {
+        assert_eq!(
+            TransferAmount::new(E8s::new(10_000u64)).build(),
+            Err("Balance 0.00010000 doesn't cover the fee 0.00010000".into())
+        );
+        // exactly the dust is not enough
+        assert!(TransferAmount::new(E8s::new(10_500u64))
+            .dust(E8s::new(500u64))
+            .build()
+            .is_err());
+        assert_eq!(
+            TransferAmount::new(E8s::new(10_501u64))
+                .dust(E8s::new(500u64))
+                .build(),
+            Ok(E8s::new(501u64))
+        );
+        assert!(TransferAmount::new(E8s::from_whole(1))
+            .cap(E8s::new(5u64))
+            .dust(E8s::new(5u64))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn u64_and_nat_amounts() {
+        let big = TransferAmount::new(E8s::new(u64::MAX as u128 + 20_000));
+        assert!(big.build_u64().is_err());
+
+        #[cfg(feature = "candid")]
+        assert_eq!(big.build_nat(), Ok(Nat::from(u64::MAX as u128 + 10_000)));
+    }
+}
@@ -0,0 +1,131 @@
+//! `sin` and `cos` in deterministic integer math, enabled by the `trig` feature.
+//! Since the types are unsigned, results are returned as a sign (`true` for negative)
+//! and a magnitude, rounded down.
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs, math::ten_pow};
+
+/// Extra decimal points every evaluation is carried out with
+const GUARD_DIGITS: usize = 10;
+
+/// `atan(1 / k)` at `scale`
+fn atan_inv(k: u64, scale: &BigUint) -> BigUint {
+    let k = BigUint::from(k);
+    let k2 = &k * &k;
+
+    let mut power = scale / &k;
+    let mut pos = BigUint::ZERO;
+    let mut neg = BigUint::ZERO;
+    let mut n = 1u64;
+
+    loop {
+        let term = &power / BigUint::from(n);
+        if term == BigUint::ZERO {
+            break;
+        }
+
+        if n % 4 == 1 {
+            pos += term;
+        } else {
+            neg += term;
+        }
+
+        power /= &k2;
+        n += 2;
+    }
+
+    pos - neg
+}
+
+/// `pi` at `scale`, via Machin's formula `pi = 16 * atan(1/5) - 4 * atan(1/239)`
+fn pi_scaled(scale: &BigUint) -> BigUint {
+    atan_inv(5, scale) * 16u32 - atan_inv(239, scale) * 4u32
+}
+
+/// `sin(x)` for a raw `x` with `decimals` decimal points, optionally shifted by `pi / 2`
+/// (which makes it `cos(x)`)
+fn sin_raw(x: &BigUint, decimals: usize, shift_half_pi: bool) -> (bool, BigUint) {
+    // reduction modulo 2 * pi amplifies the error of pi by the amount of periods in x
+    let guard = GUARD_DIGITS + (x / ten_pow(decimals)).bits() as usize * 31 / 100 + 1;
+    let scale = ten_pow(decimals + guard);
+    let pi = pi_scaled(&scale);
+    let half_pi = &pi / 2u32;
+
+    let mut r = x * ten_pow(guard);
+    if shift_half_pi {
+        r += &half_pi;
+    }
+
+    r %= &pi * 2u32;
+
+    // sin(r) = -sin(r - pi) for r in [pi, 2 * pi)
+    let neg = r >= pi;
+    if neg {
+        r -= &pi;
+    }
+
+    // sin(r) = sin(pi - r), so r ends up in [0, pi / 2]
+    if r > half_pi {
+        r = &pi - r;
+    }
+
+    // Taylor series at r in [0, pi / 2]
+    let r2 = &r * &r / &scale;
+    let mut term = r;
+    let mut pos = BigUint::ZERO;
+    let mut neg_terms = BigUint::ZERO;
+    let mut k = 1u64;
+
+    loop {
+        if term == BigUint::ZERO {
+            break;
+        }
+
+        if k % 4 == 1 {
+            pos += &term;
+        } else {
+            neg_terms += &term;
+        }
+
+        term = term * &r2 / (&scale * BigUint::from((k + 1) * (k + 2)));
+        k += 2;
+    }
+
+    let val = (pos - neg_terms) / ten_pow(guard);
+    let neg = neg && val != BigUint::ZERO;
+
+    (neg, val)
+}
+
+impl<const D: usize> ECs<D> {
+    /// `sin(self)` (radians) as a sign (`true` for negative) and a magnitude
+    pub fn sin(&self) -> (bool, Self) {
+        let (neg, val) = sin_raw(&self.val.big(), D, false);
+
+        (neg, Self::new(val))
+    }
+
+    /// `cos(self)` (radians) as a sign (`true` for negative) and a magnitude
+    pub fn cos(&self) -> (bool, Self) {
+        let (neg, val) = sin_raw(&self.val.big(), D, true);
+
+        (neg, Self::new(val))
+    }
+}
+
+impl EDs {
+    /// `sin(self)` (radians) as a sign (`true` for negative) and a magnitude
+    pub fn sin(&self) -> (bool, Self) {
+        let (neg, val) = sin_raw(&self.val.big(), self.decimals as usize, false);
+
+        (neg, Self::new(val, self.decimals))
+    }
+
+    /// `cos(self)` (radians) as a sign (`true` for negative) and a magnitude
+    pub fn cos(&self) -> (bool, Self) {
+        let (neg, val) = sin_raw(&self.val.big(), self.decimals as usize, true);
+
+        (neg, Self::new(val, self.decimals))
+    }
+}
@@ -0,0 +1,595 @@
+//! `Uint`, the raw value of `ECs` and `EDs`
+
+use alloc::borrow::Cow;
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    iter::Sum,
+    ops::{
+        Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Shl, Shr, Sub, SubAssign,
+    },
+};
+
+use alloc::vec::Vec;
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+
+/// Unsigned raw value, stored inline while it fits into `u128` (which is the case for the vast
+/// majority of token amounts) and as a heap-allocated `BigUint` otherwise. Arithmetic on inline
+/// values doesn't allocate: results move to the heap only when they overflow `u128` and come
+/// back inline once they fit again, so the representation only shows in the cost.
+///
+/// Arithmetic with `BigUint` operands is supported as well and results in a `BigUint`.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uint(Repr);
+
+// `Big` only holds values above `u128::MAX`, so every value has a single representation
+// and the derived comparisons (`Small` before `Big`) are numeric
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Repr {
+    Small(u128),
+    Big(BigUint),
+}
+
+impl Uint {
+    pub const ZERO: Self = Self(Repr::Small(0));
+
+    pub const fn from_u128(val: u128) -> Self {
+        Self(Repr::Small(val))
+    }
+
+    pub fn from_big(val: BigUint) -> Self {
+        match u128::try_from(&val) {
+            Ok(val) => Self(Repr::Small(val)),
+            Err(_) => Self(Repr::Big(val)),
+        }
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        match &self.0 {
+            Repr::Small(val) => Some(*val),
+            Repr::Big(_) => None,
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        self.to_u128().and_then(|val| u64::try_from(val).ok())
+    }
+
+    /// Whether the value is stored inline, i.e. fits into `u128`
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Small(_))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self.0, Repr::Small(0))
+    }
+
+    /// Significant bits, 0 for zero
+    pub fn bits(&self) -> u64 {
+        match &self.0 {
+            Repr::Small(val) => (u128::BITS - val.leading_zeros()) as u64,
+            Repr::Big(val) => val.bits(),
+        }
+    }
+
+    /// The value as `BigUint`, only allocating if it's stored inline
+    pub fn big(&self) -> Cow<'_, BigUint> {
+        match &self.0 {
+            Repr::Small(val) => Cow::Owned(BigUint::from(*val)),
+            Repr::Big(val) => Cow::Borrowed(val),
+        }
+    }
+
+    pub fn into_big(self) -> BigUint {
+        match self.0 {
+            Repr::Small(val) => BigUint::from(val),
+            Repr::Big(val) => val,
+        }
+    }
+
+    /// Writes the value into `out`, reusing its buffer
+    pub fn clone_into_big(&self, out: &mut BigUint) {
+        match &self.0 {
+            Repr::Small(val) => out.assign_from_slice(&[
+                *val as u32,
+                (*val >> 32) as u32,
+                (*val >> 64) as u32,
+                (*val >> 96) as u32,
+            ]),
+            Repr::Big(val) => out.clone_from(val),
+        }
+    }
+
+    /// Integer square root, rounded down
+    pub fn sqrt(&self) -> Self {
+        Self::from_big(self.big().sqrt())
+    }
+
+    /// Integer `n`-th root, rounded down
+    pub fn nth_root(&self, n: u32) -> Self {
+        Self::from_big(self.big().nth_root(n))
+    }
+
+    /// Quotient and remainder at once
+    pub fn div_rem(&self, rhs: &Uint) -> (Self, Self) {
+        if let (Repr::Small(a), Repr::Small(b)) = (&self.0, &rhs.0) {
+            if *b != 0 {
+                return (Self::from_u128(a / b), Self::from_u128(a % b));
+            }
+        }
+
+        let (q, r) = self.big().div_rem(&*rhs.big());
+        (Self::from_big(q), Self::from_big(r))
+    }
+
+    /// Little-endian bytes without trailing zeros, `[0]` for zero (like `BigUint::to_bytes_le`)
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        self.big().to_bytes_le()
+    }
+
+    /// Big-endian bytes without leading zeros, `[0]` for zero (like `BigUint::to_bytes_be`)
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        self.big().to_bytes_be()
+    }
+
+    pub fn pow(&self, exp: u32) -> Self {
+        if let Repr::Small(val) = self.0 {
+            if let Some(res) = val.checked_pow(exp) {
+                return Self::from_u128(res);
+            }
+        }
+
+        Self::from_big(self.big().pow(exp))
+    }
+
+    /// Applies `f` to the value as `BigUint`, in place if it's on the heap already
+    fn apply_big(&mut self, f: impl FnOnce(&mut BigUint)) {
+        let mut val = match core::mem::take(&mut self.0) {
+            Repr::Small(val) => BigUint::from(val),
+            Repr::Big(val) => val,
+        };
+
+        f(&mut val);
+        *self = Self::from_big(val);
+    }
+}
+
+impl Default for Repr {
+    fn default() -> Self {
+        Self::Small(0)
+    }
+}
+
+impl Default for Uint {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl Display for Uint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match &self.0 {
+            Repr::Small(val) => Display::fmt(val, f),
+            Repr::Big(val) => Display::fmt(val, f),
+        }
+    }
+}
+
+impl Debug for Uint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl From<BigUint> for Uint {
+    fn from(value: BigUint) -> Self {
+        Self::from_big(value)
+    }
+}
+
+impl From<&BigUint> for Uint {
+    fn from(value: &BigUint) -> Self {
+        match u128::try_from(value) {
+            Ok(val) => Self(Repr::Small(val)),
+            Err(_) => Self(Repr::Big(value.clone())),
+        }
+    }
+}
+
+impl From<u128> for Uint {
+    fn from(value: u128) -> Self {
+        Self::from_u128(value)
+    }
+}
+
+impl From<u64> for Uint {
+    fn from(value: u64) -> Self {
+        Self::from_u128(value as u128)
+    }
+}
+
+impl From<u32> for Uint {
+    fn from(value: u32) -> Self {
+        Self::from_u128(value as u128)
+    }
+}
+
+impl From<Uint> for BigUint {
+    fn from(value: Uint) -> Self {
+        value.into_big()
+    }
+}
+
+impl From<&Uint> for BigUint {
+    fn from(value: &Uint) -> Self {
+        value.big().into_owned()
+    }
+}
+
+impl From<Uint> for BigInt {
+    fn from(value: Uint) -> Self {
+        BigInt::from(value.into_big())
+    }
+}
+
+impl From<&Uint> for BigInt {
+    fn from(value: &Uint) -> Self {
+        BigInt::from(value.big().into_owned())
+    }
+}
+
+macro_rules! impl_try_into {
+    ($($t:ty),*) => {$(
+        impl TryFrom<&Uint> for $t {
+            type Error = ();
+
+            fn try_from(value: &Uint) -> Result<Self, ()> {
+                value.to_u128().and_then(|val| <$t>::try_from(val).ok()).ok_or(())
+            }
+        }
+
+        impl TryFrom<Uint> for $t {
+            type Error = ();
+
+            fn try_from(value: Uint) -> Result<Self, ()> {
+                <$t>::try_from(&value)
+            }
+        }
+    )*};
+}
+
+impl_try_into!(u32, u64, u128, usize);
+
+impl Sum for Uint {
+    fn sum<I: Iterator<Item = Uint>>(iter: I) -> Self {
+        iter.fold(Uint::ZERO, |acc, it| acc + it)
+    }
+}
+
+impl<'a> Sum<&'a Uint> for Uint {
+    fn sum<I: Iterator<Item = &'a Uint>>(iter: I) -> Self {
+        iter.fold(Uint::ZERO, |acc, it| acc + it)
+    }
+}
+
+impl Shr<u64> for &Uint {
+    type Output = Uint;
+
+    fn shr(self, rhs: u64) -> Uint {
+        match &self.0 {
+            Repr::Small(val) if rhs < 128 => Uint::from_u128(val >> rhs),
+            Repr::Small(_) => Uint::ZERO,
+            Repr::Big(val) => Uint::from_big(val >> rhs),
+        }
+    }
+}
+
+impl Shr<u64> for Uint {
+    type Output = Uint;
+
+    fn shr(self, rhs: u64) -> Uint {
+        &self >> rhs
+    }
+}
+
+impl Shl<u64> for &Uint {
+    type Output = Uint;
+
+    fn shl(self, rhs: u64) -> Uint {
+        if let Repr::Small(val) = self.0 {
+            if rhs < 128 && val.leading_zeros() as u64 >= rhs {
+                return Uint::from_u128(val << rhs);
+            }
+        }
+
+        Uint::from_big(&*self.big() << rhs)
+    }
+}
+
+impl Shl<u64> for Uint {
+    type Output = Uint;
+
+    fn shl(self, rhs: u64) -> Uint {
+        &self << rhs
+    }
+}
+
+impl PartialEq<BigUint> for Uint {
+    fn eq(&self, other: &BigUint) -> bool {
+        match &self.0 {
+            Repr::Small(val) => u128::try_from(other).is_ok_and(|other| *val == other),
+            Repr::Big(val) => val == other,
+        }
+    }
+}
+
+impl PartialEq<Uint> for BigUint {
+    fn eq(&self, other: &Uint) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd<BigUint> for Uint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(match &self.0 {
+            Repr::Small(val) => match u128::try_from(other) {
+                Ok(other) => val.cmp(&other),
+                Err(_) => Ordering::Less,
+            },
+            Repr::Big(val) => val.cmp(other),
+        })
+    }
+}
+
+impl PartialOrd<Uint> for BigUint {
+    fn partial_cmp(&self, other: &Uint) -> Option<Ordering> {
+        other.partial_cmp(self).map(Ordering::reverse)
+    }
+}
+
+/// `Uint` with `Uint`: on `u128` while the result fits, on `BigUint` otherwise
+macro_rules! impl_ops {
+    ($op:ident, $f:ident, $op_assign:ident, $f_assign:ident, $checked:ident) => {
+        impl $op<&Uint> for &Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: &Uint) -> Uint {
+                if let (Repr::Small(a), Repr::Small(b)) = (&self.0, &rhs.0) {
+                    if let Some(res) = a.$checked(*b) {
+                        return Uint::from_u128(res);
+                    }
+                }
+
+                Uint::from_big($op::$f(&*self.big(), &*rhs.big()))
+            }
+        }
+
+        impl $op<Uint> for &Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: Uint) -> Uint {
+                $op::$f(self, &rhs)
+            }
+        }
+
+        impl $op<&Uint> for Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: &Uint) -> Uint {
+                $op::$f(&self, rhs)
+            }
+        }
+
+        impl $op<Uint> for Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: Uint) -> Uint {
+                $op::$f(&self, &rhs)
+            }
+        }
+
+        impl $op_assign<&Uint> for Uint {
+            fn $f_assign(&mut self, rhs: &Uint) {
+                if let (Repr::Small(a), Repr::Small(b)) = (&mut self.0, &rhs.0) {
+                    if let Some(res) = a.$checked(*b) {
+                        *a = res;
+                        return;
+                    }
+                }
+
+                self.apply_big(|val| $op_assign::$f_assign(val, &*rhs.big()));
+            }
+        }
+
+        impl $op_assign<Uint> for Uint {
+            fn $f_assign(&mut self, rhs: Uint) {
+                $op_assign::$f_assign(self, &rhs)
+            }
+        }
+
+        impl_ops!(@scalar $op, $f, $op_assign, $f_assign, u32);
+        impl_ops!(@scalar $op, $f, $op_assign, $f_assign, u64);
+        impl_ops!(@scalar $op, $f, $op_assign, $f_assign, u128);
+
+        impl $op<&BigUint> for &Uint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: &BigUint) -> BigUint {
+                $op::$f(&*self.big(), rhs)
+            }
+        }
+
+        impl $op<BigUint> for &Uint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: BigUint) -> BigUint {
+                $op::$f(&*self.big(), rhs)
+            }
+        }
+
+        impl $op<&BigUint> for Uint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: &BigUint) -> BigUint {
+                $op::$f(self.into_big(), rhs)
+            }
+        }
+
+        impl $op<BigUint> for Uint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: BigUint) -> BigUint {
+                $op::$f(self.into_big(), rhs)
+            }
+        }
+
+        impl $op<&Uint> for &BigUint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: &Uint) -> BigUint {
+                $op::$f(self, &*rhs.big())
+            }
+        }
+
+        impl $op<Uint> for &BigUint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: Uint) -> BigUint {
+                $op::$f(self, rhs.into_big())
+            }
+        }
+
+        impl $op<&Uint> for BigUint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: &Uint) -> BigUint {
+                $op::$f(self, &*rhs.big())
+            }
+        }
+
+        impl $op<Uint> for BigUint {
+            type Output = BigUint;
+
+            fn $f(self, rhs: Uint) -> BigUint {
+                $op::$f(self, rhs.into_big())
+            }
+        }
+
+        impl $op_assign<&BigUint> for Uint {
+            fn $f_assign(&mut self, rhs: &BigUint) {
+                self.apply_big(|val| $op_assign::$f_assign(val, rhs))
+            }
+        }
+
+        impl $op_assign<BigUint> for Uint {
+            fn $f_assign(&mut self, rhs: BigUint) {
+                $op_assign::$f_assign(self, &rhs)
+            }
+        }
+
+        impl $op_assign<&Uint> for BigUint {
+            fn $f_assign(&mut self, rhs: &Uint) {
+                $op_assign::$f_assign(self, &*rhs.big())
+            }
+        }
+
+        impl $op_assign<Uint> for BigUint {
+            fn $f_assign(&mut self, rhs: Uint) {
+                $op_assign::$f_assign(self, rhs.into_big())
+            }
+        }
+    };
+    (@scalar $op:ident, $f:ident, $op_assign:ident, $f_assign:ident, $t:ty) => {
+        impl $op<$t> for &Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: $t) -> Uint {
+                $op::$f(self, &Uint::from(rhs))
+            }
+        }
+
+        impl $op<$t> for Uint {
+            type Output = Uint;
+
+            fn $f(self, rhs: $t) -> Uint {
+                $op::$f(&self, &Uint::from(rhs))
+            }
+        }
+
+        impl $op_assign<$t> for Uint {
+            fn $f_assign(&mut self, rhs: $t) {
+                $op_assign::$f_assign(self, &Uint::from(rhs))
+            }
+        }
+    };
+}
+
+impl_ops!(Add, add, AddAssign, add_assign, checked_add);
+impl_ops!(Sub, sub, SubAssign, sub_assign, checked_sub);
+impl_ops!(Mul, mul, MulAssign, mul_assign, checked_mul);
+impl_ops!(Div, div, DivAssign, div_assign, checked_div);
+impl_ops!(Rem, rem, RemAssign, rem_assign, checked_rem);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_inline_while_it_fits() {
+        let max = Uint::from(u128::MAX);
+        assert!(max.is_inline());
+
+        let above = &max + 1u64;
+        assert!(!above.is_inline());
+        assert_eq!(above, BigUint::from(u128::MAX) + 1u32);
+
+        // back inline once the result fits again
+        let back = &above - 1u64;
+        assert!(back.is_inline());
+        assert_eq!(back, max);
+
+        let mut acc = Uint::from(u64::MAX);
+        acc *= u64::MAX;
+        acc *= 4u64;
+        assert!(!acc.is_inline());
+        acc /= 8u64;
+        assert!(acc.is_inline());
+        assert_eq!(acc, BigUint::from(u64::MAX) * u64::MAX / 2u32);
+    }
+
+    #[test]
+    fn compares_numerically_across_representations() {
+        let small = Uint::from(u128::MAX);
+        let big = Uint::from(BigUint::from(u128::MAX) * 2u32);
+
+        assert!(small < big);
+        assert!(Uint::ZERO < small);
+        assert_eq!(Uint::from(BigUint::from(5u32)), Uint::from(5u64));
+        assert!(small < BigUint::from(u128::MAX) + 1u32);
+        assert!(BigUint::from(u128::MAX) < big);
+        assert_eq!(big.bits(), 129);
+        assert_eq!(small.bits(), 128);
+        assert_eq!(Uint::ZERO.bits(), 0);
+    }
+
+    #[test]
+    fn shifts_past_the_width() {
+        let small = Uint::from(u128::MAX);
+        let big = Uint::from(BigUint::from(u128::MAX) << 64u32);
+
+        assert_eq!(&small >> 127, Uint::from(1u64));
+        assert_eq!(&small >> 128, Uint::ZERO);
+        // not truncated to `u32`
+        assert_eq!(&small >> (1u64 << 32), Uint::ZERO);
+        assert_eq!(&big >> (1u64 << 32), Uint::ZERO);
+        assert_eq!(&big >> 64, small);
+        assert_eq!(&small << 64, big);
+    }
+
+    #[test]
+    #[should_panic]
+    fn underflow_traps() {
+        let _ = Uint::from(1u64) - Uint::from(2u64);
+    }
+}
@@ -0,0 +1,92 @@
+//! ERC-4626-style conversions between vault assets and shares. A virtual share and a virtual
+//! asset (one raw unit each) are added to the totals, which makes the first-depositor
+//! share-price inflation attack unprofitable. Rounding always favors the vault.
+
+use num_bigint::BigUint;
+
+use crate::{
+    c::ECs,
+    rounding::{div_rounded, RoundingMode},
+};
+
+fn to_shares(
+    assets: &BigUint,
+    total_assets: &BigUint,
+    total_shares: &BigUint,
+    mode: RoundingMode,
+) -> BigUint {
+    div_rounded(
+        &(assets * (total_shares + 1u32)),
+        &(total_assets + 1u32),
+        mode,
+    )
+}
+
+fn to_assets(
+    shares: &BigUint,
+    total_assets: &BigUint,
+    total_shares: &BigUint,
+    mode: RoundingMode,
+) -> BigUint {
+    div_rounded(
+        &(shares * (total_assets + 1u32)),
+        &(total_shares + 1u32),
+        mode,
+    )
+}
+
+/// Shares minted for depositing `assets`, rounded down
+pub fn convert_to_shares<const DA: usize, const DS: usize>(
+    assets: &ECs<DA>,
+    total_assets: &ECs<DA>,
+    total_shares: &ECs<DS>,
+) -> ECs<DS> {
+    ECs::new(to_shares(
+        &assets.val.big(),
+        &total_assets.val.big(),
+        &total_shares.val.big(),
+        RoundingMode::Down,
+    ))
+}
+
+/// Assets paid out for redeeming `shares`, rounded down
+pub fn convert_to_assets<const DA: usize, const DS: usize>(
+    shares: &ECs<DS>,
+    total_assets: &ECs<DA>,
+    total_shares: &ECs<DS>,
+) -> ECs<DA> {
+    ECs::new(to_assets(
+        &shares.val.big(),
+        &total_assets.val.big(),
+        &total_shares.val.big(),
+        RoundingMode::Down,
+    ))
+}
+
+/// Shares burned for withdrawing exactly `assets`, rounded up
+pub fn shares_for_withdraw<const DA: usize, const DS: usize>(
+    assets: &ECs<DA>,
+    total_assets: &ECs<DA>,
+    total_shares: &ECs<DS>,
+) -> ECs<DS> {
+    ECs::new(to_shares(
+        &assets.val.big(),
+        &total_assets.val.big(),
+        &total_shares.val.big(),
+        RoundingMode::Up,
+    ))
+}
+
+/// Assets required for minting exactly `shares`, rounded up
+pub fn assets_for_mint<const DA: usize, const DS: usize>(
+    shares: &ECs<DS>,
+    total_assets: &ECs<DA>,
+    total_shares: &ECs<DS>,
+) -> ECs<DA> {
+    ECs::new(to_assets(
+        &shares.val.big(),
+        &total_assets.val.big(),
+        &total_shares.val.big(),
+        RoundingMode::Up,
+    ))
+}
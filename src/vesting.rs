@@ -0,0 +1,145 @@
+use crate::c::ECs;
+
+/// Linear vesting of `total` over `duration` starting at `start`, with nothing vested before
+/// `start + cliff`. At the cliff everything accrued so far is released at once.
+/// Timestamps and durations are in arbitrary, but consistent units (e.g. seconds or nanos).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestingSchedule<const D: usize> {
+    pub start: u64,
+    pub cliff: u64,
+    pub duration: u64,
+    pub total: ECs<D>,
+}
+
+impl<const D: usize> VestingSchedule<D> {
+    /// Traps, if the cliff is longer than the duration
+    pub fn new(start: u64, cliff: u64, duration: u64, total: ECs<D>) -> Self {
+        if cliff > duration {
+            unreachable!("Cliff can't be longer than the vesting duration");
+        }
+
+        Self {
+            start,
+            cliff,
+            duration,
+            total,
+        }
+    }
+
+    /// Amount vested at `t`, rounded down. Never decreases as `t` grows
+    /// and never exceeds `total`.
+    pub fn vested_at(&self, t: u64) -> ECs<D> {
+        // a cliff past `u64::MAX` is never reached
+        match self.start.checked_add(self.cliff) {
+            Some(cliff_end) if t >= cliff_end => {}
+            _ => return ECs::zero(),
+        }
+
+        let elapsed = t - self.start;
+        if elapsed >= self.duration {
+            return self.total.clone();
+        }
+
+        ECs::new(&self.total.val * elapsed / self.duration)
+    }
+
+    /// Amount that can be claimed at `t`, given what was already claimed.
+    /// Zero, if `already_claimed` is above the vested amount.
+    pub fn claimable(&self, t: u64, already_claimed: &ECs<D>) -> ECs<D> {
+        let vested = self.vested_at(t);
+
+        if vested.val > already_claimed.val {
+            vested - already_claimed
+        } else {
+            ECs::zero()
+        }
+    }
+
+    /// Whether everything is vested at `t`
+    pub fn is_fully_vested(&self, t: u64) -> bool {
+        self.start
+            .checked_add(self.duration)
+            .is_some_and(|end| t >= end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::E8s;
+
+    fn schedule(cliff: u64, duration: u64) -> VestingSchedule<8> {
+        VestingSchedule::new(1_000, cliff, duration, E8s::new(1_000_000_000u64))
+    }
+
+    #[test]
+    fn cliff_boundary() {
+        let s = schedule(100, 400);
+
+        assert_eq!(s.vested_at(0), E8s::zero());
+        assert_eq!(s.vested_at(1_099), E8s::zero());
+        // everything accrued before the cliff is released at once
+        assert_eq!(s.vested_at(1_100), E8s::new(250_000_000u64));
+        assert_eq!(s.vested_at(1_399), E8s::new(997_500_000u64));
+        assert_eq!(s.vested_at(1_400), s.total);
+        assert_eq!(s.vested_at(u64::MAX), s.total);
+    }
+
+    #[test]
+    fn zero_duration_vests_at_start() {
+        let s = schedule(0, 0);
+
+        assert_eq!(s.vested_at(999), E8s::zero());
+        assert_eq!(s.vested_at(1_000), s.total);
+        assert!(!s.is_fully_vested(999));
+        assert!(s.is_fully_vested(1_000));
+    }
+
+    #[test]
+    fn vested_is_monotonic_and_rounds_down() {
+        let s = VestingSchedule::new(0, 0, 3, E8s::new(10u64));
+
+        assert_eq!(s.vested_at(1), E8s::new(3u64));
+        assert_eq!(s.vested_at(2), E8s::new(6u64));
+        assert_eq!(s.vested_at(3), E8s::new(10u64));
+
+        let s = schedule(50, 997);
+        let mut prev = E8s::zero();
+        for t in 900..2_100 {
+            let v = s.vested_at(t);
+            assert!(v >= prev && v <= s.total);
+            prev = v;
+        }
+    }
+
+    #[test]
+    fn claimable_never_underflows() {
+        let s = schedule(0, 400);
+
+        assert_eq!(s.claimable(1_200, &E8s::zero()), E8s::new(500_000_000u64));
+        assert_eq!(
+            s.claimable(1_200, &E8s::new(200_000_000u64)),
+            E8s::new(300_000_000u64)
+        );
+        assert_eq!(s.claimable(1_200, &E8s::new(600_000_000u64)), E8s::zero());
+        assert_eq!(s.claimable(1_400, &s.total), E8s::zero());
+    }
+
+    #[test]
+    fn start_near_u64_max() {
+        let s = VestingSchedule::new(u64::MAX - 10, 20, 30, E8s::new(100u64));
+
+        // neither the cliff nor the end are ever reached
+        assert_eq!(s.vested_at(u64::MAX), E8s::zero());
+        assert!(!s.is_fully_vested(u64::MAX));
+
+        let s = VestingSchedule::new(u64::MAX - 10, 0, 30, E8s::new(100u64));
+        assert_eq!(s.vested_at(u64::MAX), E8s::new(33u64));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cliff can't be longer than the vesting duration")]
+    fn cliff_longer_than_duration_traps() {
+        schedule(500, 400);
+    }
+}
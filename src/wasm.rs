@@ -0,0 +1,246 @@
+//! JS bindings, so web frontends compute amounts with exactly the same fixed-point math (and
+//! rounding) as canisters, instead of floats. Errors are returned as JS strings instead of traps.
+//!
+//! ```js
+//! const amount = new E8s("1.5");
+//! amount.mul(new E8s("0.3")).toString(); // "0.45000000"
+//! ```
+
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::str::FromStr;
+
+use num_bigint::BigUint;
+use wasm_bindgen::prelude::*;
+
+use crate::{c::E8s, d::EDs};
+
+fn parse_raw(raw: &str) -> Result<BigUint, String> {
+    if raw.is_empty() || !raw.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("Invalid raw value: '{}'", raw));
+    }
+
+    // can't fail - only ascii digits
+    Ok(BigUint::parse_bytes(raw.as_bytes(), 10).unwrap())
+}
+
+/// `E8s` for JS
+#[wasm_bindgen(js_name = E8s)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsE8s(E8s);
+
+#[wasm_bindgen(js_class = E8s)]
+impl JsE8s {
+    /// From a decimal string, e.g. "1.5"
+    #[wasm_bindgen(constructor)]
+    pub fn new(s: &str) -> Result<JsE8s, String> {
+        E8s::from_str(s).map(Self)
+    }
+
+    /// From a string of raw units, e.g. "150000000"
+    #[wasm_bindgen(js_name = fromRaw)]
+    pub fn from_raw(raw: &str) -> Result<JsE8s, String> {
+        parse_raw(raw).map(|val| Self(E8s::new(val)))
+    }
+
+    pub fn zero() -> JsE8s {
+        Self(E8s::zero())
+    }
+
+    pub fn one() -> JsE8s {
+        Self(E8s::one())
+    }
+
+    /// Raw units as a string, e.g. "150000000"
+    #[wasm_bindgen(js_name = toRaw)]
+    pub fn to_raw(&self) -> String {
+        self.0.val.to_string()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+
+    #[wasm_bindgen(js_name = isZero)]
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn add(&self, other: &JsE8s) -> JsE8s {
+        Self(&self.0 + &other.0)
+    }
+
+    /// Fails, if the result would be negative
+    pub fn sub(&self, other: &JsE8s) -> Result<JsE8s, String> {
+        if self.0 < other.0 {
+            return Err(format!("Can't subtract {} from {}", other.0, self.0));
+        }
+
+        Ok(Self(&self.0 - &other.0))
+    }
+
+    pub fn mul(&self, other: &JsE8s) -> JsE8s {
+        Self(&self.0 * &other.0)
+    }
+
+    pub fn div(&self, other: &JsE8s) -> Result<JsE8s, String> {
+        if other.0.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+
+        Ok(Self(&self.0 / &other.0))
+    }
+
+    /// -1, 0 or 1
+    pub fn compare(&self, other: &JsE8s) -> i32 {
+        self.0.cmp(&other.0) as i32
+    }
+
+    pub fn equals(&self, other: &JsE8s) -> bool {
+        self.0 == other.0
+    }
+
+    #[wasm_bindgen(js_name = toDynamic)]
+    pub fn to_dynamic(&self) -> JsEDs {
+        JsEDs(self.0.clone().to_dynamic())
+    }
+}
+
+/// `EDs` for JS, for tokens with decimal points other than 8
+#[wasm_bindgen(js_name = EDs)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsEDs(EDs);
+
+#[wasm_bindgen(js_class = EDs)]
+impl JsEDs {
+    /// From a decimal string, e.g. "1.5", with exactly `decimals` decimal points
+    #[wasm_bindgen(constructor)]
+    pub fn new(s: &str, decimals: u8) -> Result<JsEDs, String> {
+        EDs::from_str_d(s, decimals).map(Self)
+    }
+
+    /// From a string of raw units, e.g. "1500000" with 6 decimals
+    #[wasm_bindgen(js_name = fromRaw)]
+    pub fn from_raw(raw: &str, decimals: u8) -> Result<JsEDs, String> {
+        parse_raw(raw).map(|val| Self(EDs::new(val, decimals)))
+    }
+
+    pub fn zero(decimals: u8) -> JsEDs {
+        Self(EDs::zero(decimals))
+    }
+
+    pub fn one(decimals: u8) -> JsEDs {
+        Self(EDs::one(decimals))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn decimals(&self) -> u8 {
+        self.0.decimals
+    }
+
+    /// Raw units as a string
+    #[wasm_bindgen(js_name = toRaw)]
+    pub fn to_raw(&self) -> String {
+        self.0.val.to_string()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.0.to_string()
+    }
+
+    #[wasm_bindgen(js_name = isZero)]
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Rescales to the given decimal points, truncating
+    #[wasm_bindgen(js_name = toDecimals)]
+    pub fn to_decimals(&self, decimals: u8) -> JsEDs {
+        Self(self.0.clone().to_decimals(decimals))
+    }
+
+    pub fn add(&self, other: &JsEDs) -> Result<JsEDs, String> {
+        self.check_decimals(other)?;
+
+        Ok(Self(&self.0 + &other.0))
+    }
+
+    /// Fails, if the result would be negative
+    pub fn sub(&self, other: &JsEDs) -> Result<JsEDs, String> {
+        self.check_decimals(other)?;
+
+        if self.0 < other.0 {
+            return Err(format!("Can't subtract {} from {}", other.0, self.0));
+        }
+
+        Ok(Self(&self.0 - &other.0))
+    }
+
+    pub fn mul(&self, other: &JsEDs) -> Result<JsEDs, String> {
+        self.check_decimals(other)?;
+
+        Ok(Self(&self.0 * &other.0))
+    }
+
+    pub fn div(&self, other: &JsEDs) -> Result<JsEDs, String> {
+        self.check_decimals(other)?;
+
+        if other.0.is_zero() {
+            return Err("Division by zero".to_string());
+        }
+
+        Ok(Self(&self.0 / &other.0))
+    }
+
+    /// -1, 0 or 1
+    pub fn compare(&self, other: &JsEDs) -> Result<i32, String> {
+        self.check_decimals(other)?;
+
+        Ok(self.0.cmp(&other.0) as i32)
+    }
+
+    pub fn equals(&self, other: &JsEDs) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl JsEDs {
+    fn check_decimals(&self, other: &JsEDs) -> Result<(), String> {
+        if self.0.decimals != other.0.decimals {
+            return Err(format!(
+                "Incompatible decimal points: {} and {}",
+                self.0.decimals, other.0.decimals
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl From<E8s> for JsE8s {
+    fn from(value: E8s) -> Self {
+        Self(value)
+    }
+}
+
+impl From<JsE8s> for E8s {
+    fn from(value: JsE8s) -> Self {
+        value.0
+    }
+}
+
+impl From<EDs> for JsEDs {
+    fn from(value: EDs) -> Self {
+        Self(value)
+    }
+}
+
+impl From<JsEDs> for EDs {
+    fn from(value: JsEDs) -> Self {
+        value.0
+    }
+}
@@ -0,0 +1,135 @@
+use core::borrow::Borrow;
+
+use num_bigint::BigUint;
+
+use crate::{c::ECs, d::EDs, rescale};
+
+/// Exchange rate as returned by the Exchange Rate Canister: the amount of the quote asset
+/// per one base asset, as a raw integer with its own decimal points
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct XrcRate {
+    pub rate: u64,
+    pub decimals: u32,
+}
+
+impl XrcRate {
+    pub fn new(rate: u64, decimals: u32) -> Self {
+        Self { rate, decimals }
+    }
+
+    fn apply_raw(&self, val: &BigUint, from_decimals: u32, to_decimals: u32) -> BigUint {
+        rescale(
+            val * self.rate,
+            (from_decimals + self.decimals) as usize,
+            to_decimals as usize,
+        )
+    }
+
+    /// Converts an amount of the base asset into an amount of the quote asset, rounding down only once
+    pub fn convert<const D: usize, const D1: usize>(&self, amount: &ECs<D>) -> ECs<D1> {
+        ECs::new(self.apply_raw(&amount.val.big(), D as u32, D1 as u32))
+    }
+
+    pub fn convert_d(&self, amount: &EDs, to_decimals: u8) -> EDs {
+        EDs::new(
+            self.apply_raw(
+                &amount.val.big(),
+                amount.decimals as u32,
+                to_decimals as u32,
+            ),
+            to_decimals,
+        )
+    }
+}
+
+/// Total value of amounts of different tokens in the common quote asset, with `decimals`
+/// decimal points. Each entry is converted through its own rate and rounded down once.
+pub fn aggregate_to<A: Borrow<EDs>>(
+    decimals: u8,
+    entries: impl IntoIterator<Item = (A, XrcRate)>,
+) -> EDs {
+    let total = entries
+        .into_iter()
+        .fold(BigUint::ZERO, |acc, (amount, rate)| {
+            let amount = amount.borrow();
+
+            acc + rate.apply_raw(&amount.val.big(), amount.decimals as u32, decimals as u32)
+        });
+
+    EDs::new(total, decimals)
+}
+
+#[cfg(feature = "ic-xrc-types")]
+impl From<&ic_xrc_types::ExchangeRate> for XrcRate {
+    fn from(value: &ic_xrc_types::ExchangeRate) -> Self {
+        Self::new(value.rate, value.metadata.decimals)
+    }
+}
+
+#[cfg(feature = "ic-xrc-types")]
+impl From<ic_xrc_types::ExchangeRate> for XrcRate {
+    fn from(value: ic_xrc_types::ExchangeRate) -> Self {
+        Self::from(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::{E6s, E8s};
+
+    // 12.345678901 USD per ICP
+    const ICP_USD: XrcRate = XrcRate {
+        rate: 12_345_678_901,
+        decimals: 9,
+    };
+
+    #[test]
+    fn converts_with_both_decimals() {
+        let icp = E8s::new(150_000_000u64);
+
+        // 18.5185183515, rounded down once
+        let usd: E6s = ICP_USD.convert(&icp);
+        assert_eq!(usd, E6s::new(18_518_518u64));
+
+        // nothing is lost with enough decimal points
+        let usd: ECs<18> = ICP_USD.convert(&icp);
+        assert_eq!(usd, ECs::new(18_518_518_351_500_000_000u128));
+        let usd: ECs<18> = ICP_USD.convert(&E8s::new(1u64));
+        assert_eq!(usd, ECs::new(123_456_789_010u64));
+
+        assert_eq!(
+            ICP_USD.convert_d(&EDs::new(150_000_000u64, 8), 6),
+            EDs::new(18_518_518u64, 6)
+        );
+        assert_eq!(ICP_USD.convert_d(&EDs::new(1u64, 8), 6), EDs::zero(6));
+    }
+
+    #[test]
+    fn rates_without_decimals() {
+        let rate = XrcRate::new(3, 0);
+
+        let converted: E8s = rate.convert(&E8s::new(5u64));
+        assert_eq!(converted, E8s::new(15u64));
+        assert_eq!(rate.convert_d(&EDs::new(5u64, 0), 2), EDs::new(1_500u64, 2));
+    }
+
+    #[test]
+    fn aggregates_owned_and_borrowed_amounts() {
+        let icp = EDs::new(150_000_000u64, 8);
+        let btc = EDs::new(1_000u64, 8);
+        // 60000.5 USD per BTC
+        let btc_usd = XrcRate::new(600_005, 1);
+
+        // 18.5185183515 + 0.600005, rounded down once per entry
+        let total = aggregate_to(6, [(&icp, ICP_USD), (&btc, btc_usd)]);
+        assert_eq!(total, EDs::new(19_118_523u64, 6));
+        assert_eq!(total.decimals, 6);
+
+        let owned = aggregate_to(6, alloc::vec![(icp, ICP_USD), (btc, btc_usd)]);
+        assert_eq!(owned, total);
+
+        let empty: [(EDs, XrcRate); 0] = [];
+        assert_eq!(aggregate_to(6, empty), EDs::zero(6));
+    }
+}